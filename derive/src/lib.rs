@@ -0,0 +1,310 @@
+//! Derive macros for the [`phy`](https://docs.rs/phy) crate.
+//!
+//! Use them through the `derive` feature of `phy` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, Ident, Member, PathArguments, Result, Type,
+    TypeParamBound, WherePredicate, parse_macro_input, parse_quote, spanned::Spanned,
+};
+
+/// Derive `phy::System`, generating `visit_vars` for all fields of a structure.
+///
+/// Each field is visited with `phy::Vars`, which is implemented for `Var` and for
+/// structures deriving `Vars` or `System`, so that subsystems can be nested.
+/// Fields which are not variables (e.g. system constants) must be marked with
+/// `#[phy(skip)]`, so that a variable cannot be forgotten silently.
+///
+/// Derivatives are computed by `compute_derivs` of the `phy::Dynamics` trait, which
+/// must be implemented manually. A structure deriving `System` also implements `Vars`.
+///
+/// # Attributes
+///
+/// On fields:
+///
+/// + `#[phy(skip)]` - do not visit the field.
+/// + `#[phy(pair = vel)]` - visit the field as a position together with its
+///   velocity field `vel` using `Visitor::apply_pair`, required by symplectic solvers.
+///
+/// On the structure:
+///
+/// + `#[phy(solver = Rk4)]` - solver type. By default the type parameter bound by
+///   `Solver` is used.
+/// + `#[phy(scalar = f64)]` - scalar type. By default it is taken from the `Solver`
+///   bound or is `f32`.
+///
+/// # Example
+///
+/// ```
+/// use phy::{Dynamics, Rk4, Solver, System, Var, Vars};
+///
+/// #[derive(Vars)]
+/// struct Body<S: Solver> {
+///     #[phy(pair = vel)]
+///     pos: Var<f32, S>,
+///     vel: Var<f32, S>,
+/// }
+///
+/// #[derive(System)]
+/// struct Oscillator<S: Solver> {
+///     body: Body<S>,
+///     #[phy(skip)]
+///     stiffness: f32,
+/// }
+///
+/// impl<S: Solver> Dynamics<S> for Oscillator<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.body.pos.deriv = *self.body.vel;
+///         self.body.vel.deriv = -self.stiffness * *self.body.pos;
+///     }
+/// }
+///
+/// let mut system = Oscillator::<Rk4> {
+///     body: Body { pos: Var::new(1.0), vel: Var::new(0.0) },
+///     stiffness: 1.0,
+/// };
+/// let mut t = 0.0;
+/// for _ in 0..1000 {
+///     Rk4.solve_step(&mut system, &mut t, 0.01);
+/// }
+/// assert!((*system.body.pos - t.cos()).abs() < 1e-4);
+/// ```
+#[proc_macro_derive(System, attributes(phy))]
+pub fn derive_system(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, true)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `phy::Vars` for a collection of variables.
+///
+/// Used for parts of a system which are nested into other systems.
+/// Accepts the same attributes as `#[derive(System)]`.
+#[proc_macro_derive(Vars, attributes(phy))]
+pub fn derive_vars(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, false)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Options set on the structure.
+#[derive(Default)]
+struct Options {
+    solver: Option<Type>,
+    scalar: Option<Type>,
+}
+
+/// Field of the structure with its options.
+struct Field {
+    member: Member,
+    ty: Type,
+    skip: bool,
+    pair: Option<Ident>,
+}
+
+fn parse_options(input: &DeriveInput) -> Result<Options> {
+    let mut options = Options::default();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("phy")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("solver") {
+                options.solver = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("scalar") {
+                options.scalar = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown phy attribute, expected `solver` or `scalar`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn parse_fields(input: &DeriveInput) -> Result<Vec<Field>> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "phy derive macros support only structs",
+            ));
+        }
+    };
+    let mut result = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let mut parsed = Field {
+            member,
+            ty: field.ty.clone(),
+            skip: false,
+            pair: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("phy")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("pair") {
+                    parsed.pair = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown phy attribute, expected `skip` or `pair`"));
+                }
+                Ok(())
+            })?;
+        }
+        if parsed.skip && parsed.pair.is_some() {
+            return Err(Error::new(
+                field.span(),
+                "field cannot be both skipped and paired",
+            ));
+        }
+        result.push(parsed);
+    }
+    if let Fields::Unnamed(_) = fields
+        && let Some(field) = result.iter().find(|f| f.pair.is_some())
+    {
+        return Err(Error::new(
+            field.ty.span(),
+            "`pair` is supported only for named fields",
+        ));
+    }
+    Ok(result)
+}
+
+/// Scalar type of the `Solver` bound, if there is one among `bounds`.
+fn solver_bound<'a>(bounds: impl IntoIterator<Item = &'a TypeParamBound>) -> Option<Option<Type>> {
+    bounds.into_iter().find_map(|bound| match bound {
+        TypeParamBound::Trait(bound) => {
+            let segment = bound.path.segments.last()?;
+            if segment.ident != "Solver" {
+                return None;
+            }
+            let scalar = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty.clone()),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            Some(scalar)
+        }
+        _ => None,
+    })
+}
+
+/// Find the solver type parameter and the scalar type from its `Solver` bound.
+fn find_solver(input: &DeriveInput) -> Option<(Type, Option<Type>)> {
+    for param in input.generics.type_params() {
+        if let Some(scalar) = solver_bound(&param.bounds) {
+            let ident = &param.ident;
+            return Some((parse_quote!(#ident), scalar));
+        }
+    }
+    for predicate in input
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|w| &w.predicates)
+    {
+        if let WherePredicate::Type(predicate) = predicate
+            && let Some(scalar) = solver_bound(&predicate.bounds)
+        {
+            return Some((predicate.bounded_ty.clone(), scalar));
+        }
+    }
+    None
+}
+
+fn visit_body(fields: &[Field], solver: &Type, scalar: &Type) -> Result<TokenStream2> {
+    let paired: Vec<&Ident> = fields.iter().filter_map(|f| f.pair.as_ref()).collect();
+    let mut body = TokenStream2::new();
+    for field in fields {
+        if field.skip {
+            continue;
+        }
+        let member = &field.member;
+        if let Member::Named(ident) = member
+            && paired.contains(&ident)
+        {
+            continue;
+        }
+        match &field.pair {
+            Some(pair) => {
+                let target = fields
+                    .iter()
+                    .find(|f| matches!(&f.member, Member::Named(ident) if ident == pair))
+                    .ok_or_else(|| Error::new(pair.span(), "no such field to pair with"))?;
+                if target.skip
+                    || target.pair.is_some()
+                    || paired.iter().filter(|p| **p == pair).count() > 1
+                {
+                    return Err(Error::new(
+                        pair.span(),
+                        "paired field must not be skipped or paired with another field",
+                    ));
+                }
+                body.extend(quote_spanned! { field.ty.span() =>
+                    visitor.apply_pair(&mut self.#member, &mut self.#pair);
+                });
+            }
+            None => {
+                let ty = &field.ty;
+                body.extend(quote_spanned! { ty.span() =>
+                    <#ty as ::phy::Vars<#solver, #scalar>>::visit_vars(&mut self.#member, visitor);
+                });
+            }
+        }
+    }
+    Ok(body)
+}
+
+fn expand(input: &DeriveInput, system: bool) -> Result<TokenStream2> {
+    let options = parse_options(input)?;
+    let fields = parse_fields(input)?;
+
+    let (solver, bound_scalar) = match options.solver {
+        Some(solver) => (solver, None),
+        None => find_solver(input).ok_or_else(|| {
+            Error::new(
+                input.ident.span(),
+                "cannot find type parameter bound by `Solver`, specify solver with `#[phy(solver = ...)]`",
+            )
+        })?,
+    };
+    let scalar = options
+        .scalar
+        .or(bound_scalar)
+        .unwrap_or_else(|| parse_quote!(f32));
+
+    let body = visit_body(&fields, &solver, &scalar)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut output = quote! {
+        impl #impl_generics ::phy::Vars<#solver, #scalar> for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn visit_vars<__V: ::phy::Visitor<#solver, #scalar>>(&mut self, visitor: &mut __V) {
+                #body
+            }
+        }
+    };
+    if system {
+        output.extend(quote! {
+            impl #impl_generics ::phy::System<#solver, #scalar> for #ident #ty_generics #where_clause {
+                fn compute_derivs(&mut self, ctx: &<#solver as ::phy::Solver<#scalar>>::Context) {
+                    <Self as ::phy::Dynamics<#solver, #scalar>>::compute_derivs(self, ctx)
+                }
+
+                fn visit_vars<__V: ::phy::Visitor<#solver, #scalar>>(&mut self, visitor: &mut __V) {
+                    <Self as ::phy::Vars<#solver, #scalar>>::visit_vars(self, visitor)
+                }
+            }
+        });
+    }
+    Ok(output)
+}
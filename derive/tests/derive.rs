@@ -0,0 +1,221 @@
+//! Tests for derived `System` and `Vars` implementations.
+
+use glam::{DVec2, Vec2};
+use phy::{Dynamics, Param, Rk4, Solver, System, Var, Vars, Verlet, Visitor};
+
+/// Visitor counting variables and pairs it was applied to.
+#[derive(Default)]
+struct Counter {
+    vars: usize,
+    pairs: usize,
+}
+
+impl<S: Solver<T>, T: phy::Scalar> Visitor<S, T> for Counter {
+    fn apply<P: Param<Scalar = T>>(&mut self, _: &mut Var<P, S>) {
+        self.vars += 1;
+    }
+
+    fn apply_pair<P: Param<Scalar = T>>(&mut self, _: &mut Var<P, S>, _: &mut Var<P::Deriv, S>)
+    where
+        P::Deriv: Param<Scalar = T>,
+    {
+        self.pairs += 1;
+    }
+}
+
+/// Particle on a spring, with hand-written `visit_vars` as a reference.
+struct ManualSpring<S: Solver> {
+    pos: Var<Vec2, S>,
+    vel: Var<Vec2, S>,
+    stiffness: f32,
+}
+
+impl<S: Solver> System<S> for ManualSpring<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -self.stiffness * *self.pos;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+/// The same particle with derived `visit_vars`.
+#[derive(System)]
+struct Spring<S: Solver> {
+    #[phy(pair = vel)]
+    pos: Var<Vec2, S>,
+    vel: Var<Vec2, S>,
+    #[phy(skip)]
+    stiffness: f32,
+}
+
+impl<S: Solver> Dynamics<S> for Spring<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -self.stiffness * *self.pos;
+    }
+}
+
+/// Point mass, a part of larger systems.
+#[derive(Vars)]
+struct Body<S>
+where
+    S: Solver,
+{
+    pos: Var<Vec2, S>,
+    vel: Var<Vec2, S>,
+}
+
+impl<S: Solver> Body<S> {
+    fn new(pos: Vec2) -> Self {
+        Self {
+            pos: Var::new(pos),
+            vel: Var::default(),
+        }
+    }
+}
+
+/// Two bodies connected with a spring, nesting other collections of variables.
+#[derive(System)]
+struct Dumbbell<S: Solver> {
+    bodies: Bodies<S>,
+    angle: Var<f32, S>,
+    #[phy(skip)]
+    unused: Var<f32, S>,
+}
+
+#[derive(Vars)]
+struct Bodies<S: Solver>(Body<S>, Body<S>);
+
+impl<S: Solver> Dynamics<S> for Dumbbell<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        let Bodies(a, b) = &mut self.bodies;
+        let force = *b.pos - *a.pos;
+        a.pos.deriv = *a.vel;
+        b.pos.deriv = *b.vel;
+        a.vel.deriv = force;
+        b.vel.deriv = -force;
+        self.angle.deriv = 1.0;
+        self.unused.deriv = 1.0;
+    }
+}
+
+/// Orbit in double precision with concrete solver.
+#[derive(System)]
+#[phy(solver = Rk4, scalar = f64)]
+struct Orbit {
+    pos: Var<DVec2, Rk4>,
+    vel: Var<DVec2, Rk4>,
+}
+
+impl Dynamics<Rk4, f64> for Orbit {
+    fn compute_derivs(&mut self, _: &<Rk4 as Solver<f64>>::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+    }
+}
+
+/// Scalar type is taken from the `Solver` bound.
+#[derive(System)]
+struct Decay<S: Solver<f64>> {
+    x: Var<f64, S>,
+}
+
+impl<S: Solver<f64>> Dynamics<S, f64> for Decay<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = -*self.x;
+    }
+}
+
+/// Test that skipped fields are not visited and nested fields are.
+#[test]
+fn test_visited_fields() {
+    let mut counter = Counter::default();
+    let mut spring = Spring::<Rk4> {
+        pos: Var::new(Vec2::X),
+        vel: Var::default(),
+        stiffness: 1.0,
+    };
+    System::visit_vars(&mut spring, &mut counter);
+    assert_eq!((counter.vars, counter.pairs), (0, 1));
+
+    let mut counter = Counter::default();
+    let mut dumbbell = Dumbbell::<Rk4> {
+        bodies: Bodies(Body::new(Vec2::ZERO), Body::new(Vec2::X)),
+        angle: Var::default(),
+        unused: Var::default(),
+    };
+    System::visit_vars(&mut dumbbell, &mut counter);
+    assert_eq!((counter.vars, counter.pairs), (5, 0));
+}
+
+/// Test that derived and hand-written systems are integrated identically.
+#[test]
+fn test_derived_matches_manual() {
+    fn check<S: Solver + Default>() {
+        let solver = S::default();
+        let mut manual = ManualSpring::<S> {
+            pos: Var::new(Vec2::new(1.0, 0.5)),
+            vel: Var::new(Vec2::new(0.0, 1.0)),
+            stiffness: 4.0,
+        };
+        let mut derived = Spring::<S> {
+            pos: Var::new(Vec2::new(1.0, 0.5)),
+            vel: Var::new(Vec2::new(0.0, 1.0)),
+            stiffness: 4.0,
+        };
+        let (mut t_manual, mut t_derived) = (0.0, 0.0);
+        for _ in 0..100 {
+            solver.solve_step(&mut manual, &mut t_manual, 0.01);
+            solver.solve_step(&mut derived, &mut t_derived, 0.01);
+        }
+        assert_eq!(*manual.pos, *derived.pos);
+        assert_eq!(*manual.vel, *derived.vel);
+    }
+
+    check::<Rk4>();
+    check::<Verlet>();
+}
+
+/// Test that nested variables are integrated while skipped ones are frozen.
+#[test]
+fn test_nested_integration() {
+    let mut system = Dumbbell::<Rk4> {
+        bodies: Bodies(Body::new(Vec2::ZERO), Body::new(Vec2::X)),
+        angle: Var::default(),
+        unused: Var::default(),
+    };
+    let mut t = 0.0;
+    for _ in 0..100 {
+        Rk4.solve_step(&mut system, &mut t, 0.01);
+    }
+    let Bodies(a, b) = &system.bodies;
+    // Center of mass stays in place, the spring contracts
+    assert!((*a.pos + *b.pos - Vec2::X).length() < 1e-5);
+    assert!((*b.pos - *a.pos).length() < 1.0);
+    assert!((*system.angle - t).abs() < 1e-5);
+    assert_eq!(*system.unused, 0.0);
+}
+
+/// Test derived systems with double precision scalar.
+#[test]
+fn test_f64_systems() {
+    let mut orbit = Orbit {
+        pos: Var::new(DVec2::X),
+        vel: Var::new(DVec2::Y),
+    };
+    let mut t = 0.0f64;
+    for _ in 0..1000 {
+        Rk4.solve_step(&mut orbit, &mut t, 1e-3);
+    }
+    assert!((*orbit.pos - DVec2::new(t.cos(), t.sin())).length() < 1e-10);
+
+    let mut decay = Decay::<Rk4> { x: Var::new(1.0) };
+    let mut t = 0.0f64;
+    for _ in 0..1000 {
+        Rk4.solve_step(&mut decay, &mut t, 1e-3);
+    }
+    assert!((*decay.x - (-t).exp()).abs() < 1e-10);
+}
@@ -1,43 +1,45 @@
-//! Bouncing ball simulation with ground contact modeled as spring-damper.
+//! Bouncing ball simulation with ground impacts detected as events.
 //!
 //! This example simulates a ball under gravity that bounces when it hits the ground.
-//! The ground contact is modeled as a spring-damper system that activates only
-//! when the ball is below the ground level (position < 0).
+//! The impact is modeled as an instantaneous velocity reversal: the ground height
+//! is an event function, and the moment it crosses zero is located within a step.
 //!
 //! Physics equations:
 //!   dx/dt = v
-//!   dv/dt = -g + (k - f*v) * max(0, -x)   (spring-damper activates when x < 0)
+//!   dv/dt = -g
+//!
+//! Impact (when x crosses zero downwards):
+//!   v -> -e*v
 //!
 //! Where:
 //!   g = 9.8 m/s²  (gravity)
-//!   k = 1000 N/m  (spring stiffness)
-//!   f = 100 N·s/m (damping coefficient)
+//!   e = 0.8       (coefficient of restitution)
 //!
 //! The visualization shows:
 //!   - Numerical position and velocity
 //!   - Visual trajectory with '*' representing the ball's position along a line
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{Event, EventLocator, EventSystem, Rk4, Solver, System, Var, Visitor};
 use std::fmt::{self, Display, Formatter};
 
+#[derive(Clone)]
 struct BouncingBall<S: Solver> {
     pos: Var<f32, S>,
     vel: Var<f32, S>,
+    resting: bool, // ball lies on the ground after bounces died out
 }
 
 const G: f32 = 9.8; // gravitational acceleration (m/s²)
-const K: f32 = 1000.0; // ground spring stiffness (N/m)
-const F: f32 = 100.0; // ground damping coefficient (N·s/m)
+const E: f32 = 0.8; // coefficient of restitution
+const V_REST: f32 = 0.1; // impact velocity below which the ball stops bouncing (m/s)
 
 impl<S: Solver> System<S> for BouncingBall<S> {
     fn compute_derivs(&mut self, _: &S::Context) {
         // Position derivative: dx/dt = v
         self.pos.deriv = *self.vel;
 
-        // Velocity derivative: dv/dt = -g + contact_force
-        // Contact force activates only when ball is below ground (pos < 0)
-        // Force = (K - F*v) * (-pos)  [spring with velocity-dependent damping]
-        self.vel.deriv += -G + (K - F * *self.vel) * (-self.pos.min(0.0));
+        // Velocity derivative: dv/dt = -g (ground holds the ball when resting)
+        self.vel.deriv = if self.resting { 0.0 } else { -G };
     }
 
     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
@@ -46,6 +48,29 @@ impl<S: Solver> System<S> for BouncingBall<S> {
     }
 }
 
+impl<S: Solver> EventSystem<S> for BouncingBall<S> {
+    fn event_count(&self) -> usize {
+        1
+    }
+
+    fn event(&self, _: usize, _: f32) -> Event {
+        // Ball reaches the ground while falling
+        Event::new(*self.pos).falling()
+    }
+
+    fn handle_event(&mut self, _: usize, _: f32) {
+        if self.vel.abs() < V_REST {
+            // Too slow to bounce again, put the ball on the ground
+            self.resting = true;
+            *self.pos = 0.0;
+            *self.vel = 0.0;
+        } else {
+            // Reverse velocity losing some energy
+            *self.vel *= -E;
+        }
+    }
+}
+
 impl<S: Solver> Display for BouncingBall<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         // Print numerical state: position and velocity
@@ -72,13 +97,14 @@ fn main() {
     let mut system = BouncingBall {
         pos: Var::new(10.0),
         vel: Var::new(0.0),
+        resting: false,
     };
+    let mut locator = EventLocator::default();
 
-    // Simulation loop: 40 frames with 10 RK4 steps per frame (dt=0.01 each)
-    for _ in 0..40 {
-        for _ in 0..10 {
-            solver.solve_step(&mut system, 0.01);
-        }
+    let mut t = 0.0;
+    // Simulation loop: 40 frames of 0.1 s with RK4 steps of dt=0.01, cut short at impacts
+    for frame in 1..=40 {
+        locator.integrate(&solver, &mut system, &mut t, 0.1 * frame as f32, 0.01);
         println!("{}", system);
     }
 }
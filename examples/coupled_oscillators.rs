@@ -6,17 +6,15 @@
 //! The visualization shows:
 //!   - Numerical positions and velocities for both masses
 //!   - Visual trajectory with '*' for mass1 and '#' for mass2 on same line
+//!   - Relative drift of the total energy at the end
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{EnergyMonitor, Hamiltonian, Rk4, Solver, System, Var, Visitor};
 use std::fmt::{self, Display, Formatter};
 
 struct CoupledOscillators<S: Solver> {
-    m1: f32,         // mass 1
-    x1: Var<f32, S>, // position of mass 1
-    v1: Var<f32, S>, // velocity of mass 1
-    m2: f32,         // mass 2
-    x2: Var<f32, S>, // position of mass 2
-    v2: Var<f32, S>, // velocity of mass 2
+    m: [f32; 2],         // masses
+    x: Var<[f32; 2], S>, // positions of masses
+    v: Var<[f32; 2], S>, // velocities of masses
 }
 
 const WALL: f32 = 3.0; // walls offset
@@ -26,32 +24,49 @@ const L: f32 = 2.0; // spring equilibrium length
 
 impl<S: Solver> System<S> for CoupledOscillators<S> {
     fn compute_derivs(&mut self, _: &S::Context) {
-        // Mass 1: dx1/dt = v1
-        self.x1.deriv = *self.v1;
-        self.v1.deriv = -K / self.m1
-            * [
-                *self.x1 + WALL - L,     // left wall
-                *self.x1 - *self.x2 + L, // mass 2
-            ]
-            .into_iter()
-            .sum::<f32>();
-
-        // Mass 2: dx2/dt = v2
-        self.x2.deriv = *self.v2;
-        self.v2.deriv = -K / self.m2
-            * [
-                *self.x2 - WALL + L,     // right wall
-                *self.x2 - *self.x1 - L, // mass 1
-            ]
-            .into_iter()
-            .sum::<f32>();
+        let [x1, x2] = *self.x;
+        // dx/dt = v
+        self.x.deriv = *self.v;
+        self.v.deriv = [
+            // Mass 1
+            -K / self.m[0]
+                * [
+                    x1 + WALL - L, // left wall
+                    x1 - x2 + L,   // mass 2
+                ]
+                .into_iter()
+                .sum::<f32>(),
+            // Mass 2
+            -K / self.m[1]
+                * [
+                    x2 - WALL + L, // right wall
+                    x2 - x1 - L,   // mass 1
+                ]
+                .into_iter()
+                .sum::<f32>(),
+        ];
     }
 
     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
-        visitor.apply(&mut self.x1);
-        visitor.apply(&mut self.v1);
-        visitor.apply(&mut self.x2);
-        visitor.apply(&mut self.v2);
+        visitor.apply_pair(&mut self.x, &mut self.v);
+    }
+}
+
+impl<S: Solver> Hamiltonian for CoupledOscillators<S> {
+    fn energy(&self) -> f32 {
+        let [x1, x2] = *self.x;
+        // Kinetic energy of masses
+        let kinetic: f32 = (0..2).map(|i| 0.5 * self.m[i] * self.v[i].powi(2)).sum();
+        // Potential energy of springs
+        let potential: f32 = [
+            x1 + WALL - L, // left wall
+            x2 - x1 - L,   // between masses
+            WALL - L - x2, // right wall
+        ]
+        .into_iter()
+        .map(|d| 0.5 * K * d * d)
+        .sum();
+        kinetic + potential
     }
 }
 
@@ -61,15 +76,15 @@ impl<S: Solver> Display for CoupledOscillators<S> {
         write!(
             f,
             "x1:{:>5.2}, v1:{:>5.2}, x2:{:>5.2}, v2:{:>5.2}",
-            *self.x1, *self.v1, *self.x2, *self.v2
+            self.x[0], self.v[0], self.x[1], self.v[1]
         )?;
 
         // Visualize both masses on a 64-character line
         let scale = 0.0625; // units per character
         let offset = WALL; // position offset so -WALL maps to character 0
 
-        let pos1_char = ((*self.x1 + offset) / scale).floor() as usize;
-        let pos2_char = ((*self.x2 + offset) / scale).floor() as usize;
+        let pos1_char = ((self.x[0] + offset) / scale).floor() as usize;
+        let pos2_char = ((self.x[1] + offset) / scale).floor() as usize;
 
         write!(f, "|")?;
         for i in 0..((2.0 * offset / scale) as usize + 1) {
@@ -91,20 +106,24 @@ fn main() {
 
     // Initial conditions: mass1 at left, mass2 at right with initial velocity
     let mut system = CoupledOscillators {
-        m1: 1.0,
-        x1: Var::new(-2.0),
-        v1: Var::new(0.0),
-        m2: 3.0,
-        x2: Var::new(1.0),
-        v2: Var::new(-3.0),
+        m: [1.0, 3.0],
+        x: Var::new([-2.0, 1.0]),
+        v: Var::new([0.0, -3.0]),
     };
     println!("{}", system);
 
+    let mut monitor = EnergyMonitor::new(&system);
+    let mut t = 0.0;
     // Simulation loop: 80 frames with 10 RK4 steps per frame (dt=0.01 each)
     for _ in 0..40 {
         for _ in 0..10 {
-            solver.solve_step(&mut system, 0.01);
+            monitor.solve_step(&solver, &mut system, &mut t, 0.01);
         }
         println!("{}", system);
     }
+    println!(
+        "Energy drift: {:e}, largest per step: {:e}",
+        monitor.energy_drift(),
+        monitor.max_step_drift()
+    );
 }
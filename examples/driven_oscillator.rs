@@ -16,13 +16,12 @@
 //!   - Numerical position and velocity
 //!   - Visual trajectory showing oscillation amplitude changes
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{Context, Rk4, Solver, System, Var, Visitor};
 use std::fmt::{self, Display, Formatter};
 
 struct DrivenOscillator<S: Solver> {
-    x: Var<f32, S>,    // position
-    v: Var<f32, S>,    // velocity
-    time: Var<f32, S>, // current time (evolves as d(time)/dt = 1)
+    x: Var<f32, S>, // position
+    v: Var<f32, S>, // velocity
 }
 
 const K: f32 = 25.0; // spring constant (natural frequency ω0 = 5)
@@ -31,32 +30,25 @@ const F0: f32 = 2.0; // driving amplitude
 const OMEGA_D: f32 = 4.5; // driving frequency (slightly below resonance)
 
 impl<S: Solver> System<S> for DrivenOscillator<S> {
-    fn compute_derivs(&mut self, _: &S::Context) {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
         // Position derivative: dx/dt = v
         self.x.deriv = *self.v;
 
         // Velocity derivative: dv/dt = -k*x - b*v + F0*cos(ω_d*t)
-        let driving_force = F0 * (OMEGA_D * *self.time).cos();
+        let driving_force = F0 * (OMEGA_D * ctx.time()).cos();
         self.v.deriv = -K * *self.x - B * *self.v + driving_force;
-
-        self.time.deriv = 1.0;
     }
 
     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
         visitor.apply(&mut self.x);
         visitor.apply(&mut self.v);
-        visitor.apply(&mut self.time);
     }
 }
 
 impl<S: Solver> Display for DrivenOscillator<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         // Print numerical state
-        write!(
-            f,
-            "x:{:>6.3}, v:{:>6.3}, t:{:>4.1}|",
-            *self.x, *self.v, *self.time
-        )?;
+        write!(f, "x:{:>6.3}, v:{:>6.3}|", *self.x, *self.v)?;
 
         // Visualize oscillator position
         // Line represents positions from -1.0 to 1.0 (0.03125 units per character)
@@ -83,14 +75,14 @@ fn main() {
     let mut system = DrivenOscillator {
         x: Var::new(0.0),
         v: Var::new(0.0),
-        time: Var::new(0.0),
     };
 
+    let mut t = 0.0;
     // Simulation loop: 120 frames with 10 RK4 steps per frame (dt=0.01 each)
     for _ in 0..120 {
         for _ in 0..10 {
-            solver.solve_step(&mut system, 0.01);
+            solver.solve_step(&mut system, &mut t, 0.01);
         }
-        println!("{}", system);
+        println!("t:{:>4.1}, {}", t, system);
     }
 }
@@ -21,13 +21,12 @@
 //!   - Numerical position and velocity
 //!   - Visual trajectory showing chaotic oscillations
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{Context, Rk4, Solver, System, Var, Visitor};
 use std::fmt::{self, Display, Formatter};
 
 struct Duffing<S: Solver> {
-    x: Var<f32, S>,    // position
-    v: Var<f32, S>,    // velocity
-    time: Var<f32, S>, // current time for driving force
+    x: Var<f32, S>, // position
+    v: Var<f32, S>, // velocity
 }
 
 const DELTA: f32 = 0.3; // damping coefficient
@@ -37,34 +36,26 @@ const GAMMA: f32 = 0.5; // forcing amplitude
 const OMEGA: f32 = 1.2; // forcing frequency
 
 impl<S: Solver> System<S> for Duffing<S> {
-    fn compute_derivs(&mut self, _: &S::Context) {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
         // Position derivative: dx/dt = v
         self.x.deriv = *self.v;
 
         // Velocity derivative: dv/dt = -δ*v - α*x - β*x³ + γ*cos(ω*t)
-        let forcing = GAMMA * (OMEGA * *self.time).cos();
+        let forcing = GAMMA * (OMEGA * ctx.time()).cos();
         self.v.deriv =
             -DELTA * *self.v - ALPHA * *self.x - BETA * *self.x * *self.x * *self.x + forcing;
-
-        // Update time for next step
-        self.time.deriv = 1.0;
     }
 
     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
         visitor.apply(&mut self.x);
         visitor.apply(&mut self.v);
-        visitor.apply(&mut self.time);
     }
 }
 
 impl<S: Solver> Display for Duffing<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         // Print numerical state
-        write!(
-            f,
-            "x:{:>6.3}, v:{:>6.3}, t:{:>4.1}|",
-            *self.x, *self.v, *self.time
-        )?;
+        write!(f, "x:{:>6.3}, v:{:>6.3}|", *self.x, *self.v)?;
 
         // Visualize position
         // Line represents positions from -2.0 to 2.0 (0.0625 units per character)
@@ -91,14 +82,14 @@ fn main() {
     let mut system = Duffing {
         x: Var::new(1.0),
         v: Var::new(0.0),
-        time: Var::new(0.0),
     };
 
+    let mut t = 0.0;
     // Simulation loop: 100 frames with 10 RK4 steps per frame (dt=0.05 each)
     for _ in 0..100 {
         for _ in 0..10 {
-            solver.solve_step(&mut system, 0.05);
+            solver.solve_step(&mut system, &mut t, 0.05);
         }
-        println!("{}", system);
+        println!("t:{:>4.1}, {}", t, system);
     }
 }
@@ -13,8 +13,9 @@
 //! The visualization shows:
 //!   - Numerical angle (θ) and angular velocity (ω)
 //!   - Visual representation of pendulum bob position along an arc
+//!   - Relative drift of the total energy at the end
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{EnergyMonitor, Hamiltonian, Rk4, Simulation, Solver, System, Var, Visitor};
 use std::fmt::{self, Display, Formatter};
 
 struct Pendulum<S: Solver> {
@@ -34,8 +35,14 @@ impl<S: Solver> System<S> for Pendulum<S> {
     }
 
     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
-        visitor.apply(&mut self.theta);
-        visitor.apply(&mut self.omega);
+        visitor.apply_pair(&mut self.theta, &mut self.omega);
+    }
+}
+
+impl<S: Solver> Hamiltonian for Pendulum<S> {
+    fn energy(&self) -> f32 {
+        // Kinetic plus potential energy of the unit mass: E = (Lω)²/2 - gL·cos(θ)
+        0.5 * (L * *self.omega).powi(2) - G * L * (*self.theta).cos()
     }
 }
 
@@ -65,19 +72,19 @@ impl<S: Solver> Display for Pendulum<S> {
 }
 
 fn main() {
-    let solver = Rk4;
-
     // Initial conditions: start with 120° angle (2π/3) and zero angular velocity
-    let mut system = Pendulum {
+    let system = Pendulum {
         theta: Var::new(2.0 * std::f32::consts::PI / 3.0), // 120 degrees
         omega: Var::new(0.0),
     };
 
-    // Simulation loop: 40 frames with 10 RK4 steps per frame (dt=0.01 each)
+    // Fixed RK4 steps of dt=0.01, about 10 steps per frame
+    let mut sim = Simulation::new(system, Rk4, 0.01);
+    let mut monitor = EnergyMonitor::new(sim.system());
     for _ in 0..40 {
-        for _ in 0..10 {
-            solver.solve_step(&mut system, 0.01);
-        }
-        println!("{}", system);
+        sim.advance(0.1);
+        monitor.observe(sim.system());
+        println!("{}", sim.system());
     }
+    println!("Energy drift: {:e}", monitor.energy_drift());
 }
@@ -18,7 +18,7 @@
 //!   - Numerical position and velocity
 //!   - Visual trajectory showing limit cycle behavior
 
-use phy::{Rk4, Solver, System, Var, Visitor};
+use phy::{Rk4, Solver, System, Var, Visitor, integrate};
 use std::fmt::{self, Display, Formatter};
 
 struct VanDerPol<S: Solver> {
@@ -68,19 +68,18 @@ impl<S: Solver> Display for VanDerPol<S> {
 }
 
 fn main() {
-    let solver = Rk4;
-
     // Initial conditions: start with small displacement
     let mut system = VanDerPol {
         x: Var::new(0.1),
         v: Var::new(0.0),
     };
 
-    // Simulation loop: 80 frames with 10 RK4 steps per frame (dt=0.02 each)
-    for _ in 0..80 {
-        for _ in 0..10 {
-            solver.solve_step(&mut system, 0.02);
+    // 800 RK4 steps (dt=0.02 each), printing every 10th step
+    let mut steps = 0;
+    integrate(&mut system, &Rk4, 0.0, 16.0, 0.02, |_, system| {
+        steps += 1;
+        if steps % 10 == 0 {
+            println!("{}", system);
         }
-        println!("{}", system);
-    }
+    });
 }
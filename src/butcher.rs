@@ -0,0 +1,567 @@
+use crate::{
+    AdaptiveSolver, AdaptiveStep, Context, DenseSolver, Deriv, ErrorNorm, Norm, Param, Scalar,
+    Solver, System, Tolerance, Var, Visitor, tableau::DormandPrince,
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+
+/// Butcher tableau of an explicit Runge–Kutta method.
+///
+/// For a differential equation dy/dt = f(t, y), an `s`-stage method computes:
+/// ```text
+/// k_i = f(t_n + c_i*h, y_n + h*sum(a_ij*k_j, j < i)),  i = 1..s
+/// y_{n+1} = y_n + h*sum(b_i*k_i)
+/// ```
+///
+/// See [`tableau`](crate::tableau) module for ready-made tableaus.
+pub trait Tableau {
+    /// Array holding one value of type `D` per stage, e.g. `[D; 4]` for a four-stage method.
+    type Stages<D: Clone + Default>: AsRef<[D]> + AsMut<[D]> + Clone + Default;
+
+    /// Order of accuracy of the method.
+    const ORDER: u32;
+
+    /// Runge–Kutta matrix `a_ij`.
+    ///
+    /// Coefficients are stored in double precision and converted to the
+    /// [`Scalar`] type of the system being integrated.
+    ///
+    /// Row `i` contains weights of the previous stages `j < i`,
+    /// so the first row is empty.
+    const A: &'static [&'static [f64]];
+
+    /// Weights `b_i` of the stage derivatives in the final update.
+    const B: &'static [f64];
+
+    /// Nodes `c_i`, offsets of stage times in units of time step.
+    const C: &'static [f64];
+}
+
+/// Butcher tableau with an embedded lower-order solution used to estimate local error.
+pub trait EmbeddedTableau: Tableau {
+    /// Order of accuracy of the embedded solution.
+    const EMBEDDED_ORDER: u32;
+
+    /// Difference between main and embedded weights `b_i - b*_i`.
+    const E: &'static [f64];
+}
+
+/// Butcher tableau with a continuous extension used for dense output.
+///
+/// The state within a step is interpolated as
+/// ```text
+/// y(t_n + θ*h) = y_n + h*sum(b_i(θ)*k_i)
+/// ```
+/// where `b_i(θ)` are polynomials with `b_i(0) = 0` and `b_i(1) = b_i`.
+pub trait DenseTableau: Tableau {
+    /// Order of accuracy of the interpolant.
+    const DENSE_ORDER: u32;
+
+    /// Coefficients of the interpolation weights.
+    ///
+    /// Row `i` contains coefficients of `b_i(θ) = sum(BI[i][j] * θ^(j+1))`,
+    /// starting with the linear term.
+    const BI: &'static [&'static [f64]];
+}
+
+/// Generic explicit Runge–Kutta method defined by Butcher tableau `T`.
+///
+/// # Usage
+///
+/// - [`Solver::solve_step`] performs a single step of the given size.
+/// - If the tableau has an embedded solution ([`EmbeddedTableau`]), then
+///   [`AdaptiveSolver::adaptive_step`] is also available. It attempts a step,
+///   shrinks and retries it until the error is within
+///   [`tolerance`](Self::tolerance), and reports the step actually taken
+///   together with a suggestion for the next one.
+///
+/// If the tableau has a continuous extension ([`DenseTableau`]) and dense output
+/// is enabled with the `DENSE` parameter, e.g. by [`with_dense_output`](Self::with_dense_output),
+/// the state can be interpolated within the last step with [`DenseSolver::interpolate`].
+/// Solvers with dense output compute all stages of the tableau in each step, while
+/// otherwise the stages not needed by the step itself are skipped, e.g. the last
+/// stage of [`Dopri5`] in fixed steps.
+///
+/// Errors of individual variables are combined using [`ErrorNorm`].
+/// Variables may override the default tolerance with [`Var::with_tolerance`].
+/// Step size control parameters are ignored by fixed-step integration.
+///
+/// # Example
+/// ```
+/// use phy::{ButcherRk, Solver, System, Var, Visitor, tableau::Ralston};
+///
+/// struct Decay<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Decay<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = -*self.x;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let solver = ButcherRk::<Ralston>::default();
+/// let mut system = Decay { x: Var::new(1.0) };
+/// let mut t = 0.0;
+/// for _ in 0..100 {
+///     solver.solve_step(&mut system, &mut t, 0.01);
+/// }
+/// assert!((*system.x - (-1.0f32).exp()).abs() < 1e-4);
+/// ```
+pub struct ButcherRk<T: Tableau, const DENSE: bool = false> {
+    /// Default tolerance on the local error of variables per step.
+    ///
+    /// Converted to the [`Scalar`] type of the system being integrated.
+    pub tolerance: Tolerance,
+    /// The way errors of individual variables are combined.
+    pub norm: Norm,
+    /// Safety factor applied to the optimal step size estimate.
+    ///
+    /// This and other step control parameters are stored in `f32` regardless
+    /// of the [`Scalar`] type of the system being integrated.
+    pub safety: f32,
+    /// Smallest allowed ratio between the next and the current step size.
+    pub min_factor: f32,
+    /// Largest allowed ratio between the next and the current step size.
+    pub max_factor: f32,
+    /// Smallest step size relative to the step requested from
+    /// [`adaptive_step`](AdaptiveSolver::adaptive_step).
+    ///
+    /// Steps are not shrunk below this fraction of the requested step, nor below
+    /// the resolution of the current time, so that the step size does not collapse
+    /// to zero at discontinuities. Steps of the smallest size are accepted with
+    /// [`forced`](AdaptiveStep::forced) set if their error is finite but exceeds the
+    /// tolerance. Being relative, the bound applies to any [`Scalar`] type, and to
+    /// integration backward in time with negative steps.
+    pub min_step_ratio: f32,
+    _tableau: PhantomData<T>,
+}
+
+impl<T: Tableau, const DENSE: bool> Clone for ButcherRk<T, DENSE> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Tableau, const DENSE: bool> Copy for ButcherRk<T, DENSE> {}
+
+impl<T: Tableau, const DENSE: bool> Debug for ButcherRk<T, DENSE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ButcherRk")
+            .field("tolerance", &self.tolerance)
+            .field("norm", &self.norm)
+            .field("safety", &self.safety)
+            .field("min_factor", &self.min_factor)
+            .field("max_factor", &self.max_factor)
+            .field("min_step_ratio", &self.min_step_ratio)
+            .field("dense", &DENSE)
+            .finish()
+    }
+}
+
+impl<T: Tableau, const DENSE: bool> Default for ButcherRk<T, DENSE> {
+    fn default() -> Self {
+        Self {
+            tolerance: Tolerance::default(),
+            norm: Norm::default(),
+            safety: 0.9,
+            min_factor: 0.2,
+            max_factor: 5.0,
+            min_step_ratio: 1e-6,
+            _tableau: PhantomData,
+        }
+    }
+}
+
+impl<T: Tableau, const DENSE: bool> ButcherRk<T, DENSE> {
+    /// Create a solver with the given error tolerance and default step control.
+    pub fn new(tolerance: Tolerance) -> Self {
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+}
+
+impl<T: Tableau> ButcherRk<T> {
+    /// Convert to the same solver with dense output enabled.
+    ///
+    /// Variables of systems must use the returned solver type, e.g. [`Dopri5Dense`].
+    pub fn with_dense_output(self) -> ButcherRk<T, true> {
+        ButcherRk {
+            tolerance: self.tolerance,
+            norm: self.norm,
+            safety: self.safety,
+            min_factor: self.min_factor,
+            max_factor: self.max_factor,
+            min_step_ratio: self.min_step_ratio,
+            _tableau: PhantomData,
+        }
+    }
+}
+
+/// Storage required by the Butcher tableau solver for each variable.
+///
+/// Holds the value at the beginning of the step and the derivatives
+/// computed at each stage.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, T::Stages<P::Deriv>: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, T::Stages<P::Deriv>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ButcherStorage<P: Param, T: Tableau> {
+    /// The initial value at the beginning of the step (y_n)
+    init_value: P,
+    /// Stage derivatives k1..ks
+    stages: T::Stages<P::Deriv>,
+}
+
+impl<P: Param, T: Tableau> Clone for ButcherStorage<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            init_value: self.init_value.clone(),
+            stages: self.stages.clone(),
+        }
+    }
+}
+
+impl<P: Param, T: Tableau> Copy for ButcherStorage<P, T>
+where
+    P: Copy,
+    T::Stages<P::Deriv>: Copy,
+{
+}
+
+impl<P: Param, T: Tableau> Default for ButcherStorage<P, T> {
+    fn default() -> Self {
+        Self {
+            init_value: P::default(),
+            stages: T::Stages::default(),
+        }
+    }
+}
+
+impl<P: Param, T: Tableau> Debug for ButcherStorage<P, T>
+where
+    P: Debug,
+    P::Deriv: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ButcherStorage")
+            .field("init_value", &self.init_value)
+            .field("stages", &self.stages.as_ref())
+            .finish()
+    }
+}
+
+/// Visitor that applies a single Runge–Kutta stage to variables.
+pub struct ButcherStep<T: Tableau, R: Scalar = f32> {
+    stage: usize,
+    /// Number of stages in this step.
+    stages: usize,
+    /// Weights of the error estimate, empty if error is not estimated.
+    error: &'static [f64],
+    /// Time at the beginning of the step (t_n)
+    t: R,
+    dt: R,
+    _tableau: PhantomData<T>,
+}
+
+impl<T: Tableau, R: Scalar, const DENSE: bool> Context<ButcherRk<T, DENSE>, R>
+    for ButcherStep<T, R>
+{
+    /// Returns the time of the current stage: `t_n + c_i * h`.
+    fn time(&self) -> R {
+        self.t + R::from_f64(T::C[self.stage]) * self.dt
+    }
+
+    fn time_step(&self) -> R {
+        self.dt
+    }
+}
+
+impl<T: Tableau, R: Scalar, const DENSE: bool> Visitor<ButcherRk<T, DENSE>, R>
+    for ButcherStep<T, R>
+{
+    fn apply<P: Param<Scalar = R>>(&mut self, var: &mut Var<P, ButcherRk<T, DENSE>>) {
+        let storage = &mut var.storage;
+        let stages = storage.stages.as_mut();
+        let stage = self.stage;
+
+        if stage == 0 {
+            // Save initial value y_n for use in subsequent stages
+            storage.init_value.clone_from(&var.value);
+        }
+        stages[stage].clone_from(&var.deriv);
+
+        // Prepare state for the next stage: y = y_n + dt * sum(a_ij * k_j),
+        // or compute the final state: y_{n+1} = y_n + dt * sum(b_j * k_j)
+        let last = stage + 1 == self.stages;
+        let weights = if last { T::B } else { T::A[stage + 1] };
+        let incr = weighted_sum(weights, stages);
+        var.value.clone_from(&storage.init_value);
+        var.value.step(&incr, self.dt);
+
+        if last && !self.error.is_empty() {
+            // Error estimate is stored in place of derivative to be collected by `ErrorNorm`
+            var.deriv = weighted_sum(self.error, stages);
+            var.deriv.scale(self.dt);
+        } else {
+            // Reset derivative for next stage
+            var.deriv = var.value.zero_deriv();
+        }
+    }
+}
+
+/// Visitor that restores variables to their values at the beginning of the step.
+struct ButcherRestore;
+
+impl<T: Tableau, R: Scalar, const DENSE: bool> Visitor<ButcherRk<T, DENSE>, R> for ButcherRestore {
+    fn apply<P: Param<Scalar = R>>(&mut self, var: &mut Var<P, ButcherRk<T, DENSE>>) {
+        var.value.clone_from(&var.storage.init_value);
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+/// Visitor that moves variables to the interpolated state within the last step.
+struct ButcherInterpolate<R: Scalar> {
+    dt: R,
+    theta: R,
+}
+
+impl<T: DenseTableau, R: Scalar> Visitor<ButcherRk<T, true>, R> for ButcherInterpolate<R> {
+    fn apply<P: Param<Scalar = R>>(&mut self, var: &mut Var<P, ButcherRk<T, true>>) {
+        let storage = &var.storage;
+        // y = y_n + dt * sum(b_i(θ) * k_i)
+        let mut incr = P::Deriv::default();
+        for (coeffs, k) in T::BI.iter().zip(storage.stages.as_ref()) {
+            // Horner's scheme for sum(c_j * θ^(j+1))
+            let w = self.theta
+                * coeffs
+                    .iter()
+                    .rev()
+                    .fold(R::ZERO, |acc, &c| acc * self.theta + R::from_f64(c));
+            if w != R::ZERO {
+                incr.add_scaled(k, w);
+            }
+        }
+        var.value.clone_from(&storage.init_value);
+        var.value.step(&incr, self.dt);
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+/// Compute `sum(weights[j] * stages[j])` over the given weights.
+fn weighted_sum<D: Deriv>(weights: &[f64], stages: &[D]) -> D {
+    let mut sum = D::default();
+    for (&w, k) in weights.iter().zip(stages) {
+        if w != 0.0 {
+            sum.add_scaled(k, D::Scalar::from_f64(w));
+        }
+    }
+    sum
+}
+
+/// Number of leading stages that have non-zero weights.
+fn used_stages(weights: &[f64]) -> usize {
+    weights.iter().rposition(|&w| w != 0.0).map_or(0, |i| i + 1)
+}
+
+/// Limit the magnitude of a step from below, keeping its sign.
+///
+/// Negative steps integrate backward in time.
+fn limit_step<R: Scalar>(dt: R, min_step: R) -> R {
+    if dt < R::ZERO {
+        dt.min(-min_step)
+    } else {
+        dt.max(min_step)
+    }
+}
+
+impl<T: Tableau, const DENSE: bool> ButcherRk<T, DENSE> {
+    /// Run a step consisting of the given number of stages.
+    ///
+    /// If `error` weights are not empty, error estimate is stored in derivatives.
+    fn run_stages<R: Scalar, S: System<Self, R>>(
+        &self,
+        system: &mut S,
+        t: R,
+        dt: R,
+        stages: usize,
+        error: &'static [f64],
+    ) {
+        for stage in 0..stages {
+            let mut step = ButcherStep {
+                stage,
+                stages,
+                error,
+                t,
+                dt,
+                _tableau: PhantomData,
+            };
+
+            // Compute derivatives at the current stage state
+            system.compute_derivs(&step);
+
+            // Store stage derivative and move to the next stage state
+            system.visit_vars(&mut step);
+        }
+    }
+}
+
+impl<T: Tableau, R: Scalar, const DENSE: bool> Solver<R> for ButcherRk<T, DENSE> {
+    type Context = ButcherStep<T, R>;
+    type Storage<P: Param<Scalar = R>> = ButcherStorage<P, T>;
+
+    /// Perform one step of fixed size without error control.
+    ///
+    /// Trailing stages which are only needed for error estimation are skipped,
+    /// unless dense output is enabled.
+    fn solve_step<S: System<Self, R>>(&self, system: &mut S, t: &mut R, dt: R) {
+        let stages = if DENSE { T::A.len() } else { used_stages(T::B) };
+        self.run_stages(system, *t, dt, stages, &[]);
+        *t += dt;
+    }
+
+    fn restore_step<S: System<Self, R>>(&self, system: &mut S) {
+        system.visit_vars(&mut ButcherRestore);
+    }
+}
+
+impl<T: EmbeddedTableau, R: Scalar, const DENSE: bool> AdaptiveSolver<R> for ButcherRk<T, DENSE> {
+    fn adaptive_step<S: System<Self, R>>(
+        &self,
+        system: &mut S,
+        t: &mut R,
+        dt: R,
+    ) -> AdaptiveStep<R> {
+        let stages = if DENSE {
+            T::A.len()
+        } else {
+            used_stages(T::B).max(used_stages(T::E))
+        };
+        // Error of the lower-order solution is O(dt^(q+1))
+        let exponent = -1.0 / (T::ORDER.min(T::EMBEDDED_ORDER) + 1) as f32;
+
+        let min_step = (dt.abs() * R::from_f32(self.min_step_ratio)).max(R::EPSILON * t.abs());
+        let mut dt = dt;
+        let mut rejected = false;
+        loop {
+            self.run_stages(system, *t, dt, stages, T::E);
+
+            // Collect scaled error estimate of all variables
+            let mut norm = ErrorNorm::new(self.norm, self.tolerance.cast());
+            system.visit_vars(&mut norm);
+            let value = norm.value();
+            let finite = value.is_finite();
+            let error = value.to_f32();
+
+            // Optimal step size: dt * error^(-1/(q+1))
+            let factor = if error > 0.0 {
+                (self.safety * libm::powf(error, exponent)).clamp(self.min_factor, self.max_factor)
+            } else if error == 0.0 {
+                self.max_factor
+            } else {
+                // Error is NaN
+                self.min_factor
+            };
+
+            // Steps with non-finite errors are never accepted
+            let smallest = dt.abs() <= min_step;
+            if error <= 1.0 || (finite && smallest) {
+                // Do not grow the step right after a rejection
+                let factor = if rejected { factor.min(1.0) } else { factor };
+                *t += dt;
+                return AdaptiveStep {
+                    taken: dt,
+                    next: limit_step(dt * R::from_f32(factor), min_step),
+                    forced: error > 1.0,
+                };
+            }
+
+            // Reject the step and retry with a smaller one
+            system.visit_vars(&mut ButcherRestore);
+            if smallest {
+                return AdaptiveStep {
+                    taken: R::ZERO,
+                    next: dt,
+                    forced: true,
+                };
+            }
+            rejected = true;
+            dt = limit_step(dt * R::from_f32(factor), min_step);
+        }
+    }
+}
+
+impl<T: DenseTableau, R: Scalar> DenseSolver<R> for ButcherRk<T, true> {
+    const DENSE_ORDER: u32 = T::DENSE_ORDER;
+
+    fn interpolate<S: System<Self, R>>(&self, system: &mut S, dt: R, theta: R) {
+        system.visit_vars(&mut ButcherInterpolate { dt, theta });
+    }
+}
+
+/// The Dormand–Prince 5(4) method with adaptive step size control.
+///
+/// This is an explicit Runge–Kutta method of fifth order with an embedded
+/// fourth-order solution. The difference between the two gives an estimate
+/// of the local error, which is used to accept or reject a step and to choose
+/// the size of the next one.
+///
+/// # Usage
+///
+/// - [`Solver::solve_step`] performs a single fifth-order step of the given
+///   size without any error control.
+/// - [`AdaptiveSolver::adaptive_step`] attempts a step, shrinks and retries
+///   it until the error is within [`tolerance`](ButcherRk::tolerance), and reports
+///   the step actually taken together with a suggestion for the next one.
+/// - [`Dopri5Dense`] is the same method with dense output.
+///
+/// # Example
+/// ```
+/// use phy::{AdaptiveSolver, Dopri5, Solver, System, Tolerance, Var, Visitor};
+///
+/// struct Decay<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Decay<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = -*self.x;
+///     }
+///
+///     fn visit_vars<V: phy::Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let solver = Dopri5::new(Tolerance::new(1e-6, 1e-5));
+/// let mut system = Decay { x: Var::new(1.0) };
+///
+/// let (mut t, mut dt) = (0.0, 0.1f32);
+/// while t < 1.0 {
+///     dt = dt.min(1.0 - t);
+///     dt = solver.adaptive_step(&mut system, &mut t, dt).next;
+/// }
+/// assert!((*system.x - (-1.0f32).exp()).abs() < 1e-4);
+/// ```
+pub type Dopri5 = ButcherRk<DormandPrince>;
+
+/// The Dormand–Prince 5(4) method with fourth-order dense output.
+///
+/// Computes all seven stages in each step, so that the state can be interpolated
+/// within the last step with [`DenseSolver::interpolate`], see [`Dopri5`].
+pub type Dopri5Dense = ButcherRk<DormandPrince, true>;
+
+/// Storage required by the Dormand–Prince solver for each variable.
+pub type Dopri5Storage<P> = ButcherStorage<P, DormandPrince>;
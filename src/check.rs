@@ -0,0 +1,230 @@
+use crate::{Deriv, Param, Scalar, Snapshot, Solver, System, Var, Visitor};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Point within a step at which a variable was checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStage {
+    /// Right after the given evaluation of derivatives, counting from zero.
+    ///
+    /// Multi-stage solvers evaluate derivatives several times per step,
+    /// e.g. four times for [`Rk4`](crate::Rk4).
+    Derivs(usize),
+    /// After the step has been completed.
+    Update,
+}
+
+/// Part of a variable which contains a non-finite number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckedPart {
+    /// Value of the variable.
+    Value,
+    /// Derivative of the variable.
+    Deriv,
+}
+
+/// Error of a checked step, see [`FiniteCheck`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonFiniteError {
+    /// Index of the offending variable in visiting order.
+    pub var: usize,
+    /// Point within the step at which it was found.
+    pub stage: CheckStage,
+    /// Whether the value or the derivative is not finite.
+    pub part: CheckedPart,
+}
+
+impl Display for NonFiniteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let part = match self.part {
+            CheckedPart::Value => "value",
+            CheckedPart::Deriv => "derivative",
+        };
+        write!(f, "Non-finite {} of variable {}", part, self.var)?;
+        match self.stage {
+            CheckStage::Derivs(index) => write!(f, " at evaluation {} of derivatives", index),
+            CheckStage::Update => write!(f, " after update"),
+        }
+    }
+}
+
+impl core::error::Error for NonFiniteError {}
+
+/// Stepping which detects NaN and infinite numbers and rolls back the step.
+///
+/// Values and derivatives of all variables are scanned after each evaluation of
+/// derivatives, and values are scanned again after the update. Values are scanned
+/// through all their components written by [`Param::write_flat`], so custom
+/// parameters are checked as well. If a non-finite
+/// number is found, the rest of the step is skipped and the system is restored to
+/// its state before the step with a [`Snapshot`], so that the step can be retried,
+/// e.g. with a smaller time step. The snapshot clones the whole system, so fields
+/// which are not variables are rolled back as well.
+///
+/// The checker keeps the snapshot and its buffers between steps, so it should be reused.
+///
+/// # Example
+/// ```
+/// use phy::{CheckStage, Euler, FiniteCheck, Solver, System, Var, Visitor};
+///
+/// #[derive(Clone)]
+/// struct Blowup<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Blowup<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = 1.0 / (1.0 - *self.x);
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let mut system = Blowup::<Euler> { x: Var::new(1.0) };
+/// let mut check = FiniteCheck::new();
+/// let mut t = 0.0;
+/// let error = check.solve_step(&Euler, &mut system, &mut t, 0.1).unwrap_err();
+/// assert_eq!((error.var, error.stage), (0, CheckStage::Derivs(0)));
+/// assert_eq!((t, *system.x), (0.0, 1.0));
+/// ```
+#[derive(Debug)]
+pub struct FiniteCheck<Y, T: Scalar = f32> {
+    snapshot: Option<Snapshot<Y, T>>,
+    buffer: Vec<T>,
+}
+
+impl<Y, T: Scalar> Default for FiniteCheck<Y, T> {
+    fn default() -> Self {
+        Self {
+            snapshot: None,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl<Y: Clone, T: Scalar> FiniteCheck<Y, T> {
+    /// Create a checker with empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Perform one step with [`Solver::solve_step`], checking that all numbers stay finite.
+    ///
+    /// On error `t` is left unchanged and the system is restored to its state before
+    /// the step, including derivatives, solver storage and fields which are not variables.
+    pub fn solve_step<S>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        dt: T,
+    ) -> Result<(), NonFiniteError>
+    where
+        S: Solver<T>,
+        Y: System<S, T>,
+    {
+        match &mut self.snapshot {
+            Some(snapshot) => snapshot.save(system, *t),
+            None => self.snapshot = Some(Snapshot::new(system, *t)),
+        }
+        let mut checked = Checked {
+            system,
+            buffer: &mut self.buffer,
+            evaluations: 0,
+            error: None,
+        };
+        solver.solve_step(&mut checked, t, dt);
+        if checked.error.is_none() {
+            checked.scan(CheckStage::Update);
+        }
+        match checked.error {
+            None => Ok(()),
+            Some(error) => {
+                if let Some(snapshot) = &self.snapshot {
+                    snapshot.restore(system, t);
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+/// System wrapper which scans variables after each evaluation of derivatives.
+///
+/// Once an error is found, further evaluations and solver stages are skipped.
+struct Checked<'a, Y, T: Scalar> {
+    system: &'a mut Y,
+    buffer: &'a mut Vec<T>,
+    evaluations: usize,
+    error: Option<NonFiniteError>,
+}
+
+impl<Y, T: Scalar> Checked<'_, Y, T> {
+    fn scan<S: Solver<T>>(&mut self, stage: CheckStage)
+    where
+        Y: System<S, T>,
+    {
+        let mut scan = Scan {
+            stage,
+            index: 0,
+            buffer: self.buffer,
+            error: None,
+        };
+        self.system.visit_vars(&mut scan);
+        self.error = scan.error;
+    }
+}
+
+impl<S: Solver<T>, T: Scalar, Y: System<S, T>> System<S, T> for Checked<'_, Y, T> {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        if self.error.is_none() {
+            self.system.compute_derivs(ctx);
+            self.scan(CheckStage::Derivs(self.evaluations));
+            self.evaluations += 1;
+        }
+    }
+
+    fn visit_vars<V: Visitor<S, T>>(&mut self, visitor: &mut V) {
+        if self.error.is_none() {
+            self.system.visit_vars(visitor);
+        }
+    }
+}
+
+/// Visitor looking for the first variable with non-finite numbers.
+struct Scan<'a, T: Scalar> {
+    stage: CheckStage,
+    index: usize,
+    buffer: &'a mut Vec<T>,
+    error: Option<NonFiniteError>,
+}
+
+impl<S: Solver<T>, T: Scalar> Visitor<S, T> for Scan<'_, T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, S>) {
+        if self.error.is_some() {
+            return;
+        }
+        self.buffer.clear();
+        self.buffer.resize(var.value.flat_dim(), T::ZERO);
+        var.value.write_flat(self.buffer);
+        let part = if !self.buffer.iter().all(|x| x.is_finite()) {
+            Some(CheckedPart::Value)
+        } else if matches!(self.stage, CheckStage::Derivs(_))
+            && !(0..var.deriv.dim()).all(|i| var.deriv.component(i).is_finite())
+        {
+            Some(CheckedPart::Deriv)
+        } else {
+            None
+        };
+        if let Some(part) = part {
+            self.error = Some(NonFiniteError {
+                var: self.index,
+                stage: self.stage,
+                part,
+            });
+        }
+        self.index += 1;
+    }
+}
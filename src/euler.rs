@@ -1,4 +1,4 @@
-use crate::{Context, Param, Solver, System, Var, Visitor};
+use crate::{Context, Param, Scalar, Solver, System, Var, Visitor, fallible::ResetDerivs};
 
 /// The explicit Euler method for numerical integration.
 ///
@@ -21,39 +21,46 @@ use crate::{Context, Param, Solver, System, Var, Visitor};
 /// Euler's method has truncation error O(dt²) per step and O(dt) globally.
 /// It can be unstable for stiff equations or large time steps.
 /// Consider using [`Rk4`](crate::Rk4) for higher accuracy requirements.
+#[derive(Clone, Copy, Default, Debug)]
 pub struct Euler;
 
 /// Visitor that applies a single Euler step to variables.
-pub struct EulerStep {
-    dt: f32,
+pub struct EulerStep<T: Scalar = f32> {
+    t: T,
+    dt: T,
 }
 
-impl Context<Euler> for EulerStep {
-    fn time_step(&self) -> f32 {
+impl<T: Scalar> Context<Euler, T> for EulerStep<T> {
+    /// Derivatives are evaluated at the beginning of the step.
+    fn time(&self) -> T {
+        self.t
+    }
+
+    fn time_step(&self) -> T {
         self.dt
     }
 }
 
-impl Visitor<Euler> for EulerStep {
+impl<T: Scalar> Visitor<Euler, T> for EulerStep<T> {
     /// Apply the Euler update to a variable.
     ///
     /// Updates the variable's value using:
     /// `value_{n+1} = value_n + deriv * dt`
     ///
     /// Then resets the derivative to prepare for the next step.
-    fn apply<P: Param>(&mut self, var: &mut Var<P, Euler>) {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Euler>) {
         // Euler integration: x_{n+1} = x_n + dx/dt * dt
         var.value.step(&var.deriv, self.dt);
 
         // Reset derivative for next computation
-        var.deriv = P::Deriv::default();
+        var.deriv = var.value.zero_deriv();
     }
 }
 
-impl Solver for Euler {
-    type Context = EulerStep;
+impl<T: Scalar> Solver<T> for Euler {
+    type Context = EulerStep<T>;
     /// Euler's method requires no additional storage per variable.
-    type Storage<P: Param> = ();
+    type Storage<P: Param<Scalar = T>> = ();
 
     /// Perform one Euler integration step for the given system.
     ///
@@ -63,14 +70,23 @@ impl Solver for Euler {
     ///
     /// # Arguments
     /// * `system` - The system to integrate.
+    /// * `t` - Time at the beginning of the step, advanced by `dt`.
     /// * `dt` - Time step for the integration.
-    fn solve_step<S: System<Self>>(&self, system: &mut S, dt: f32) {
-        let mut step = EulerStep { dt };
+    fn solve_step<S: System<Self, T>>(&self, system: &mut S, t: &mut T, dt: T) {
+        let mut step = EulerStep { t: *t, dt };
 
         // Compute derivatives at current state
         system.compute_derivs(&step);
 
         // Apply Euler update to all variables
         system.visit_vars(&mut step);
+
+        *t += dt;
+    }
+
+    /// Variables are only visited after the single evaluation of derivatives,
+    /// so this is never called by [`Solver::try_solve_step`]. Derivatives are reset.
+    fn restore_step<S: System<Self, T>>(&self, system: &mut S) {
+        system.visit_vars(&mut ResetDerivs);
     }
 }
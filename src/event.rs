@@ -0,0 +1,598 @@
+use crate::{AdaptiveSolver, AdaptiveStep, DenseSolver, Scalar, Solver, System};
+
+/// Direction of zero crossings that trigger an event.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Crossings in both directions.
+    #[default]
+    Both,
+    /// Crossings from negative to non-negative values.
+    Rising,
+    /// Crossings from positive to non-positive values.
+    Falling,
+}
+
+impl Direction {
+    /// Whether change of event function from `before` to `after` is a crossing in this direction.
+    pub fn is_crossing<T: Scalar>(self, before: T, after: T) -> bool {
+        let rising = before < T::ZERO && after >= T::ZERO;
+        let falling = before > T::ZERO && after <= T::ZERO;
+        match self {
+            Direction::Both => rising || falling,
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+        }
+    }
+}
+
+/// Value of a scalar event function along with its settings.
+///
+/// An event occurs when the value crosses zero in the given [`Direction`].
+///
+/// # Example
+/// ```
+/// use phy::{Direction, Event};
+///
+/// let height = 0.5;
+/// let event = Event::new(height).falling().terminal();
+/// assert_eq!(event.direction, Direction::Falling);
+/// assert!(event.terminal);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Event<T: Scalar = f32> {
+    /// Current value of the event function.
+    pub value: T,
+    /// Crossings that trigger the event.
+    pub direction: Direction,
+    /// Whether integration should stop when the event occurs.
+    pub terminal: bool,
+}
+
+impl<T: Scalar> Event<T> {
+    /// Create a non-terminal event triggered by crossings in both directions.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            direction: Direction::Both,
+            terminal: false,
+        }
+    }
+
+    /// Trigger the event only by crossings from negative to non-negative values.
+    pub fn rising(self) -> Self {
+        Self {
+            direction: Direction::Rising,
+            ..self
+        }
+    }
+
+    /// Trigger the event only by crossings from positive to non-positive values.
+    pub fn falling(self) -> Self {
+        Self {
+            direction: Direction::Falling,
+            ..self
+        }
+    }
+
+    /// Stop integration when the event occurs.
+    pub fn terminal(self) -> Self {
+        Self {
+            terminal: true,
+            ..self
+        }
+    }
+}
+
+/// A system that declares scalar event functions of its state.
+///
+/// Events are identified by their index in `0..event_count()`.
+///
+/// # Example
+/// ```
+/// use phy::{Event, EventSystem, Solver, System, Var, Visitor};
+///
+/// #[derive(Clone)]
+/// struct Ball<S: Solver> {
+///     pos: Var<f32, S>,
+///     vel: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Ball<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -9.8;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.pos);
+///         visitor.apply(&mut self.vel);
+///     }
+/// }
+///
+/// impl<S: Solver> EventSystem<S> for Ball<S> {
+///     fn event_count(&self) -> usize {
+///         1
+///     }
+///
+///     fn event(&self, _index: usize, _t: f32) -> Event {
+///         // Ball hits the ground
+///         Event::new(*self.pos).falling()
+///     }
+///
+///     fn handle_event(&mut self, _index: usize, _t: f32) {
+///         // Elastic impact
+///         *self.vel = -*self.vel;
+///     }
+/// }
+/// ```
+pub trait EventSystem<S: Solver<T> + ?Sized, T: Scalar = f32>: System<S, T> {
+    /// Number of event functions.
+    fn event_count(&self) -> usize;
+
+    /// Evaluate event function with the given index at the current state and time `t`.
+    fn event(&self, index: usize, t: T) -> Event<T>;
+
+    /// Handle the event that occurred at time `t`.
+    ///
+    /// The system is in the state right after the zero crossing, so the
+    /// event function already has a new sign. This method may change the
+    /// state discontinuously, e.g. reverse velocity on impact.
+    fn handle_event(&mut self, index: usize, t: T) {
+        let _ = (index, t);
+    }
+}
+
+/// Event that occurred during integration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EventHit<T: Scalar = f32> {
+    /// Index of the event function.
+    pub index: usize,
+    /// Time of the zero crossing.
+    pub time: T,
+    /// Whether the event is terminal.
+    pub terminal: bool,
+}
+
+/// Stepper that detects events within steps and locates their times.
+///
+/// After each step the event functions are compared with their values at the
+/// beginning of the step. If some of them changed sign, the crossing time is
+/// located by the Illinois variant of regula falsi. The system is then moved to
+/// the earliest crossing and the event is handled there, so the step is cut short.
+///
+/// The state within the step is obtained in one of two ways:
+/// - [`solve_step`](Self::solve_step), [`adaptive_step`](Self::adaptive_step) and
+///   [`integrate`](Self::integrate) work with any solver and use the solver itself
+///   as an interpolant: the state at time `t + θ` is obtained by a single step of
+///   size `θ` from the beginning of the step, which costs a whole step per iteration.
+/// - [`solve_step_dense`](Self::solve_step_dense),
+///   [`adaptive_step_dense`](Self::adaptive_step_dense) and
+///   [`integrate_dense`](Self::integrate_dense) evaluate the dense output of a
+///   [`DenseSolver`] instead, which needs no evaluations of derivatives.
+///
+/// Systems must be [`Clone`] so the state at the beginning of the step can be saved.
+///
+/// The system is left at the end of the final bracket, just past the root. The
+/// locator remembers the event it handled last and ignores a crossing of the same
+/// event at the beginning of the next step while its function is still within the
+/// located residual of zero, so that an event is handled only once at each root.
+/// Therefore a locator should only be used with a single system.
+///
+/// # Algorithm
+///
+/// Bracket `[a, b]` with `g(a)` and `g(b)` of different signs is shrunk with
+/// the secant point `θ = b - g(b) * (b - a) / (g(b) - g(a))`. If the same end
+/// of the bracket is retained twice in a row, its function value is halved,
+/// which guarantees superlinear convergence unlike plain regula falsi.
+///
+/// # Example
+/// ```
+/// # use phy::{Event, EventSystem, Solver, System, Var, Visitor};
+/// # #[derive(Clone)]
+/// # struct Ball<S: Solver> { pos: Var<f32, S>, vel: Var<f32, S> }
+/// # impl<S: Solver> System<S> for Ball<S> {
+/// #     fn compute_derivs(&mut self, _: &S::Context) {
+/// #         self.pos.deriv = *self.vel;
+/// #         self.vel.deriv = -9.8;
+/// #     }
+/// #     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+/// #         visitor.apply(&mut self.pos);
+/// #         visitor.apply(&mut self.vel);
+/// #     }
+/// # }
+/// # impl<S: Solver> EventSystem<S> for Ball<S> {
+/// #     fn event_count(&self) -> usize { 1 }
+/// #     fn event(&self, _: usize, _: f32) -> Event { Event::new(*self.pos).falling().terminal() }
+/// # }
+/// use phy::{EventLocator, Rk4, Rk4Dense};
+///
+/// let mut ball = Ball::<Rk4> { pos: Var::new(4.9), vel: Var::new(0.0) };
+/// let mut t = 0.0;
+///
+/// // Integrate until the ball hits the ground
+/// let hit = EventLocator::default().integrate(&Rk4, &mut ball, &mut t, 10.0, 0.1);
+/// assert!(hit.is_some_and(|hit| hit.terminal));
+/// assert!((t - 1.0).abs() < 1e-4);
+///
+/// // The same using dense output
+/// let mut ball = Ball::<Rk4Dense> { pos: Var::new(4.9), vel: Var::new(0.0) };
+/// let mut t = 0.0;
+/// let hit = EventLocator::default().integrate_dense(&Rk4Dense, &mut ball, &mut t, 10.0, 0.1);
+/// assert!(hit.is_some_and(|hit| hit.terminal));
+/// assert!((t - 1.0).abs() < 1e-4);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EventLocator {
+    /// Root localisation stops when the crossing is bracketed within this time interval.
+    ///
+    /// Stored in `f32` regardless of the [`Scalar`] type of the system.
+    pub tolerance: f32,
+    /// Maximum number of root localisation iterations.
+    pub max_iterations: usize,
+    /// Event handled at the end of the last step, if any.
+    last: Option<HandledEvent>,
+}
+
+impl Default for EventLocator {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-6,
+            max_iterations: 50,
+            last: None,
+        }
+    }
+}
+
+/// Event handled by the locator, ignored at the beginning of the next step.
+#[derive(Clone, Copy, Debug)]
+struct HandledEvent {
+    index: usize,
+    /// Time of the event.
+    time: f64,
+    /// Absolute value of the event function at the located root.
+    residual: f64,
+}
+
+impl EventLocator {
+    /// Create an event locator with the given time tolerance.
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+
+    /// Perform one step of the solver, stopping at the earliest event.
+    ///
+    /// Returns the event that occurred, if any. In this case `t` is advanced
+    /// only to the event time.
+    ///
+    /// # Arguments
+    /// * `solver` - The solver used for integration.
+    /// * `system` - The system to integrate.
+    /// * `t` - Time at the beginning of the step.
+    /// * `dt` - Time step for the integration.
+    pub fn solve_step<T: Scalar, S: Solver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        dt: T,
+    ) -> Option<EventHit<T>> {
+        let start = system.clone();
+        let t0 = *t;
+        solver.solve_step(system, t, dt);
+        let restep = Restep {
+            solver,
+            start: &start,
+            t0,
+        };
+        self.handle_events(&restep, system, &start, t0, t)
+    }
+
+    /// Perform one step of a dense solver, stopping at the earliest event.
+    ///
+    /// The same as [`solve_step`](Self::solve_step), but the crossing is located
+    /// using the dense output of the solver.
+    pub fn solve_step_dense<T: Scalar, S: DenseSolver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        dt: T,
+    ) -> Option<EventHit<T>> {
+        let start = system.clone();
+        let t0 = *t;
+        solver.solve_step(system, t, dt);
+        let dense = Dense {
+            solver,
+            dt: *t - t0,
+        };
+        self.handle_events(&dense, system, &start, t0, t)
+    }
+
+    /// Perform one adaptive step of the solver, stopping at the earliest event.
+    ///
+    /// If an event occurred, the returned step is shortened to the event time.
+    pub fn adaptive_step<T: Scalar, S: AdaptiveSolver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        dt: T,
+    ) -> (AdaptiveStep<T>, Option<EventHit<T>>) {
+        let start = system.clone();
+        let t0 = *t;
+        let mut step = solver.adaptive_step(system, t, dt);
+        let restep = Restep {
+            solver,
+            start: &start,
+            t0,
+        };
+        let hit = self.handle_events(&restep, system, &start, t0, t);
+        step.taken = *t - t0;
+        (step, hit)
+    }
+
+    /// Perform one adaptive step of a dense solver, stopping at the earliest event.
+    ///
+    /// The same as [`adaptive_step`](Self::adaptive_step), but the crossing is located
+    /// using the dense output of the solver.
+    pub fn adaptive_step_dense<T, S, Y>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        dt: T,
+    ) -> (AdaptiveStep<T>, Option<EventHit<T>>)
+    where
+        T: Scalar,
+        S: AdaptiveSolver<T> + DenseSolver<T>,
+        Y: EventSystem<S, T> + Clone,
+    {
+        let start = system.clone();
+        let t0 = *t;
+        let mut step = solver.adaptive_step(system, t, dt);
+        let dense = Dense {
+            solver,
+            dt: step.taken,
+        };
+        let hit = self.handle_events(&dense, system, &start, t0, t);
+        step.taken = *t - t0;
+        (step, hit)
+    }
+
+    /// Integrate the system with fixed step `dt` until `t_end` or a terminal event.
+    ///
+    /// Non-terminal events are handled and integration continues from the
+    /// event time. Returns the last event that occurred, if any.
+    ///
+    /// # Panics
+    /// If `dt` is not positive.
+    pub fn integrate<T: Scalar, S: Solver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        t_end: T,
+        dt: T,
+    ) -> Option<EventHit<T>> {
+        integrate_events(t, t_end, dt, |t, step| {
+            self.solve_step(solver, system, t, step)
+        })
+    }
+
+    /// Integrate the system with a dense solver and fixed step `dt` until `t_end`
+    /// or a terminal event.
+    ///
+    /// The same as [`integrate`](Self::integrate), but crossings are located using
+    /// the dense output of the solver.
+    ///
+    /// # Panics
+    /// If `dt` is not positive.
+    pub fn integrate_dense<T: Scalar, S: DenseSolver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        solver: &S,
+        system: &mut Y,
+        t: &mut T,
+        t_end: T,
+        dt: T,
+    ) -> Option<EventHit<T>> {
+        integrate_events(t, t_end, dt, |t, step| {
+            self.solve_step_dense(solver, system, t, step)
+        })
+    }
+
+    /// Find the earliest event in the step from `start` at `t0` to `system` at `t`.
+    fn handle_events<T: Scalar, S: Solver<T>, Y: EventSystem<S, T> + Clone>(
+        &mut self,
+        interpolant: &impl Interpolant<T, Y>,
+        system: &mut Y,
+        start: &Y,
+        t0: T,
+        t: &mut T,
+    ) -> Option<EventHit<T>> {
+        let dt = *t - t0;
+        // States within the step, which start as copies of the state at the end of the step
+        let mut trial: Option<Y> = None;
+        let mut best: Option<Y> = None;
+        let mut earliest: Option<(usize, T, bool)> = None;
+        for index in 0..system.event_count() {
+            let before = start.event(index, t0);
+            let after = system.event(index, *t);
+            if !before.direction.is_crossing(before.value, after.value) {
+                continue;
+            }
+            // The step starts just past the root of the event handled last,
+            // which is not a crossing again
+            if let Some(last) = self.last
+                && last.index == index
+                && last.time == t0.to_f64()
+                && before.value.abs().to_f64() <= last.residual
+            {
+                continue;
+            }
+            // Search only before the earliest crossing found so far
+            let (end, value) = match (&earliest, &best) {
+                (Some((_, offset, _)), Some(state)) => {
+                    let value = state.event(index, t0 + *offset).value;
+                    if !before.direction.is_crossing(before.value, value) {
+                        continue;
+                    }
+                    (*offset, value)
+                }
+                _ => (dt, after.value),
+            };
+            let trial = trial.get_or_insert_with(|| system.clone());
+            let best = best.get_or_insert_with(|| system.clone());
+            let offset = self.locate(
+                interpolant,
+                trial,
+                best,
+                t0,
+                index,
+                before.value,
+                end,
+                value,
+            );
+            earliest = Some((index, offset, after.terminal));
+        }
+
+        let (index, offset, terminal) = earliest?;
+        if let Some(state) = best {
+            *system = state;
+        }
+        *t = t0 + offset;
+        self.last = Some(HandledEvent {
+            index,
+            time: t.to_f64(),
+            residual: system.event(index, *t).value.abs().to_f64(),
+        });
+        system.handle_event(index, *t);
+        Some(EventHit {
+            index,
+            time: *t,
+            terminal,
+        })
+    }
+
+    /// Locate zero crossing of event function within `(0, end]` after `t0`.
+    ///
+    /// Returns the end of the final bracket, at which the function already
+    /// crossed zero, and moves `best` to the system state there. Both `trial`
+    /// and `best` are used as buffers for states within the step.
+    #[allow(clippy::too_many_arguments)]
+    fn locate<T: Scalar, S: Solver<T>, Y: EventSystem<S, T> + Clone>(
+        &self,
+        interpolant: &impl Interpolant<T, Y>,
+        trial: &mut Y,
+        best: &mut Y,
+        t0: T,
+        index: usize,
+        g_start: T,
+        end: T,
+        g_end: T,
+    ) -> T {
+        let tolerance = T::from_f32(self.tolerance);
+        let half = T::from_f32(0.5);
+
+        let (mut a, mut ga) = (T::ZERO, g_start);
+        let (mut b, mut gb) = (end, g_end);
+        let mut found = false;
+        // Which end of the bracket was retained at the previous iteration: -1, 0 or 1
+        let mut retained = 0;
+        for _ in 0..self.max_iterations {
+            if b - a <= tolerance {
+                break;
+            }
+            // Secant point, guarded against rounding out of the bracket
+            let mut theta = b - gb * (b - a) / (gb - ga);
+            if !(theta > a && theta < b) {
+                theta = half * (a + b);
+            }
+
+            interpolant.state_at(trial, theta);
+            let g = trial.event(index, t0 + theta).value;
+            let zero = T::ZERO;
+            if (ga < zero && g < zero) || (ga > zero && g > zero) {
+                // Crossing is after θ
+                a = theta;
+                ga = g;
+                if retained == 1 {
+                    gb *= half;
+                }
+                retained = 1;
+            } else {
+                // Crossing is at or before θ
+                b = theta;
+                gb = g;
+                core::mem::swap(trial, best);
+                found = true;
+                if retained == -1 {
+                    ga *= half;
+                }
+                retained = -1;
+            }
+        }
+
+        if !found {
+            interpolant.state_at(best, b);
+        }
+        b
+    }
+}
+
+/// Step repeatedly until `t_end` or a terminal event.
+fn integrate_events<T: Scalar>(
+    t: &mut T,
+    t_end: T,
+    dt: T,
+    mut solve_step: impl FnMut(&mut T, T) -> Option<EventHit<T>>,
+) -> Option<EventHit<T>> {
+    assert!(dt > T::ZERO, "Time step must be positive");
+    let mut last = None;
+    while *t < t_end {
+        let step = dt.min(t_end - *t);
+        if let Some(hit) = solve_step(t, step) {
+            last = Some(hit);
+            if hit.terminal {
+                break;
+            }
+        }
+    }
+    last
+}
+
+/// Source of system states within the last step.
+trait Interpolant<T: Scalar, Y> {
+    /// Move `state`, a copy of the system at the end of the step, to time `t0 + offset`.
+    fn state_at(&self, state: &mut Y, offset: T);
+}
+
+/// States obtained by stepping from the beginning of the step.
+struct Restep<'a, S, Y, T> {
+    solver: &'a S,
+    start: &'a Y,
+    t0: T,
+}
+
+impl<T: Scalar, S: Solver<T>, Y: System<S, T> + Clone> Interpolant<T, Y> for Restep<'_, S, Y, T> {
+    fn state_at(&self, state: &mut Y, offset: T) {
+        state.clone_from(self.start);
+        let mut t = self.t0;
+        self.solver.solve_step(state, &mut t, offset);
+    }
+}
+
+/// States obtained from the dense output of the solver.
+struct Dense<'a, S, T> {
+    solver: &'a S,
+    dt: T,
+}
+
+impl<T: Scalar, S: DenseSolver<T>, Y: System<S, T>> Interpolant<T, Y> for Dense<'_, S, T> {
+    fn state_at(&self, state: &mut Y, offset: T) {
+        // Dense output only depends on the storage of the step, which is kept intact
+        self.solver.interpolate(state, self.dt, offset / self.dt);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{Param, Scalar, Solver, System, TrySystem, Var, Visitor};
+
+/// Adapter stepping a [`TrySystem`] with [`Solver::solve_step`].
+///
+/// Keeps the first error of evaluation of derivatives. After that, further
+/// evaluations and solver stages are skipped, so that the rest of the step
+/// leaves the state intact.
+pub(crate) struct Fallible<'a, Y, E> {
+    system: &'a mut Y,
+    /// Whether the solver has visited variables in this step.
+    visited: bool,
+    error: Option<E>,
+}
+
+impl<'a, Y, E> Fallible<'a, Y, E> {
+    pub(crate) fn new(system: &'a mut Y) -> Self {
+        Self {
+            system,
+            visited: false,
+            error: None,
+        }
+    }
+
+    /// Return the error, restoring the state before the step if there is one.
+    pub(crate) fn finish<S, T>(mut self, solver: &S) -> Result<(), E>
+    where
+        S: Solver<T> + ?Sized,
+        T: Scalar,
+        Y: TrySystem<S, T, Error = E>,
+    {
+        let Some(error) = self.error.take() else {
+            return Ok(());
+        };
+        if self.visited {
+            // Solver may have moved values within the step
+            solver.restore_step(&mut self);
+        } else {
+            self.system.visit_vars(&mut ResetDerivs);
+        }
+        Err(error)
+    }
+}
+
+impl<S, T, Y> System<S, T> for Fallible<'_, Y, Y::Error>
+where
+    S: Solver<T> + ?Sized,
+    T: Scalar,
+    Y: TrySystem<S, T>,
+{
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        if self.error.is_none()
+            && let Err(error) = self.system.try_compute_derivs(ctx)
+        {
+            self.error = Some(error);
+        }
+    }
+
+    fn visit_vars<V: Visitor<S, T>>(&mut self, visitor: &mut V) {
+        if self.error.is_none() {
+            self.visited = true;
+            self.system.visit_vars(visitor);
+        }
+    }
+}
+
+/// Visitor resetting derivatives of all variables.
+pub(crate) struct ResetDerivs;
+
+impl<S: Solver<T> + ?Sized, T: Scalar> Visitor<S, T> for ResetDerivs {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, S>) {
+        var.deriv = var.value.zero_deriv();
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{Param, Scalar, Solver, System, Var, Visitor};
+use alloc::{format, string::String, vec::Vec};
+use core::ops::Range;
+
+/// Position of a variable in a flattened state vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VarLayout {
+    /// Name of the variable, `var0`, `var1`, ... unless set with [`Layout::with_names`].
+    pub name: String,
+    /// Index of the first component.
+    pub offset: usize,
+    /// Number of components, see [`Param::flat_dim`].
+    pub dim: usize,
+}
+
+impl VarLayout {
+    /// Range of components of the variable.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.dim
+    }
+}
+
+/// Description of a flattened state, with one entry per variable in visiting order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    vars: Vec<VarLayout>,
+}
+
+impl Layout {
+    /// Entries of all variables.
+    pub fn vars(&self) -> &[VarLayout] {
+        &self.vars
+    }
+
+    /// Entry of the variable with the given name.
+    pub fn get(&self, name: &str) -> Option<&VarLayout> {
+        self.vars.iter().find(|var| var.name == name)
+    }
+
+    /// Total number of components.
+    pub fn dim(&self) -> usize {
+        self.vars.last().map_or(0, |var| var.offset + var.dim)
+    }
+
+    /// Name the variables in visiting order.
+    ///
+    /// # Panics
+    /// If the number of names differs from the number of variables.
+    pub fn with_names<N: Into<String>>(mut self, names: impl IntoIterator<Item = N>) -> Self {
+        let mut count = 0;
+        for (var, name) in self.vars.iter_mut().zip(names) {
+            var.name = name.into();
+            count += 1;
+        }
+        assert_eq!(
+            count,
+            self.vars.len(),
+            "Number of names must match number of variables"
+        );
+        self
+    }
+}
+
+/// Visitor packing values of all variables into a contiguous vector.
+///
+/// Values are appended in visiting order using [`Param::write_flat`], while their
+/// positions are recorded in a [`Layout`]. See [`flatten_state`] for a shortcut.
+#[derive(Clone, Debug, Default)]
+pub struct Flatten<T: Scalar = f32> {
+    data: Vec<T>,
+    layout: Layout,
+}
+
+impl<T: Scalar> Flatten<T> {
+    /// Create a visitor with an empty state vector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packed values.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Layout of the packed values.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Take the packed values and their layout.
+    pub fn into_parts(self) -> (Vec<T>, Layout) {
+        (self.data, self.layout)
+    }
+}
+
+impl<S: Solver<T>, T: Scalar> Visitor<S, T> for Flatten<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, S>) {
+        let offset = self.data.len();
+        let dim = var.value.flat_dim();
+        self.data.resize(offset + dim, T::ZERO);
+        var.value.write_flat(&mut self.data[offset..]);
+        let name = format!("var{}", self.layout.vars.len());
+        self.layout.vars.push(VarLayout { name, offset, dim });
+    }
+}
+
+/// Visitor restoring values of all variables from a contiguous vector.
+///
+/// Reverse of [`Flatten`]: values are read in visiting order using [`Param::read_flat`].
+/// Derivatives and solver storage are left intact. See [`unflatten_state`] for a shortcut.
+pub struct Unflatten<'a, T: Scalar = f32> {
+    data: &'a [T],
+    offset: usize,
+}
+
+impl<'a, T: Scalar> Unflatten<'a, T> {
+    /// Create a visitor reading values from `data`.
+    pub fn new(data: &'a [T]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// Number of components read so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<S: Solver<T>, T: Scalar> Visitor<S, T> for Unflatten<'_, T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, S>) {
+        let end = self.offset + var.value.flat_dim();
+        assert!(end <= self.data.len(), "State vector is too short");
+        var.value.read_flat(&self.data[self.offset..end]);
+        self.offset = end;
+    }
+}
+
+/// Pack values of all variables of the system into a vector.
+///
+/// # Example
+/// ```
+/// use phy::{Euler, Rot2, Solver, System, Var, Visitor, flatten_state, unflatten_state};
+/// use glam::Vec2;
+///
+/// struct Body<S: Solver> {
+///     pos: Var<Vec2, S>,
+///     rot: Var<Rot2, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Body<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {}
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.pos);
+///         visitor.apply(&mut self.rot);
+///     }
+/// }
+///
+/// let mut body = Body::<Euler> {
+///     pos: Var::new(Vec2::new(1.0, 2.0)),
+///     rot: Var::new(Rot2::from_angle(0.5)),
+/// };
+/// let (mut state, layout) = flatten_state(&mut body);
+/// let layout = layout.with_names(["pos", "rot"]);
+/// assert_eq!(state, [1.0, 2.0, 0.5]);
+/// assert_eq!(layout.get("rot").unwrap().range(), 2..3);
+///
+/// state[1] = 3.0;
+/// unflatten_state(&mut body, &state);
+/// assert_eq!(*body.pos, Vec2::new(1.0, 3.0));
+/// ```
+pub fn flatten_state<S, T, Y>(system: &mut Y) -> (Vec<T>, Layout)
+where
+    S: Solver<T>,
+    T: Scalar,
+    Y: System<S, T>,
+{
+    let mut visitor = Flatten::new();
+    system.visit_vars(&mut visitor);
+    visitor.into_parts()
+}
+
+/// Restore values of all variables of the system from a vector packed by [`flatten_state`].
+///
+/// # Panics
+/// If the length of `data` differs from the size of the state.
+pub fn unflatten_state<S, T, Y>(system: &mut Y, data: &[T])
+where
+    S: Solver<T>,
+    T: Scalar,
+    Y: System<S, T>,
+{
+    let mut visitor = Unflatten::new(data);
+    system.visit_vars(&mut visitor);
+    assert_eq!(visitor.offset(), data.len(), "State vector is too long");
+}
@@ -0,0 +1,244 @@
+use crate::{Scalar, Solver, System};
+
+/// A conservative system which exposes its integrals of motion.
+///
+/// Implemented by systems whose total energy is conserved by the exact solution,
+/// so that numerical errors of a solver can be measured by [`EnergyMonitor`].
+/// Systems which also conserve momentum or angular momentum may expose them too.
+///
+/// # Example
+/// ```
+/// use phy::{Hamiltonian, Solver, System, Var, Visitor};
+/// use glam::Vec2;
+///
+/// struct Planet<S: Solver> {
+///     pos: Var<Vec2, S>,
+///     vel: Var<Vec2, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Planet<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply_pair(&mut self.pos, &mut self.vel);
+///     }
+/// }
+///
+/// impl<S: Solver> Hamiltonian for Planet<S> {
+///     fn energy(&self) -> f32 {
+///         0.5 * self.vel.length_squared() - 1.0 / self.pos.length()
+///     }
+///
+///     fn angular_momentum(&self) -> Option<[f32; 3]> {
+///         Some([0.0, 0.0, self.pos.perp_dot(*self.vel)])
+///     }
+/// }
+/// ```
+pub trait Hamiltonian<T: Scalar = f32> {
+    /// Total energy of the system in its current state.
+    fn energy(&self) -> T;
+
+    /// Total linear momentum, if it is conserved.
+    ///
+    /// Components of lower-dimensional systems are padded with zeros.
+    fn momentum(&self) -> Option<[T; 3]> {
+        None
+    }
+
+    /// Total angular momentum, if it is conserved.
+    ///
+    /// Angular momentum of planar systems is the `z` component.
+    fn angular_momentum(&self) -> Option<[T; 3]> {
+        None
+    }
+}
+
+/// Monitor of energy drift of a [`Hamiltonian`] system.
+///
+/// Energy is compared with its value at creation of the monitor after each observed
+/// step. Drifts of energy are relative to the magnitude of the initial energy, or
+/// absolute if it is zero. Drifts of momentum and angular momentum are absolute.
+///
+/// The drift of a single step shows the local error of a solver, while the drift over
+/// the run shows whether the error accumulates: it grows steadily for non-symplectic
+/// solvers such as [`Rk4`](crate::Rk4), and stays bounded for symplectic ones such as
+/// [`Verlet`](crate::Verlet) with a fixed time step.
+///
+/// # Example
+/// ```
+/// # use phy::{Hamiltonian, Solver, System, Var, Visitor};
+/// # struct Oscillator<S: Solver> {
+/// #     x: Var<f32, S>,
+/// #     v: Var<f32, S>,
+/// # }
+/// # impl<S: Solver> System<S> for Oscillator<S> {
+/// #     fn compute_derivs(&mut self, _: &S::Context) {
+/// #         self.x.deriv = *self.v;
+/// #         self.v.deriv = -*self.x;
+/// #     }
+/// #     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+/// #         visitor.apply_pair(&mut self.x, &mut self.v);
+/// #     }
+/// # }
+/// # impl<S: Solver> Hamiltonian for Oscillator<S> {
+/// #     fn energy(&self) -> f32 {
+/// #         0.5 * (*self.x * *self.x + *self.v * *self.v)
+/// #     }
+/// # }
+/// use phy::{EnergyMonitor, Verlet};
+///
+/// let mut system = Oscillator::<Verlet> {
+///     x: Var::new(1.0),
+///     v: Var::new(0.0),
+/// };
+/// let mut monitor = EnergyMonitor::new(&system);
+/// let mut t = 0.0;
+/// for _ in 0..10000 {
+///     monitor.solve_step(&Verlet, &mut system, &mut t, 0.01);
+/// }
+/// assert_eq!(monitor.steps(), 10000);
+/// assert!(monitor.max_energy_drift() < 1e-4);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EnergyMonitor<T: Scalar = f32> {
+    initial_energy: T,
+    energy: T,
+    steps: usize,
+    step_drift: T,
+    max_step_drift: T,
+    max_energy_drift: T,
+    momentum: Conserved<T>,
+    angular_momentum: Conserved<T>,
+}
+
+impl<T: Scalar> EnergyMonitor<T> {
+    /// Create a monitor of the system in its initial state.
+    pub fn new<H: Hamiltonian<T>>(system: &H) -> Self {
+        let energy = system.energy();
+        Self {
+            initial_energy: energy,
+            energy,
+            steps: 0,
+            step_drift: T::ZERO,
+            max_step_drift: T::ZERO,
+            max_energy_drift: T::ZERO,
+            momentum: Conserved::new(system.momentum()),
+            angular_momentum: Conserved::new(system.angular_momentum()),
+        }
+    }
+
+    /// Perform one step with [`Solver::solve_step`] and observe the system after it.
+    ///
+    /// Returns the relative drift of energy in this step, see [`step_drift`](Self::step_drift).
+    pub fn solve_step<S, Y>(&mut self, solver: &S, system: &mut Y, t: &mut T, dt: T) -> T
+    where
+        S: Solver<T>,
+        Y: System<S, T> + Hamiltonian<T>,
+    {
+        solver.solve_step(system, t, dt);
+        self.observe(system)
+    }
+
+    /// Observe the system after a step taken by other means, e.g. an adaptive step.
+    ///
+    /// Returns the relative drift of energy since the previous observation.
+    pub fn observe<H: Hamiltonian<T>>(&mut self, system: &H) -> T {
+        let energy = system.energy();
+        self.step_drift = (energy - self.energy) / self.scale();
+        self.energy = energy;
+        self.steps += 1;
+
+        self.max_step_drift = self.max_step_drift.max(self.step_drift.abs());
+        self.max_energy_drift = self.max_energy_drift.max(self.energy_drift().abs());
+        self.momentum.observe(system.momentum());
+        self.angular_momentum.observe(system.angular_momentum());
+        self.step_drift
+    }
+
+    /// Magnitude of the initial energy used to make drifts relative.
+    fn scale(&self) -> T {
+        if self.initial_energy == T::ZERO {
+            T::ONE
+        } else {
+            self.initial_energy.abs()
+        }
+    }
+
+    /// Number of observed steps.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Energy of the system at creation of the monitor.
+    pub fn initial_energy(&self) -> T {
+        self.initial_energy
+    }
+
+    /// Energy of the system at the last observation.
+    pub fn energy(&self) -> T {
+        self.energy
+    }
+
+    /// Signed relative drift of energy in the last observed step: `(E_n - E_{n-1}) / |E_0|`.
+    pub fn step_drift(&self) -> T {
+        self.step_drift
+    }
+
+    /// Largest magnitude of the drift of energy in a single step.
+    pub fn max_step_drift(&self) -> T {
+        self.max_step_drift
+    }
+
+    /// Signed relative drift of energy over the run: `(E_n - E_0) / |E_0|`.
+    pub fn energy_drift(&self) -> T {
+        (self.energy - self.initial_energy) / self.scale()
+    }
+
+    /// Largest magnitude of the drift of energy over the run.
+    pub fn max_energy_drift(&self) -> T {
+        self.max_energy_drift
+    }
+
+    /// Largest deviation of momentum from its initial value, if the system exposes it.
+    pub fn max_momentum_drift(&self) -> Option<T> {
+        self.momentum.max_drift()
+    }
+
+    /// Largest deviation of angular momentum from its initial value, if the system exposes it.
+    pub fn max_angular_momentum_drift(&self) -> Option<T> {
+        self.angular_momentum.max_drift()
+    }
+}
+
+/// Drift of an optional conserved vector quantity.
+#[derive(Clone, Copy, Debug)]
+struct Conserved<T: Scalar> {
+    initial: Option<[T; 3]>,
+    max_drift: T,
+}
+
+impl<T: Scalar> Conserved<T> {
+    fn new(initial: Option<[T; 3]>) -> Self {
+        Self {
+            initial,
+            max_drift: T::ZERO,
+        }
+    }
+
+    fn observe(&mut self, value: Option<[T; 3]>) {
+        if let (Some(initial), Some(value)) = (self.initial, value) {
+            let drift = (0..3)
+                .map(|i| (value[i] - initial[i]) * (value[i] - initial[i]))
+                .sum::<T>()
+                .sqrt();
+            self.max_drift = self.max_drift.max(drift);
+        }
+    }
+
+    fn max_drift(&self) -> Option<T> {
+        self.initial.map(|_| self.max_drift)
+    }
+}
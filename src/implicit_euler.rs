@@ -0,0 +1,402 @@
+use crate::{
+    Context, Deriv, ErrorNorm, Norm, Param, Scalar, Solver, System, Tolerance, Var, Visitor,
+};
+use alloc::vec::Vec;
+
+/// The implicit (backward) Euler method for stiff systems.
+///
+/// Unlike explicit methods, backward Euler evaluates derivatives at the end
+/// of the step. It is only first-order accurate, but it is stable for
+/// arbitrarily stiff systems (e.g. hard contact springs) at any time step.
+///
+/// # Algorithm
+///
+/// For a differential equation dy/dt = f(t, y), backward Euler solves:
+/// ```text
+/// y_{n+1} = y_n + f(t_{n+1}, y_{n+1}) * h
+/// ```
+///
+/// The unknown increment `δ` is expressed in the derivative (tangent) space
+/// of each variable, so that `y_{n+1} = y_n.step(δ, 1)`. This handles
+/// rotations correctly: for [`Rot3`](crate::Rot3) the increment is a rotation
+/// vector rather than raw quaternion components. The nonlinear equation
+/// ```text
+/// G(δ) = δ - h * f(y_n.step(δ, 1)) = 0
+/// ```
+/// is solved with Newton iterations `δ -= J⁻¹ G(δ)`, starting from `δ = 0`.
+/// The Jacobian `J = I - h * ∂f/∂δ` is built by finite differences:
+/// every scalar component of every variable is perturbed in turn and
+/// derivatives of the whole system are recomputed.
+///
+/// If the Jacobian is singular, the increment is updated once by fixed-point
+/// iteration `δ = h * f(y_n.step(δ, 1))` instead and iterations stop. At the first
+/// iteration this is an explicit Euler step, so the state is never left at `y_n`
+/// while time advances.
+///
+/// # Cost
+///
+/// Each Newton iteration computes derivatives `n + 1` times, where `n` is
+/// the total number of scalar components in the system, and solves a dense
+/// `n × n` linear system. The method is intended for small stiff systems.
+///
+/// # Example
+/// ```
+/// use phy::{ImplicitEuler, Solver, System, Var, Visitor};
+///
+/// /// Very stiff spring that would make explicit methods explode at this step.
+/// struct Spring<S: Solver> {
+///     pos: Var<f32, S>,
+///     vel: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Spring<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -1e6 * *self.pos - 1e3 * *self.vel;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.pos);
+///         visitor.apply(&mut self.vel);
+///     }
+/// }
+///
+/// let solver = ImplicitEuler::default();
+/// let mut system = Spring { pos: Var::new(1.0), vel: Var::new(0.0) };
+/// let mut t = 0.0;
+/// for _ in 0..100 {
+///     solver.solve_step(&mut system, &mut t, 0.01);
+/// }
+/// assert!(system.pos.abs() < 1e-3);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ImplicitEuler {
+    /// Newton iterations stop when the last update is within this tolerance.
+    ///
+    /// The absolute part also limits how small finite-difference perturbations
+    /// of variables with values close to zero can become.
+    pub tolerance: Tolerance,
+    /// The way update sizes of individual variables are combined.
+    pub norm: Norm,
+    /// Maximum number of Newton iterations per step.
+    ///
+    /// If iterations do not converge, the last iterate is accepted.
+    pub max_iterations: usize,
+}
+
+impl Default for ImplicitEuler {
+    fn default() -> Self {
+        Self {
+            tolerance: Tolerance::new(1e-5, 1e-5),
+            norm: Norm::default(),
+            max_iterations: 10,
+        }
+    }
+}
+
+impl ImplicitEuler {
+    /// Create a solver with the given tolerance of Newton iterations.
+    pub fn new(tolerance: Tolerance) -> Self {
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+}
+
+/// Storage required by the implicit Euler solver for each variable.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, P::Deriv: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, P::Deriv: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ImplicitEulerStorage<P: Param> {
+    /// The initial value at the beginning of the step (y_n)
+    init_value: P,
+    /// Current Newton iterate of the increment (δ)
+    delta: P::Deriv,
+    /// Derivative at the current iterate, f(y_n.step(δ, 1))
+    base_deriv: P::Deriv,
+}
+
+/// Context of derivative computation in the implicit Euler method.
+pub struct ImplicitEulerStep<T: Scalar = f32> {
+    /// Time at the beginning of the step (t_n)
+    t: T,
+    dt: T,
+}
+
+impl<T: Scalar> Context<ImplicitEuler, T> for ImplicitEulerStep<T> {
+    /// Derivatives are always evaluated at the end of the step.
+    fn time(&self) -> T {
+        self.t + self.dt
+    }
+
+    fn time_step(&self) -> T {
+        self.dt
+    }
+}
+
+/// Size of finite-difference perturbation for a variable with the given value.
+///
+/// Values much smaller than absolute tolerance are considered to be of its size.
+/// The size is floored at machine epsilon, so that the perturbation is not zero
+/// for a value at zero even if its absolute tolerance is zero.
+fn perturbation<P: Param>(value: &P, tolerance: Tolerance<P::Scalar>) -> P::Scalar {
+    let size = value.magnitude().max(tolerance.abs).max(P::Scalar::EPSILON);
+    P::Scalar::EPSILON.sqrt() * size
+}
+
+/// Zero derivative with the same number of components as `deriv`.
+fn zero_like<D: Deriv>(deriv: &D) -> D {
+    let mut zero = deriv.clone();
+    zero.scale(D::Scalar::ZERO);
+    zero
+}
+
+/// Visitor that stores derivatives at the current iterate and computes the residual `G(δ)`.
+struct Residual<'a, T: Scalar> {
+    /// Whether this is the first iteration of the step.
+    first: bool,
+    dt: T,
+    residual: &'a mut Vec<T>,
+}
+
+impl<T: Scalar> Visitor<ImplicitEuler, T> for Residual<'_, T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, ImplicitEuler>) {
+        let storage = &mut var.storage;
+        if self.first {
+            // Save initial value y_n and start from zero increment
+            storage.init_value.clone_from(&var.value);
+            storage.delta = zero_like(&var.deriv);
+        }
+        storage.base_deriv = core::mem::take(&mut var.deriv);
+
+        // G(δ) = δ - h * f(y_n.step(δ, 1))
+        let base = &storage.base_deriv;
+        self.residual.extend(
+            (0..base.dim()).map(|i| storage.delta.component(i) - self.dt * base.component(i)),
+        );
+    }
+}
+
+/// Visitor that perturbs a single scalar component of the increment.
+struct Perturb<T: Scalar> {
+    /// Index of the perturbed component in the whole system.
+    index: usize,
+    /// Offset of the current variable's components.
+    offset: usize,
+    /// Default tolerance of variables.
+    tolerance: Tolerance<T>,
+    /// Size of the perturbation, set by the perturbed variable.
+    eps: T,
+}
+
+impl<T: Scalar> Visitor<ImplicitEuler, T> for Perturb<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, ImplicitEuler>) {
+        let storage = &var.storage;
+        let dim = storage.base_deriv.dim();
+        if (self.offset..self.offset + dim).contains(&self.index) {
+            // y = y_n.step(δ + ε * e_j, 1)
+            self.eps = perturbation(&var.value, var.tolerance().unwrap_or(self.tolerance));
+            let mut delta = storage.delta.clone();
+            *delta.component_mut(self.index - self.offset) += self.eps;
+            var.value.clone_from(&storage.init_value);
+            var.value.step(&delta, T::ONE);
+        }
+        self.offset += dim;
+    }
+}
+
+/// Visitor that collects a column of the Jacobian and undoes the perturbation.
+struct JacobianColumn<'a, T: Scalar> {
+    /// Index of the perturbed component in the whole system.
+    index: usize,
+    /// Offset of the current variable's components.
+    offset: usize,
+    /// Size of the whole system.
+    size: usize,
+    /// Size of the perturbation.
+    eps: T,
+    dt: T,
+    jacobian: &'a mut [T],
+}
+
+impl<T: Scalar> Visitor<ImplicitEuler, T> for JacobianColumn<'_, T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, ImplicitEuler>) {
+        let storage = &var.storage;
+        let dim = storage.base_deriv.dim();
+
+        // J_ij = δ_ij - h * (f_i(δ + ε * e_j) - f_i(δ)) / ε, without the identity part
+        for i in 0..dim {
+            let diff = var.deriv.component(i) - storage.base_deriv.component(i);
+            self.jacobian[(self.offset + i) * self.size + self.index] = -self.dt * diff / self.eps;
+        }
+        if (self.offset..self.offset + dim).contains(&self.index) {
+            // Restore the current iterate
+            var.value.clone_from(&storage.init_value);
+            var.value.step(&storage.delta, T::ONE);
+        }
+
+        var.deriv = var.value.zero_deriv();
+        self.offset += dim;
+    }
+}
+
+/// Visitor that applies the Newton update `Δ` to the increment.
+///
+/// The update is stored in place of derivative to be collected by [`ErrorNorm`].
+struct Update<'a, T: Scalar> {
+    offset: usize,
+    update: &'a [T],
+}
+
+impl<T: Scalar> Visitor<ImplicitEuler, T> for Update<'_, T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, ImplicitEuler>) {
+        let storage = &mut var.storage;
+        let mut update = zero_like(&storage.base_deriv);
+        for i in 0..update.dim() {
+            *update.component_mut(i) = self.update[self.offset + i];
+        }
+        self.offset += update.dim();
+
+        // δ = δ + Δ, y = y_n.step(δ, 1)
+        storage.delta.add_scaled(&update, T::ONE);
+        var.value.clone_from(&storage.init_value);
+        var.value.step(&storage.delta, T::ONE);
+        var.deriv = update;
+    }
+}
+
+/// Visitor that restores variables to their values at the beginning of the step.
+struct Restore;
+
+impl<T: Scalar> Visitor<ImplicitEuler, T> for Restore {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, ImplicitEuler>) {
+        var.value.clone_from(&var.storage.init_value);
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+/// Solve linear system `a * x = b` in place using Gaussian elimination with partial pivoting.
+///
+/// `a` is a row-major `n × n` matrix, the solution is written to `b`.
+/// Returns `false` if the matrix is singular.
+fn solve_linear<T: Scalar>(a: &mut [T], b: &mut [T]) -> bool {
+    let n = b.len();
+    for col in 0..n {
+        // Choose the row with the largest pivot
+        let pivot = (col..n)
+            .max_by(|&i, &j| {
+                let (x, y) = (a[i * n + col].abs(), a[j * n + col].abs());
+                x.partial_cmp(&y).unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        let max = a[pivot * n + col].abs();
+        if max == T::ZERO || max.is_nan() {
+            return false;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap(col * n + k, pivot * n + k);
+            }
+            b.swap(col, pivot);
+        }
+
+        // Eliminate the column below the pivot
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            if factor != T::ZERO {
+                for k in col..n {
+                    a[row * n + k] -= factor * a[col * n + k];
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+
+    // Back substitution
+    for row in (0..n).rev() {
+        let sum: T = (row + 1..n).map(|k| a[row * n + k] * b[k]).sum();
+        b[row] = (b[row] - sum) / a[row * n + row];
+    }
+    true
+}
+
+impl<T: Scalar> Solver<T> for ImplicitEuler {
+    type Context = ImplicitEulerStep<T>;
+    type Storage<P: Param<Scalar = T>> = ImplicitEulerStorage<P>;
+
+    /// Perform one backward Euler step for the given system.
+    fn solve_step<S: System<Self, T>>(&self, system: &mut S, t: &mut T, dt: T) {
+        let ctx = ImplicitEulerStep { t: *t, dt };
+        let mut residual = Vec::new();
+        let mut jacobian = Vec::new();
+        let mut update = Vec::new();
+        for iteration in 0..self.max_iterations.max(1) {
+            // Compute derivatives and residual at the current iterate
+            system.compute_derivs(&ctx);
+            residual.clear();
+            system.visit_vars(&mut Residual {
+                first: iteration == 0,
+                dt,
+                residual: &mut residual,
+            });
+
+            // Build the Jacobian column by column
+            let size = residual.len();
+            jacobian.clear();
+            jacobian.resize(size * size, T::ZERO);
+            for index in 0..size {
+                let mut perturb = Perturb {
+                    index,
+                    offset: 0,
+                    tolerance: self.tolerance.cast(),
+                    eps: T::ZERO,
+                };
+                system.visit_vars(&mut perturb);
+                system.compute_derivs(&ctx);
+                system.visit_vars(&mut JacobianColumn {
+                    index,
+                    offset: 0,
+                    size,
+                    eps: perturb.eps,
+                    dt,
+                    jacobian: &mut jacobian,
+                });
+                jacobian[index * size + index] += T::ONE;
+            }
+
+            // Newton update: Δ = -J⁻¹ G(δ)
+            update.clear();
+            update.extend(residual.iter().map(|&r| -r));
+            let singular = !solve_linear(&mut jacobian, &mut update);
+            if singular {
+                // Fixed-point update Δ = -G(δ), i.e. δ = h * f(y_n.step(δ, 1))
+                update.clear();
+                update.extend(residual.iter().map(|&r| -r));
+            }
+            system.visit_vars(&mut Update {
+                offset: 0,
+                update: &update,
+            });
+
+            // Stop when the update is within tolerance
+            let mut norm = ErrorNorm::new(self.norm, self.tolerance.cast());
+            system.visit_vars(&mut norm);
+            if singular || norm.value() <= T::ONE {
+                break;
+            }
+        }
+
+        *t += dt;
+    }
+
+    fn restore_step<S: System<Self, T>>(&self, system: &mut S) {
+        system.visit_vars(&mut Restore);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{AdaptiveSolver, Scalar, Solver, System};
+
+/// Steps shorter than this fraction of the time step are merged into the previous step.
+const SLIVER: f64 = 1e-3;
+
+/// Integrate the system with fixed step `dt` from `t0` to `t1`.
+///
+/// The observer is called after each step with the time at the end of the step and
+/// the system. The number of steps is computed in advance and step times are
+/// `t0 + k * dt` rather than accumulated, so the final time is exactly `t1`
+/// regardless of rounding errors. The last step is shortened to end at `t1`, or is
+/// lengthened by less than 0.1% of `dt` instead of taking an extra tiny step.
+///
+/// Integration only runs forward in time: if `t1 <= t0`, no steps are taken, the
+/// observer is not called and the system is left unchanged.
+///
+/// Returns the number of steps taken.
+///
+/// # Example
+/// ```
+/// use phy::{Rk4, Solver, System, Var, Visitor, integrate};
+///
+/// struct Decay<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Decay<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = -*self.x;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let mut system = Decay::<Rk4> { x: Var::new(1.0) };
+/// let mut last = 0.0;
+/// let steps = integrate(&mut system, &Rk4, 0.0, 1.05, 0.1, |t, system| {
+///     assert!((*system.x - (-t).exp()).abs() < 1e-5);
+///     last = t;
+/// });
+/// assert_eq!(steps, 11);
+/// assert_eq!(last, 1.05);
+/// ```
+pub fn integrate<T, S, Y, F>(
+    system: &mut Y,
+    solver: &S,
+    t0: T,
+    t1: T,
+    dt: T,
+    mut observer: F,
+) -> usize
+where
+    T: Scalar,
+    S: Solver<T>,
+    Y: System<S, T>,
+    F: FnMut(T, &Y),
+{
+    assert!(dt > T::ZERO, "Time step must be positive");
+    if t1 <= t0 {
+        return 0;
+    }
+    let steps = (libm::ceil(((t1 - t0) / dt).to_f64() - SLIVER) as usize).max(1);
+    let mut t = t0;
+    for k in 1..=steps {
+        let end = if k == steps {
+            t1
+        } else {
+            t0 + T::from_f64(k as f64) * dt
+        };
+        let h = end - t;
+        solver.solve_step(system, &mut t, h);
+        t = end;
+        observer(t, system);
+    }
+    steps
+}
+
+/// Integrate the system with an adaptive solver from `t0` to `t1`.
+///
+/// Integration starts with the step `dt`, then the step size suggested by the solver
+/// is used. The observer is called after each accepted step with the time at the end
+/// of the step and the system. The last step is shortened to end exactly at `t1`.
+///
+/// As with [`integrate`], no steps are taken if `t1 <= t0`.
+///
+/// # Panics
+/// If the solver cannot take a step because its error is not finite even at the
+/// smallest step size, see [`AdaptiveStep::forced`](crate::AdaptiveStep::forced).
+///
+/// Returns the number of accepted steps.
+pub fn integrate_adaptive<T, S, Y, F>(
+    system: &mut Y,
+    solver: &S,
+    t0: T,
+    t1: T,
+    dt: T,
+    mut observer: F,
+) -> usize
+where
+    T: Scalar,
+    S: AdaptiveSolver<T>,
+    Y: System<S, T>,
+    F: FnMut(T, &Y),
+{
+    assert!(dt > T::ZERO, "Time step must be positive");
+    let mut t = t0;
+    let mut dt = dt;
+    let mut steps = 0;
+    while t < t1 {
+        let remaining = t1 - t;
+        let last = remaining <= dt * T::from_f64(1.0 + SLIVER);
+        let h = if last { remaining } else { dt };
+        let step = solver.adaptive_step(system, &mut t, h);
+        assert!(
+            !step.forced || step.taken != T::ZERO,
+            "Error is not finite at the smallest step size"
+        );
+        if last && step.taken == h {
+            t = t1;
+        }
+        steps += 1;
+        observer(t, system);
+        dt = step.next;
+    }
+    steps
+}
@@ -0,0 +1,323 @@
+use crate::{DRot2, DRot3, Deriv, Param, Rot2, Rot3, Scalar};
+use glam::{DVec2, DVec3, Vec2, Vec3};
+
+/// 2D rigid body pose: rotation followed by translation (an element of SE(2)).
+///
+/// Transforms points from the body frame to the world frame as `rot * p + pos`.
+/// The derivative of a pose is a [`Twist2`] expressed in the body frame, which is
+/// integrated with the exact exponential map, so that a body moving with constant
+/// twist follows a circular arc.
+///
+/// # Example
+/// ```
+/// use phy::{Iso2, Param, Rot2, Twist2};
+/// use glam::Vec2;
+/// use core::f32::consts::PI;
+///
+/// // Drive forward along a quarter of the unit circle
+/// let mut pose = Iso2::default();
+/// pose.step(&Twist2::new(Vec2::X, 1.0), 0.5 * PI);
+/// assert!(pose.pos.abs_diff_eq(Vec2::new(1.0, 1.0), 1e-6));
+/// assert!((pose.rot.angle() - 0.5 * PI).abs() < 1e-6);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso2 {
+    /// Orientation of the body.
+    pub rot: Rot2,
+    /// Position of the body origin in the world frame.
+    pub pos: Vec2,
+}
+
+/// 3D rigid body pose: rotation followed by translation (an element of SE(3)).
+///
+/// Transforms points from the body frame to the world frame as `rot * p + pos`.
+/// The derivative of a pose is a [`Twist3`] expressed in the body frame, which is
+/// integrated with the exact exponential map, so that a body moving with constant
+/// twist follows a helix.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso3 {
+    /// Orientation of the body.
+    pub rot: Rot3,
+    /// Position of the body origin in the world frame.
+    pub pos: Vec3,
+}
+
+/// 2D pose in double precision, see [`Iso2`].
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DIso2 {
+    /// Orientation of the body.
+    pub rot: DRot2,
+    /// Position of the body origin in the world frame.
+    pub pos: DVec2,
+}
+
+/// 3D pose in double precision, see [`Iso3`].
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DIso3 {
+    /// Orientation of the body.
+    pub rot: DRot3,
+    /// Position of the body origin in the world frame.
+    pub pos: DVec3,
+}
+
+/// Velocity of a 2D rigid body expressed in its own frame, the derivative of [`Iso2`].
+///
+/// Components are numbered as `linear.x`, `linear.y`, `angular`.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Twist2 {
+    /// Velocity of the body origin in the body frame.
+    pub linear: Vec2,
+    /// Angular speed in radians per unit time.
+    pub angular: f32,
+}
+
+/// Velocity of a 3D rigid body expressed in its own frame, the derivative of [`Iso3`].
+///
+/// Components are numbered as `linear.x`, `linear.y`, `linear.z`, `angular.x`, ...
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Twist3 {
+    /// Velocity of the body origin in the body frame.
+    pub linear: Vec3,
+    /// Angular velocity in the body frame.
+    pub angular: Vec3,
+}
+
+/// 2D twist in double precision, see [`Twist2`].
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DTwist2 {
+    /// Velocity of the body origin in the body frame.
+    pub linear: DVec2,
+    /// Angular speed in radians per unit time.
+    pub angular: f64,
+}
+
+/// 3D twist in double precision, see [`Twist3`].
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DTwist3 {
+    /// Velocity of the body origin in the body frame.
+    pub linear: DVec3,
+    /// Angular velocity in the body frame.
+    pub angular: DVec3,
+}
+
+// Pose operations common for 2D and 3D
+
+macro_rules! impl_iso {
+    ($iso:ident, $twist:ident, $rot:ident, $vec:ty, $angular:ty, $scalar:ty) => {
+        impl $iso {
+            /// Create a pose from orientation and position.
+            pub fn new(rot: $rot, pos: $vec) -> Self {
+                Self { rot, pos }
+            }
+
+            /// Transform a point from the body frame to the world frame.
+            pub fn transform_point(&self, p: $vec) -> $vec {
+                self.rot.transform(p) + self.pos
+            }
+
+            /// Transform a vector (e.g. velocity) from the body frame to the world frame.
+            ///
+            /// Only the rotation is applied.
+            pub fn transform_vector(&self, v: $vec) -> $vec {
+                self.rot.transform(v)
+            }
+
+            /// Chain this pose with another pose.
+            ///
+            /// Returns a new pose that represents applying `self` then `other`.
+            pub fn chain(self, other: Self) -> Self {
+                Self {
+                    rot: self.rot.chain(other.rot),
+                    pos: other.transform_point(self.pos),
+                }
+            }
+
+            /// Get the inverse pose, transforming from the world frame to the body frame.
+            pub fn inverse(self) -> Self {
+                let rot = self.rot.inverse();
+                Self {
+                    rot,
+                    pos: -rot.transform(self.pos),
+                }
+            }
+        }
+
+        impl $twist {
+            /// Create a twist from linear and angular velocities in the body frame.
+            pub fn new(linear: $vec, angular: $angular) -> Self {
+                Self { linear, angular }
+            }
+        }
+
+        impl Param for $iso {
+            type Scalar = $scalar;
+            type Deriv = $twist;
+
+            /// Advance the pose by a twist in the body frame.
+            ///
+            /// The twist is assumed to be constant over the step and is integrated exactly:
+            /// `pose_{n+1} = pose_n * exp(twist * dt)`.
+            fn step(&mut self, twist: &$twist, dt: $scalar) {
+                *self = twist.exp(dt).chain(*self);
+            }
+
+            /// Orientation has no natural scale, so this is the magnitude of position.
+            fn magnitude(&self) -> $scalar {
+                self.pos.length()
+            }
+
+            /// Flattened to the components of rotation followed by position.
+            fn flat_dim(&self) -> usize {
+                self.rot.flat_dim() + self.pos.flat_dim()
+            }
+
+            fn write_flat(&self, out: &mut [$scalar]) {
+                let n = self.rot.flat_dim();
+                self.rot.write_flat(&mut out[..n]);
+                self.pos.write_flat(&mut out[n..]);
+            }
+
+            fn read_flat(&mut self, data: &[$scalar]) {
+                let n = self.rot.flat_dim();
+                self.rot.read_flat(&data[..n]);
+                self.pos.read_flat(&data[n..]);
+            }
+        }
+
+        impl Deriv for $twist {
+            type Scalar = $scalar;
+            fn scale(&mut self, factor: $scalar) {
+                self.linear *= factor;
+                self.angular *= factor;
+            }
+            fn add_scaled(&mut self, other: &Self, factor: $scalar) {
+                self.linear += other.linear * factor;
+                self.angular += other.angular * factor;
+            }
+            fn norm(&self) -> $scalar {
+                let angular = self.angular.norm();
+                Scalar::sqrt(self.linear.length_squared() + angular * angular)
+            }
+            fn dim(&self) -> usize {
+                self.linear.dim() + self.angular.dim()
+            }
+            fn component(&self, index: usize) -> $scalar {
+                let n = self.linear.dim();
+                if index < n {
+                    self.linear.component(index)
+                } else {
+                    self.angular.component(index - n)
+                }
+            }
+            fn component_mut(&mut self, index: usize) -> &mut $scalar {
+                let n = self.linear.dim();
+                if index < n {
+                    self.linear.component_mut(index)
+                } else {
+                    self.angular.component_mut(index - n)
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_iso2 {
+    ($iso:ident, $twist:ident, $rot:ident, $vec:ty, $scalar:ty, $sin:path) => {
+        impl_iso!($iso, $twist, $rot, $vec, $scalar, $scalar);
+
+        impl $iso {
+            /// Convert world-frame velocity of the body origin and angular speed to a twist.
+            pub fn body_twist(&self, vel: $vec, angular: $scalar) -> $twist {
+                $twist::new(self.rot.inverse().transform(vel), angular)
+            }
+
+            /// Velocity of the body origin in the world frame for the given twist.
+            pub fn world_velocity(&self, twist: &$twist) -> $vec {
+                self.rot.transform(twist.linear)
+            }
+        }
+
+        impl $twist {
+            /// Exponential map: the pose reached from identity by moving with this twist for `dt`.
+            pub fn exp(&self, dt: $scalar) -> $iso {
+                let theta = self.angular * dt;
+                let rho = self.linear * dt;
+                // sin(θ)/θ and (1 - cos(θ))/θ = 2 sin²(θ/2)/θ without cancellation
+                let (a, b) = if theta == 0.0 {
+                    (1.0, 0.0)
+                } else {
+                    let s = $sin(0.5 * theta);
+                    ($sin(theta) / theta, 2.0 * s * s / theta)
+                };
+                $iso {
+                    rot: $rot::from_angle(theta),
+                    pos: a * rho + b * rho.perp(),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_iso3 {
+    ($iso:ident, $twist:ident, $rot:ident, $vec:ty, $scalar:ty, $sin:path) => {
+        impl_iso!($iso, $twist, $rot, $vec, $vec, $scalar);
+
+        impl $iso {
+            /// Convert world-frame velocity of the body origin and angular velocity to a twist.
+            pub fn body_twist(&self, vel: $vec, angular: $vec) -> $twist {
+                let inv = self.rot.inverse();
+                $twist::new(inv.transform(vel), inv.transform(angular))
+            }
+
+            /// Velocity of the body origin and angular velocity in the world frame for the given twist.
+            pub fn world_velocity(&self, twist: &$twist) -> ($vec, $vec) {
+                (
+                    self.rot.transform(twist.linear),
+                    self.rot.transform(twist.angular),
+                )
+            }
+        }
+
+        impl $twist {
+            /// Exponential map: the pose reached from identity by moving with this twist for `dt`.
+            pub fn exp(&self, dt: $scalar) -> $iso {
+                let phi = self.angular * dt;
+                let rho = self.linear * dt;
+                let theta2 = phi.length_squared();
+                let theta = Scalar::sqrt(theta2);
+                // a = (1 - cos(θ))/θ², b = (θ - sin(θ))/θ³
+                let a = if theta == 0.0 {
+                    0.5
+                } else {
+                    let s = $sin(0.5 * theta) / (0.5 * theta);
+                    0.5 * s * s
+                };
+                let b = if theta < 0.1 {
+                    // Taylor series avoiding cancellation
+                    1.0 / 6.0 - theta2 / 120.0 + theta2 * theta2 / 5040.0
+                        - theta2 * theta2 * theta2 / 362880.0
+                } else {
+                    (theta - $sin(theta)) / (theta2 * theta)
+                };
+                let phi_rho = phi.cross(rho);
+                $iso {
+                    rot: $rot::from_scaled_axis(phi),
+                    pos: rho + a * phi_rho + b * phi.cross(phi_rho),
+                }
+            }
+        }
+    };
+}
+
+impl_iso2!(Iso2, Twist2, Rot2, Vec2, f32, libm::sinf);
+impl_iso2!(DIso2, DTwist2, DRot2, DVec2, f64, libm::sin);
+impl_iso3!(Iso3, Twist3, Rot3, Vec3, f32, libm::sinf);
+impl_iso3!(DIso3, DTwist3, DRot3, DVec3, f64, libm::sin);
@@ -41,23 +41,159 @@
 //! # Available Solvers
 //! - [`Euler`]: First-order explicit Euler method (simple, low accuracy).
 //! - [`Rk4`]: Fourth-order Runge-Kutta method (higher accuracy, more computation).
+//! - [`Rk4Dense`]: The same method keeping stage derivatives for dense output.
+//! - [`Dopri5`]: Dormand–Prince 5(4) method with adaptive step size control.
+//! - [`ButcherRk`]: Generic explicit Runge–Kutta method defined by a Butcher tableau,
+//!   see [`tableau`] module for available methods.
+//! - [`Verlet`]: Velocity Verlet symplectic method for second-order systems.
+//! - [`Symplectic`]: Splitting methods for second-order systems, from [`SymplecticEuler`]
+//!   to eighth order, see [`splitting`] module for available methods.
+//! - [`ImplicitEuler`]: Backward Euler method for stiff systems (requires `alloc` feature).
+//!
+//! # Dense Output
+//! Solvers implementing [`DenseSolver`] can interpolate the state at any time within
+//! the last step, so sampling times do not have to be aligned with steps. These are
+//! [`Rk4Dense`], [`Dopri5Dense`] and [`ButcherRk`] with a tableau implementing
+//! [`DenseTableau`] and dense output enabled with [`ButcherRk::with_dense_output`].
+//!
+//! # Deriving Systems
+//! With the `derive` feature, `#[derive(System)]` generates [`System::visit_vars`]
+//! visiting every field of a structure.
+//!
+//! # Integration Loops
+//! [`integrate`] and [`integrate_adaptive`] step a system over a time interval, ending
+//! exactly at its end, and call an observer after each step.
+//!
+//! # State Vectors
+//! With the `alloc` feature, [`flatten_state`] packs values of all variables of a system
+//! into a contiguous vector described by a [`Layout`], and [`unflatten_state`] restores them.
+//!
+//! # Snapshots
+//! [`Snapshot`] saves a clone of a system together with its time, including derivatives,
+//! solver storage and fields which are not variables, so that restoring it and stepping
+//! again reproduces the same trajectory bit for bit.
+//!
+//! # Recording
+//! With the `std` feature, [`Recorder`] samples named quantities of a system at fixed
+//! output times into a [`Trajectory`], which can be written as CSV or JSON Lines.
+//!
+//! # Fallible Systems
+//! Systems implementing [`TrySystem`] may fail to evaluate their derivatives, e.g. outside
+//! of the domain of a model. [`Solver::try_solve_step`] returns the error and restores the
+//! state before the step from solver storage.
+//!
+//! # Checked Stepping
+//! With the `alloc` feature, [`FiniteCheck`] performs steps which detect NaN and infinite
+//! numbers, reporting the offending variable and stage in a [`NonFiniteError`] and
+//! rolling the system back, so that the step can be retried with a smaller time step.
+//!
+//! # Serialization
+//! With the `serde` feature, [`Var`], its solver storage (e.g. [`Rk4Storage`]) and all
+//! parameter types implement `Serialize` and `Deserialize`, so systems can be saved to
+//! checkpoints. 2D rotations are stored as angles and 3D rotations as normalized
+//! quaternions `[x, y, z, w]`, which are validated when loaded.
+//!
+//! # Energy Diagnostics
+//! Conservative systems implementing [`Hamiltonian`] expose their total energy, and
+//! optionally momentum and angular momentum. [`EnergyMonitor`] records relative drift of
+//! energy per step and over the run, e.g. to compare [`Rk4`] with [`Symplectic`] solvers.
+//!
+//! # Real-Time Simulation
+//! [`Simulation`] owns a system and a solver and advances them with a fixed time step
+//! by frames of varying duration, providing interpolated states for rendering.
+//!
+//! # Events
+//! Systems implementing [`EventSystem`] declare scalar event functions, whose zero
+//! crossings are located within steps by [`EventLocator`], e.g. to model impacts.
 //!
 //! # Available Parameters
 //! - `f32`, `Vec2`, `Vec3` from `glam` for positions and linear quantities.
 //! - [`Rot2`], [`Rot3`] from [`rot`] module for rotations.
+//! - [`BodyRot3`] for rotations driven by angular velocity in the body frame.
+//! - [`Iso2`], [`Iso3`] for rigid body poses, with body-frame twists [`Twist2`], [`Twist3`] as derivatives.
+//!
+//! # Rigid Bodies
+//! The [`rigid`] module provides ready-made rigid body systems in 2D and 3D with
+//! mass, inertia and accumulation of forces and torques.
+//!
+//! # Precision
+//! Solvers are generic over the [`Scalar`] type of time and variables, `f32` by default.
+//! Systems that need double precision use `f64`, `DVec2`, `DVec3`, [`DRot2`], [`DRot3`],
+//! [`DIso2`] and [`DIso3`].
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod butcher;
+#[cfg(feature = "alloc")]
+mod check;
 mod euler;
+mod event;
+mod fallible;
+#[cfg(feature = "alloc")]
+mod flat;
+mod hamiltonian;
+#[cfg(feature = "alloc")]
+mod implicit_euler;
+mod integrate;
+mod iso;
+mod norm;
 mod param;
+#[cfg(feature = "std")]
+mod record;
+pub mod rigid;
 mod rk4;
 mod rot;
+mod scalar;
+mod simulation;
+mod snapshot;
+pub mod splitting;
+mod symplectic;
+pub mod tableau;
 mod var;
+mod verlet;
 
 #[cfg(test)]
 mod tests;
 
-pub use crate::{euler::Euler, param::*, rk4::Rk4, rot::*, var::*};
+use crate::fallible::Fallible;
+
+pub use crate::{
+    butcher::{
+        ButcherRk, ButcherStep, ButcherStorage, DenseTableau, Dopri5, Dopri5Dense, Dopri5Storage,
+        EmbeddedTableau, Tableau,
+    },
+    euler::Euler,
+    event::{Direction, Event, EventHit, EventLocator, EventSystem},
+    hamiltonian::{EnergyMonitor, Hamiltonian},
+    integrate::{integrate, integrate_adaptive},
+    iso::*,
+    norm::{ErrorNorm, Norm, Tolerance},
+    param::*,
+    rk4::{Rk4, Rk4Dense, Rk4DenseStorage, Rk4Storage},
+    rot::*,
+    scalar::Scalar,
+    simulation::{Frame, Simulation},
+    snapshot::Snapshot,
+    symplectic::{Splitting, Symplectic, SymplecticEuler, SymplecticStorage},
+    var::*,
+    verlet::{Verlet, VerletStorage},
+};
+
+#[cfg(feature = "alloc")]
+pub use crate::{
+    check::{CheckStage, CheckedPart, FiniteCheck, NonFiniteError},
+    flat::{Flatten, Layout, Unflatten, VarLayout, flatten_state, unflatten_state},
+    implicit_euler::{ImplicitEuler, ImplicitEulerStorage},
+};
+
+#[cfg(feature = "std")]
+pub use crate::record::{Recorder, Trajectory};
+
+#[cfg(feature = "derive")]
+pub use phy_derive::{System, Vars};
 
 /// A visitor that applies solver-specific operations to variables.
 ///
@@ -65,15 +201,32 @@ pub use crate::{euler::Euler, param::*, rk4::Rk4, rot::*, var::*};
 /// without the system needing to know the solver's internal details.
 /// Each solver defines its own visitor type that implements this trait.
 ///
-/// # Type Parameter
+/// # Type Parameters
 /// * `S` - The solver type that this visitor operates with.
-pub trait Visitor<S: Solver + ?Sized> {
+/// * `T` - The scalar type of time and variables, see [`Solver`].
+pub trait Visitor<S: Solver<T> + ?Sized, T: Scalar = f32> {
     /// Apply the visitor's operation to a single variable.
     ///
     /// This method is called by the system for each variable during
     /// a solver step. The visitor typically updates the variable's
     /// value using its derivative and solver-specific storage.
-    fn apply<P: Param>(&mut self, v: &mut Var<P, S>);
+    fn apply<P: Param<Scalar = T>>(&mut self, v: &mut Var<P, S>);
+
+    /// Apply the visitor's operation to a position-velocity pair.
+    ///
+    /// Second-order systems should visit their positions together with
+    /// corresponding velocities using this method. The value of `vel` is
+    /// the derivative of `pos`, and the derivative of `vel` is acceleration.
+    ///
+    /// Solvers that exploit this structure (e.g. [`Verlet`]) override this method.
+    /// By default both variables are visited independently with [`apply`](Self::apply).
+    fn apply_pair<P: Param<Scalar = T>>(&mut self, pos: &mut Var<P, S>, vel: &mut Var<P::Deriv, S>)
+    where
+        P::Deriv: Param<Scalar = T>,
+    {
+        self.apply(pos);
+        self.apply(vel);
+    }
 }
 
 /// A physical system whose temporal evolution we want to simulate.
@@ -81,9 +234,10 @@ pub trait Visitor<S: Solver + ?Sized> {
 /// Systems contain variables (degrees of freedom) and define the physics
 /// that governs their evolution through the `compute_derivs` method.
 ///
-/// # Type Parameter
+/// # Type Parameters
 /// * `S` - The solver type used to integrate this system's equations.
-pub trait System<S: Solver + ?Sized> {
+/// * `T` - The scalar type of time and variables, see [`Solver`].
+pub trait System<S: Solver<T> + ?Sized, T: Scalar = f32> {
     /// Compute derivatives for all variables in the system.
     ///
     /// This method defines the physics of the system by setting the
@@ -94,29 +248,153 @@ pub trait System<S: Solver + ?Sized> {
     ///
     /// This method should call `visitor.apply()` for each variable
     /// in the system, allowing the solver to update them.
-    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V);
+    fn visit_vars<V: Visitor<S, T>>(&mut self, visitor: &mut V);
 }
 
-pub trait Context<S: Solver + ?Sized> {
-    /// Time step for upcoming integration.
+/// A physical system whose derivatives may fail to evaluate.
+///
+/// The same as [`System`], but [`try_compute_derivs`](Self::try_compute_derivs)
+/// returns an error when the state is outside of the domain of the model, e.g. a
+/// negative volume of a gas. Such systems are stepped with [`Solver::try_solve_step`],
+/// which stops at the first error and restores the state before the step.
+///
+/// # Example
+/// ```
+/// use phy::{Rk4, Solver, TrySystem, Var, Visitor};
+///
+/// /// Gas compressed by a piston of unit mass.
+/// struct Piston<S: Solver> {
+///     volume: Var<f32, S>,
+///     speed: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> TrySystem<S> for Piston<S> {
+///     type Error = &'static str;
+///
+///     fn try_compute_derivs(&mut self, _: &S::Context) -> Result<(), Self::Error> {
+///         if *self.volume <= 0.0 {
+///             return Err("Volume is not positive");
+///         }
+///         self.volume.deriv = *self.speed;
+///         self.speed.deriv = 1.0 / *self.volume - 2.0;
+///         Ok(())
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply_pair(&mut self.volume, &mut self.speed);
+///     }
+/// }
+///
+/// let mut piston = Piston::<Rk4> {
+///     volume: Var::new(1.0),
+///     speed: Var::new(-2.0),
+/// };
+/// let mut t = 0.0;
+/// assert!(Rk4.try_solve_step(&mut piston, &mut t, 1.0).is_err());
+/// assert_eq!((t, *piston.volume, *piston.speed), (0.0, 1.0, -2.0));
+/// assert!(Rk4.try_solve_step(&mut piston, &mut t, 0.1).is_ok());
+/// ```
+pub trait TrySystem<S: Solver<T> + ?Sized, T: Scalar = f32> {
+    /// Error of evaluation of derivatives.
+    type Error;
+
+    /// Compute derivatives for all variables in the system, see [`System::compute_derivs`].
     ///
-    /// # Note on Time Dependence
+    /// Derivatives set before an error is returned are discarded.
+    fn try_compute_derivs(&mut self, ctx: &S::Context) -> Result<(), Self::Error>;
+
+    /// Visit all variables in the system with the provided visitor, see [`System::visit_vars`].
+    fn visit_vars<V: Visitor<S, T>>(&mut self, visitor: &mut V);
+}
+
+/// A collection of variables that can be visited by solvers.
+///
+/// Implemented by [`Var`] itself and by structures holding variables, so that
+/// they can be nested into each other and into systems. With the `derive`
+/// feature this trait can be derived with `#[derive(Vars)]`.
+pub trait Vars<S: Solver<T> + ?Sized, T: Scalar = f32> {
+    /// Visit all variables in the collection with the provided visitor.
+    fn visit_vars<V: Visitor<S, T>>(&mut self, visitor: &mut V);
+}
+
+/// Physics of a system, separated from its variables.
+///
+/// Systems derived with `#[derive(System)]` implement [`System::compute_derivs`]
+/// by calling this trait, while [`System::visit_vars`] is generated.
+pub trait Dynamics<S: Solver<T> + ?Sized, T: Scalar = f32> {
+    /// Compute derivatives for all variables in the system, see [`System::compute_derivs`].
+    fn compute_derivs(&mut self, ctx: &S::Context);
+}
+
+/// Information about the point at which derivatives are being computed.
+///
+/// Passed by solvers to [`System::compute_derivs`].
+pub trait Context<S: Solver<T> + ?Sized, T: Scalar = f32> {
+    /// Time at which derivatives are evaluated.
     ///
-    /// This time step may differ from time step passed to [`System::solve_step`].
-    /// You should not use it for counting time or so on.
+    /// Multi-stage solvers evaluate derivatives at intermediate points of a step,
+    /// so this is the time of the current stage, e.g. `t + h/2` for the second
+    /// and third stages of RK4. Non-autonomous systems should use it instead of
+    /// counting time themselves.
+    fn time(&self) -> T;
+
+    /// Size of the whole step being taken.
     ///
     /// This value should be used primarily for numerical stability in
     /// algorithms that may need it (e.g., for handling constraints or
     /// stiff equations).
-    fn time_step(&self) -> f32;
+    fn time_step(&self) -> T;
 }
 
 /// A numerical integration algorithm for solving differential equations.
 ///
 /// Solvers implement specific integration methods (e.g., Euler, RK4)
 /// and define any additional storage required per variable.
-pub trait Solver {
-    type Context: Context<Self>;
+///
+/// # Precision
+///
+/// Solvers are generic over the [`Scalar`] type `T` of time and variables,
+/// which is `f32` by default. To integrate a system in double precision,
+/// implement `System<S, f64>` for it with `S: Solver<f64>`, and use `f64`-based
+/// parameters such as `f64`, `DVec3` or [`DRot3`].
+///
+/// ```
+/// use phy::{DRot3, Rk4, Solver, System, Var, Visitor};
+/// use glam::DVec3;
+///
+/// struct Satellite<S: Solver<f64>> {
+///     pos: Var<DVec3, S>,
+///     vel: Var<DVec3, S>,
+///     rot: Var<DRot3, S>,
+/// }
+///
+/// impl<S: Solver<f64>> System<S, f64> for Satellite<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+///         self.rot.deriv = DVec3::Z;
+///     }
+///
+///     fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.pos);
+///         visitor.apply(&mut self.vel);
+///         visitor.apply(&mut self.rot);
+///     }
+/// }
+///
+/// let mut satellite = Satellite::<Rk4> {
+///     pos: Var::new(DVec3::X),
+///     vel: Var::new(DVec3::Y),
+///     rot: Var::default(),
+/// };
+/// let mut t = 0.0f64;
+/// for _ in 0..1000 {
+///     Rk4.solve_step(&mut satellite, &mut t, 1e-3);
+/// }
+/// assert!((satellite.pos.length() - 1.0).abs() < 1e-12);
+/// ```
+pub trait Solver<T: Scalar = f32> {
+    type Context: Context<Self, T>;
 
     /// Solver-specific storage type for variables of type `P`.
     ///
@@ -127,12 +405,109 @@ pub trait Solver {
     /// # Examples
     /// - Euler method: `()` (no storage needed)
     /// - RK4 method: `Rk4Storage<P>` (stores initial value and accumulated derivatives)
-    type Storage<P: Param>: Sized + Clone + Default;
+    type Storage<P: Param<Scalar = T>>: Sized + Clone + Default;
 
     /// Perform one integration step for the given system.
     ///
     /// # Arguments
     /// * `system` - The system to integrate.
+    /// * `t` - Time at the beginning of the step, advanced by `dt`.
+    /// * `dt` - Time step for the integration.
+    fn solve_step<S: System<Self, T>>(&self, system: &mut S, t: &mut T, dt: T);
+
+    /// Restore values of all variables to the beginning of an interrupted step.
+    ///
+    /// Called by [`try_solve_step`](Self::try_solve_step) when an evaluation of
+    /// derivatives fails after the solver has visited variables in the step. So the
+    /// first visit of each step must save values at the beginning of the step in
+    /// storage. Derivatives are reset.
+    fn restore_step<S: System<Self, T>>(&self, system: &mut S);
+
+    /// Perform one integration step for a system whose derivatives may fail to evaluate.
+    ///
+    /// The step stops at the first error, which is returned. Then `t` is left unchanged,
+    /// values of variables are restored to the beginning of the step, and derivatives
+    /// are reset.
+    ///
+    /// # Arguments
+    /// * `system` - The system to integrate.
+    /// * `t` - Time at the beginning of the step, advanced by `dt` on success.
     /// * `dt` - Time step for the integration.
-    fn solve_step<S: System<Self>>(&self, system: &mut S, dt: f32);
+    fn try_solve_step<S: TrySystem<Self, T>>(
+        &self,
+        system: &mut S,
+        t: &mut T,
+        dt: T,
+    ) -> Result<(), S::Error> {
+        let t0 = *t;
+        let mut fallible = Fallible::new(system);
+        self.solve_step(&mut fallible, t, dt);
+        fallible.finish(self).inspect_err(|_| *t = t0)
+    }
+}
+
+/// Result of a single adaptive integration step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveStep<T: Scalar = f32> {
+    /// Time step that was actually taken.
+    ///
+    /// May be smaller than the requested one if the step was rejected
+    /// and retried because of a too large error.
+    pub taken: T,
+    /// Suggested size of the next step.
+    pub next: T,
+    /// Whether the error tolerance was not met at the smallest allowed step size.
+    ///
+    /// If the error was finite, the step was taken anyway. Otherwise no step
+    /// was taken: `taken` is zero and the system is left unchanged.
+    pub forced: bool,
+}
+
+/// A solver that estimates its local error and adapts the step size.
+pub trait AdaptiveSolver<T: Scalar = f32>: Solver<T> {
+    /// Perform one integration step, starting with the time step `dt`.
+    ///
+    /// If the estimated error exceeds the solver's tolerance, the step is
+    /// rejected, the system is restored to its initial state and the step
+    /// is retried with a smaller size. The step that was finally accepted
+    /// is returned along with a suggestion for the next step size.
+    /// Steps whose error is not finite are never accepted, see [`AdaptiveStep::forced`].
+    ///
+    /// # Arguments
+    /// * `system` - The system to integrate.
+    /// * `t` - Time at the beginning of the step, advanced by the step taken.
+    /// * `dt` - Initial guess for the time step.
+    fn adaptive_step<S: System<Self, T>>(
+        &self,
+        system: &mut S,
+        t: &mut T,
+        dt: T,
+    ) -> AdaptiveStep<T>;
+}
+
+/// A solver that can evaluate the state at any time within the last step.
+///
+/// Such solvers retain the stage derivatives of the last step and use them
+/// to build a continuous interpolant (dense output) of the same order as the
+/// step itself or lower. This allows taking steps of arbitrary size and still
+/// sampling the state at given times, e.g. at render frames.
+pub trait DenseSolver<T: Scalar = f32>: Solver<T> {
+    /// Order of accuracy of the interpolant.
+    const DENSE_ORDER: u32;
+
+    /// Move the system to the interpolated state at time `t_n + theta * dt`.
+    ///
+    /// The system must have just been stepped by this solver from `t_n` with
+    /// the time step `dt`. Values are interpolated using [`Param::step`] from the
+    /// values at the beginning of the step, so rotations stay on their manifold.
+    /// Derivatives are reset.
+    ///
+    /// The stored step is not changed, so the system can be interpolated again at
+    /// another point. To keep the state at the end of the step, interpolate a clone.
+    ///
+    /// # Arguments
+    /// * `system` - The system that has just been stepped.
+    /// * `dt` - Size of the last step.
+    /// * `theta` - Position within the step in the range `[0, 1]`.
+    fn interpolate<S: System<Self, T>>(&self, system: &mut S, dt: T, theta: T);
 }
@@ -0,0 +1,144 @@
+use crate::{Deriv, Param, Scalar, Solver, Var, Visitor};
+
+/// Absolute and relative tolerance on the local error of a variable.
+///
+/// The error of a variable with value `y` is acceptable if its norm
+/// does not exceed `abs + rel * |y|`, where `|y|` is [`Param::magnitude`].
+/// Tolerances of variables have the same [`Scalar`] type as the variables, while
+/// default tolerances of solvers are stored in `f32` like other step control settings.
+///
+/// # Example
+/// ```
+/// use phy::{Euler, Tolerance, Var};
+///
+/// // Variable which must be integrated more precisely than the others
+/// let x = Var::<f64, Euler>::new(1.0).with_tolerance(Tolerance::new(1e-12, 1e-10));
+/// assert_eq!(x.tolerance(), Some(Tolerance::new(1e-12, 1e-10)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tolerance<T: Scalar = f32> {
+    /// Absolute tolerance, which must be positive so that values at zero have
+    /// a finite scaled error.
+    pub abs: T,
+    /// Tolerance relative to the magnitude of the value.
+    pub rel: T,
+}
+
+impl<T: Scalar> Default for Tolerance<T> {
+    fn default() -> Self {
+        Self::new(T::from_f32(1e-4), T::from_f32(1e-4))
+    }
+}
+
+impl<T: Scalar> Tolerance<T> {
+    /// Create tolerance from absolute and relative parts.
+    ///
+    /// # Panics
+    /// If `abs` is not positive. A purely relative tolerance would make the scaled
+    /// error of a variable at zero infinite or NaN.
+    pub fn new(abs: T, rel: T) -> Self {
+        assert!(abs > T::ZERO, "Absolute tolerance must be positive");
+        Self { abs, rel }
+    }
+
+    /// Maximum acceptable error norm for a value of the given magnitude.
+    pub fn scale(&self, magnitude: T) -> T {
+        self.abs + self.rel * magnitude
+    }
+
+    /// Convert tolerance to another scalar type.
+    pub fn cast<U: Scalar>(self) -> Tolerance<U> {
+        Tolerance {
+            abs: U::from_f64(self.abs.to_f64()),
+            rel: U::from_f64(self.rel.to_f64()),
+        }
+    }
+}
+
+/// The way errors of individual variables are combined into a single value.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Norm {
+    /// Root mean square of scaled errors over all scalar components.
+    #[default]
+    Rms,
+    /// Maximum of scaled errors over all variables.
+    Max,
+}
+
+/// Visitor that folds local error estimates of all variables into a single number.
+///
+/// The error estimate of each variable is taken from its `deriv` field,
+/// which is reset after being read. Solvers with an embedded error estimate
+/// store it there before visiting the system with this visitor.
+///
+/// Each error is divided by the variable's [`Tolerance`] (or by the default
+/// one, if the variable has none), so the resulting [`value`](Self::value)
+/// is not greater than `1.0` when all errors are within tolerance.
+#[derive(Clone, Debug)]
+pub struct ErrorNorm<T: Scalar = f32> {
+    norm: Norm,
+    tolerance: Tolerance<T>,
+    /// Sum of squared scaled errors.
+    sum_sq: T,
+    /// Total number of scalar components.
+    dim: usize,
+    /// Largest scaled error.
+    max: T,
+}
+
+impl<T: Scalar> ErrorNorm<T> {
+    /// Create an empty error norm with the default tolerance for variables.
+    pub fn new(norm: Norm, tolerance: Tolerance<T>) -> Self {
+        Self {
+            norm,
+            tolerance,
+            sum_sq: T::ZERO,
+            dim: 0,
+            max: T::ZERO,
+        }
+    }
+
+    /// Add the error of a single variable.
+    ///
+    /// # Arguments
+    /// * `value` - Value of the variable, used to scale relative tolerance.
+    /// * `error` - Local error estimate.
+    /// * `tolerance` - Tolerance of the variable, the default one is used if `None`.
+    pub fn add<P: Param<Scalar = T>>(
+        &mut self,
+        value: &P,
+        error: &P::Deriv,
+        tolerance: Option<Tolerance<T>>,
+    ) {
+        let scale = tolerance.unwrap_or(self.tolerance).scale(value.magnitude());
+        let scaled = error.norm() / scale;
+        self.sum_sq += scaled * scaled;
+        self.dim += error.dim();
+        // Once NaN is encountered it must stay there
+        if scaled.is_nan() || scaled > self.max {
+            self.max = scaled;
+        }
+    }
+
+    /// Combined scaled error of all variables added so far.
+    pub fn value(&self) -> T {
+        match self.norm {
+            Norm::Rms => {
+                if self.dim == 0 {
+                    T::ZERO
+                } else {
+                    (self.sum_sq / T::from_f64(self.dim as f64)).sqrt()
+                }
+            }
+            Norm::Max => self.max,
+        }
+    }
+}
+
+impl<T: Scalar, S: Solver<T>> Visitor<S, T> for ErrorNorm<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, S>) {
+        self.add(&var.value, &var.deriv, var.tolerance());
+        var.deriv = var.value.zero_deriv();
+    }
+}
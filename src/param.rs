@@ -1,6 +1,7 @@
-use core::ops::{AddAssign, MulAssign};
-
-use glam::{Vec2, Vec3};
+use crate::Scalar;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use glam::{DVec2, DVec3, Vec2, Vec3};
 
 /// A system parameter representing a degree of freedom.
 ///
@@ -12,16 +13,25 @@ use glam::{Vec2, Vec3};
 /// - `Clone` and `Default` for value semantics and initialization.
 /// - Derivative type must implement the [`Deriv`] trait for accumulation operations.
 /// - `step()` method to integrate the derivative over a time step.
+/// - `flat_dim()`, `write_flat()` and `read_flat()` to pack the value into scalars.
 ///
 /// # Provided Implementations
 /// - `f32`, `Vec2`, `Vec3` for scalar and vector quantities.
-/// - [`Rot2`], [`Rot3`] for rotations (see [`rot`] module).
+/// - `f64`, `DVec2`, `DVec3` for scalar and vector quantities in double precision.
+/// - [`Rot2`](crate::Rot2), [`Rot3`](crate::Rot3), [`DRot2`](crate::DRot2),
+///   [`DRot3`](crate::DRot3) for rotations.
+/// - Arrays `[P; N]` (up to `N = 32`) and tuples of up to 6 parameters with the same
+///   scalar type, which are stepped element-wise.
+/// - `Vec<P>` for dynamically sized state (requires `alloc` feature).
 pub trait Param: Clone + Default {
+    /// Floating-point type of time and of the parameter components.
+    type Scalar: Scalar;
+
     /// The type of derivative for this parameter.
     ///
     /// Must implement the [`Deriv`] trait to support accumulation and scaling
     /// operations required by numerical integration algorithms.
-    type Deriv: Deriv;
+    type Deriv: Deriv<Scalar = Self::Scalar>;
 
     /// Advance the parameter by integrating its derivative over time.
     ///
@@ -31,34 +41,446 @@ pub trait Param: Clone + Default {
     /// # Arguments
     /// * `deriv` - The derivative (rate of change) of the parameter.
     /// * `dt` - Time step over which to integrate.
-    fn step(&mut self, deriv: &Self::Deriv, dt: f32);
+    fn step(&mut self, deriv: &Self::Deriv, dt: Self::Scalar);
+
+    /// Characteristic size of the parameter value.
+    ///
+    /// Used to scale relative error tolerances (see [`Tolerance`](crate::Tolerance)).
+    /// For vector quantities this is the Euclidean length.
+    fn magnitude(&self) -> Self::Scalar;
+
+    /// Zero derivative of the parameter.
+    ///
+    /// Solvers reset derivatives to this value before computing them again.
+    /// Parameters of dynamic size override it to return a derivative of the same size.
+    fn zero_deriv(&self) -> Self::Deriv {
+        Self::Deriv::default()
+    }
+
+    /// Number of scalar components of the value when flattened.
+    ///
+    /// Used to pack the state of a system into a contiguous vector, see [`Flatten`](crate::Flatten).
+    /// Unlike [`Deriv::dim`], this is the number of components needed to store the value,
+    /// e.g. 4 for a rotation stored as a quaternion.
+    fn flat_dim(&self) -> usize;
+
+    /// Write components of the value to `out` of length [`flat_dim`](Self::flat_dim).
+    fn write_flat(&self, out: &mut [Self::Scalar]);
+
+    /// Read the value from components written by [`write_flat`](Self::write_flat).
+    ///
+    /// The length of `data` is [`flat_dim`](Self::flat_dim) of the current value.
+    fn read_flat(&mut self, data: &[Self::Scalar]);
 }
 
-pub trait Deriv: Clone + Default + MulAssign<f32> + for<'a> AddAssign<&'a Self> {}
+/// Derivative of a system parameter.
+///
+/// Derivatives form a vector space: they can be scaled, summed and subtracted,
+/// which is what numerical integration algorithms need to combine stage
+/// derivatives and to estimate errors.
+pub trait Deriv: Clone + Default {
+    /// Floating-point type of the components.
+    type Scalar: Scalar;
+
+    /// Multiply the derivative by a scalar factor.
+    fn scale(&mut self, factor: Self::Scalar);
+
+    /// Add another derivative multiplied by a scalar factor: `self += other * factor`.
+    fn add_scaled(&mut self, other: &Self, factor: Self::Scalar);
+
+    /// Subtract another derivative: `self -= other`.
+    ///
+    /// Equivalent to `add_scaled(other, -1)`, which is the default implementation,
+    /// so the difference of derivatives of dynamic size has the length of the longer one.
+    fn sub_assign(&mut self, other: &Self) {
+        self.add_scaled(other, -Self::Scalar::ONE);
+    }
+
+    /// Euclidean norm of the derivative.
+    ///
+    /// Used by adaptive solvers to measure the local error.
+    fn norm(&self) -> Self::Scalar;
+
+    /// Number of scalar components.
+    ///
+    /// Used to average errors over all components of a system.
+    fn dim(&self) -> usize;
+
+    /// Scalar component with the given index in the range `0..dim()`.
+    fn component(&self, index: usize) -> Self::Scalar;
+
+    /// Mutable reference to the scalar component with the given index.
+    ///
+    /// Used by implicit solvers to build Jacobians component by component.
+    fn component_mut(&mut self, index: usize) -> &mut Self::Scalar;
+}
 
 // Implement Param and Deriv for basic numeric types
 
-impl Param for f32 {
-    type Deriv = f32;
-    fn step(&mut self, deriv: &f32, dt: f32) {
-        *self += *deriv * dt
+macro_rules! impl_scalar {
+    ($scalar:ty) => {
+        impl Param for $scalar {
+            type Scalar = $scalar;
+            type Deriv = $scalar;
+            fn step(&mut self, deriv: &$scalar, dt: $scalar) {
+                *self += *deriv * dt
+            }
+            fn magnitude(&self) -> $scalar {
+                self.abs()
+            }
+            fn flat_dim(&self) -> usize {
+                1
+            }
+            fn write_flat(&self, out: &mut [$scalar]) {
+                out[0] = *self;
+            }
+            fn read_flat(&mut self, data: &[$scalar]) {
+                *self = data[0];
+            }
+        }
+
+        impl Deriv for $scalar {
+            type Scalar = $scalar;
+            fn scale(&mut self, factor: $scalar) {
+                *self *= factor
+            }
+            fn add_scaled(&mut self, other: &$scalar, factor: $scalar) {
+                *self += *other * factor
+            }
+            fn norm(&self) -> $scalar {
+                self.abs()
+            }
+            fn dim(&self) -> usize {
+                1
+            }
+            fn component(&self, index: usize) -> $scalar {
+                assert_eq!(index, 0, "Component index out of bounds");
+                *self
+            }
+            fn component_mut(&mut self, index: usize) -> &mut $scalar {
+                assert_eq!(index, 0, "Component index out of bounds");
+                self
+            }
+        }
+    };
+}
+
+macro_rules! impl_vector {
+    ($vector:ty, $scalar:ty, $dim:expr) => {
+        impl Param for $vector {
+            type Scalar = $scalar;
+            type Deriv = $vector;
+            fn step(&mut self, deriv: &$vector, dt: $scalar) {
+                *self += *deriv * dt
+            }
+            fn magnitude(&self) -> $scalar {
+                self.length()
+            }
+            fn flat_dim(&self) -> usize {
+                $dim
+            }
+            fn write_flat(&self, out: &mut [$scalar]) {
+                self.write_to_slice(out);
+            }
+            fn read_flat(&mut self, data: &[$scalar]) {
+                *self = <$vector>::from_slice(data);
+            }
+        }
+
+        impl Deriv for $vector {
+            type Scalar = $scalar;
+            fn scale(&mut self, factor: $scalar) {
+                *self *= factor
+            }
+            fn add_scaled(&mut self, other: &$vector, factor: $scalar) {
+                *self += *other * factor
+            }
+            fn norm(&self) -> $scalar {
+                self.length()
+            }
+            fn dim(&self) -> usize {
+                $dim
+            }
+            fn component(&self, index: usize) -> $scalar {
+                self[index]
+            }
+            fn component_mut(&mut self, index: usize) -> &mut $scalar {
+                &mut self[index]
+            }
+        }
+    };
+}
+
+impl_scalar!(f32);
+impl_scalar!(f64);
+
+impl_vector!(Vec2, f32, 2);
+impl_vector!(Vec3, f32, 3);
+impl_vector!(DVec2, f64, 2);
+impl_vector!(DVec3, f64, 3);
+
+// Implement Param and Deriv for arrays and tuples, stepped element-wise
+
+/// Euclidean norm of a sequence of norms.
+fn norm_of<T: Scalar>(norms: impl IntoIterator<Item = T>) -> T {
+    norms.into_iter().map(|x| x * x).sum::<T>().sqrt()
+}
+
+/// Write flattened values of a sequence of parameters one after another.
+fn write_flat_seq<'a, P: Param + 'a>(
+    values: impl IntoIterator<Item = &'a P>,
+    out: &mut [P::Scalar],
+) {
+    let mut offset = 0;
+    for value in values {
+        let n = value.flat_dim();
+        value.write_flat(&mut out[offset..offset + n]);
+        offset += n;
+    }
+}
+
+/// Read values of a sequence of parameters written by [`write_flat_seq`].
+fn read_flat_seq<'a, P: Param + 'a>(
+    values: impl IntoIterator<Item = &'a mut P>,
+    data: &[P::Scalar],
+) {
+    let mut offset = 0;
+    for value in values {
+        let n = value.flat_dim();
+        value.read_flat(&data[offset..offset + n]);
+        offset += n;
+    }
+}
+
+/// Component of a sequence of derivatives with components numbered consecutively.
+fn slice_component<D: Deriv>(derivs: &[D], mut index: usize) -> D::Scalar {
+    for x in derivs {
+        if index < x.dim() {
+            return x.component(index);
+        }
+        index -= x.dim();
+    }
+    panic!("Component index out of bounds")
+}
+
+/// Mutable reference to a component of a sequence of derivatives.
+fn slice_component_mut<D: Deriv>(derivs: &mut [D], mut index: usize) -> &mut D::Scalar {
+    for x in derivs {
+        if index < x.dim() {
+            return x.component_mut(index);
+        }
+        index -= x.dim();
     }
+    panic!("Component index out of bounds")
 }
 
-impl Param for Vec2 {
-    type Deriv = Vec2;
-    fn step(&mut self, deriv: &Vec2, dt: f32) {
-        *self += *deriv * dt
+impl<P: Param, const N: usize> Param for [P; N]
+where
+    [P; N]: Default,
+    [P::Deriv; N]: Default,
+{
+    type Scalar = P::Scalar;
+    type Deriv = [P::Deriv; N];
+    fn step(&mut self, deriv: &Self::Deriv, dt: P::Scalar) {
+        for (value, deriv) in self.iter_mut().zip(deriv) {
+            value.step(deriv, dt);
+        }
+    }
+    fn magnitude(&self) -> P::Scalar {
+        norm_of(self.iter().map(P::magnitude))
+    }
+    fn zero_deriv(&self) -> Self::Deriv {
+        core::array::from_fn(|i| self[i].zero_deriv())
+    }
+    fn flat_dim(&self) -> usize {
+        self.iter().map(P::flat_dim).sum()
+    }
+    fn write_flat(&self, out: &mut [P::Scalar]) {
+        write_flat_seq(self, out);
+    }
+    fn read_flat(&mut self, data: &[P::Scalar]) {
+        read_flat_seq(self, data);
     }
 }
 
-impl Param for Vec3 {
-    type Deriv = Vec3;
-    fn step(&mut self, deriv: &Vec3, dt: f32) {
-        *self += *deriv * dt
+impl<D: Deriv, const N: usize> Deriv for [D; N]
+where
+    [D; N]: Default,
+{
+    type Scalar = D::Scalar;
+    fn scale(&mut self, factor: D::Scalar) {
+        for x in self {
+            x.scale(factor);
+        }
+    }
+    fn add_scaled(&mut self, other: &Self, factor: D::Scalar) {
+        for (x, y) in self.iter_mut().zip(other) {
+            x.add_scaled(y, factor);
+        }
+    }
+    fn norm(&self) -> D::Scalar {
+        norm_of(self.iter().map(D::norm))
+    }
+    fn dim(&self) -> usize {
+        self.iter().map(D::dim).sum()
     }
+    fn component(&self, index: usize) -> D::Scalar {
+        slice_component(self, index)
+    }
+    fn component_mut(&mut self, index: usize) -> &mut D::Scalar {
+        slice_component_mut(self, index)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($first:ident $(, $name:ident)* ; $($index:tt),+) => {
+        impl<$first: Param $(, $name: Param<Scalar = $first::Scalar>)*> Param for ($first, $($name,)*) {
+            type Scalar = $first::Scalar;
+            type Deriv = ($first::Deriv, $($name::Deriv,)*);
+            fn step(&mut self, deriv: &Self::Deriv, dt: Self::Scalar) {
+                $(self.$index.step(&deriv.$index, dt);)+
+            }
+            fn magnitude(&self) -> Self::Scalar {
+                norm_of([$(self.$index.magnitude()),+])
+            }
+            fn zero_deriv(&self) -> Self::Deriv {
+                ($(self.$index.zero_deriv(),)+)
+            }
+            fn flat_dim(&self) -> usize {
+                0 $(+ self.$index.flat_dim())+
+            }
+            fn write_flat(&self, out: &mut [Self::Scalar]) {
+                let mut offset = 0;
+                $(
+                    let n = self.$index.flat_dim();
+                    self.$index.write_flat(&mut out[offset..offset + n]);
+                    offset += n;
+                )+
+                let _ = offset;
+            }
+            fn read_flat(&mut self, data: &[Self::Scalar]) {
+                let mut offset = 0;
+                $(
+                    let n = self.$index.flat_dim();
+                    self.$index.read_flat(&data[offset..offset + n]);
+                    offset += n;
+                )+
+                let _ = offset;
+            }
+        }
+
+        impl<$first: Deriv $(, $name: Deriv<Scalar = $first::Scalar>)*> Deriv for ($first, $($name,)*) {
+            type Scalar = $first::Scalar;
+            fn scale(&mut self, factor: Self::Scalar) {
+                $(self.$index.scale(factor);)+
+            }
+            fn add_scaled(&mut self, other: &Self, factor: Self::Scalar) {
+                $(self.$index.add_scaled(&other.$index, factor);)+
+            }
+            fn norm(&self) -> Self::Scalar {
+                norm_of([$(self.$index.norm()),+])
+            }
+            fn dim(&self) -> usize {
+                0 $(+ self.$index.dim())+
+            }
+            fn component(&self, mut index: usize) -> Self::Scalar {
+                $(
+                    if index < self.$index.dim() {
+                        return self.$index.component(index);
+                    }
+                    index -= self.$index.dim();
+                )+
+                let _ = index;
+                panic!("Component index out of bounds")
+            }
+            fn component_mut(&mut self, mut index: usize) -> &mut Self::Scalar {
+                $(
+                    if index < self.$index.dim() {
+                        return self.$index.component_mut(index);
+                    }
+                    index -= self.$index.dim();
+                )+
+                let _ = index;
+                panic!("Component index out of bounds")
+            }
+        }
+    };
 }
 
-impl Deriv for f32 {}
-impl Deriv for Vec2 {}
-impl Deriv for Vec3 {}
+impl_tuple!(A; 0);
+impl_tuple!(A, B; 0, 1);
+impl_tuple!(A, B, C; 0, 1, 2);
+impl_tuple!(A, B, C, D; 0, 1, 2, 3);
+impl_tuple!(A, B, C, D, E; 0, 1, 2, 3, 4);
+impl_tuple!(A, B, C, D, E, F; 0, 1, 2, 3, 4, 5);
+
+/// Dynamically sized parameter, e.g. for particle systems which gain and lose particles.
+///
+/// Elements are stepped element-wise. A shorter derivative is treated as padded with
+/// zeros, so the derivative set by [`Default`] is zero for a vector of any length.
+///
+/// The length may change between steps. Solvers reset derivatives to the length of the
+/// value at the end of each step, so after adding elements the whole derivative should be
+/// assigned in [`System::compute_derivs`](crate::System::compute_derivs) rather than
+/// written by index.
+#[cfg(feature = "alloc")]
+impl<P: Param> Param for Vec<P> {
+    type Scalar = P::Scalar;
+    type Deriv = Vec<P::Deriv>;
+    fn step(&mut self, deriv: &Self::Deriv, dt: P::Scalar) {
+        for (value, deriv) in self.iter_mut().zip(deriv) {
+            value.step(deriv, dt);
+        }
+    }
+    fn magnitude(&self) -> P::Scalar {
+        norm_of(self.iter().map(P::magnitude))
+    }
+    fn zero_deriv(&self) -> Self::Deriv {
+        self.iter().map(P::zero_deriv).collect()
+    }
+    fn flat_dim(&self) -> usize {
+        self.iter().map(P::flat_dim).sum()
+    }
+    fn write_flat(&self, out: &mut [P::Scalar]) {
+        write_flat_seq(self, out);
+    }
+    /// Elements are read into the existing ones, the length is not changed.
+    fn read_flat(&mut self, data: &[P::Scalar]) {
+        read_flat_seq(self, data);
+    }
+}
+
+/// Derivative of a dynamically sized parameter.
+///
+/// Adding a longer derivative extends this one, treating missing elements as zeros.
+#[cfg(feature = "alloc")]
+impl<D: Deriv> Deriv for Vec<D> {
+    type Scalar = D::Scalar;
+    fn scale(&mut self, factor: D::Scalar) {
+        for x in self {
+            x.scale(factor);
+        }
+    }
+    fn add_scaled(&mut self, other: &Self, factor: D::Scalar) {
+        for (x, y) in self.iter_mut().zip(other) {
+            x.add_scaled(y, factor);
+        }
+        for y in other.iter().skip(self.len()) {
+            let mut x = y.clone();
+            x.scale(factor);
+            self.push(x);
+        }
+    }
+    fn norm(&self) -> D::Scalar {
+        norm_of(self.iter().map(D::norm))
+    }
+    fn dim(&self) -> usize {
+        self.iter().map(D::dim).sum()
+    }
+    fn component(&self, index: usize) -> D::Scalar {
+        slice_component(self, index)
+    }
+    fn component_mut(&mut self, index: usize) -> &mut D::Scalar {
+        slice_component_mut(self, index)
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{DenseSolver, Param, Scalar, Solver, System, integrate};
+use core::any::TypeId;
+use std::{
+    boxed::Box,
+    io::{self, Write},
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+/// Whether values of the scalar type are printed in single precision.
+fn is_single<T: Scalar>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<f32>()
+}
+
+/// Recorded time series of named columns, sampled at increasing times.
+///
+/// Values are stored in double precision. Columns recorded from `f32` values are
+/// written with the shortest representation of the original `f32` value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    columns: Vec<String>,
+    single: Vec<bool>,
+    time_single: bool,
+    times: Vec<f64>,
+    values: Vec<f64>,
+}
+
+impl Trajectory {
+    /// Names of the columns, not including time.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Number of recorded samples.
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Whether no samples have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// Sample times.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// Values of all columns of the sample with the given index.
+    pub fn row(&self, index: usize) -> &[f64] {
+        let n = self.columns.len();
+        &self.values[index * n..(index + 1) * n]
+    }
+
+    /// Values of the column with the given name for all samples.
+    pub fn column(&self, name: &str) -> Option<impl Iterator<Item = f64> + '_> {
+        let index = self.columns.iter().position(|c| c == name)?;
+        let n = self.columns.len();
+        Some(self.values.iter().skip(index).step_by(n).copied())
+    }
+
+    /// Index of the last sample taken at or before time `t`.
+    pub fn index_at(&self, t: f64) -> Option<usize> {
+        self.times.partition_point(|&time| time <= t).checked_sub(1)
+    }
+
+    /// Values of all columns at time `t`, linearly interpolated between samples.
+    ///
+    /// Components are interpolated independently, so interpolated quaternions are
+    /// not normalized. Returns `None` if `t` is outside of the recorded time range.
+    pub fn at(&self, t: f64) -> Option<Vec<f64>> {
+        let index = self.index_at(t)?;
+        let before = self.row(index);
+        if self.times[index] == t {
+            return Some(before.to_vec());
+        }
+        let next = *self.times.get(index + 1)?;
+        let s = (t - self.times[index]) / (next - self.times[index]);
+        let after = self.row(index + 1);
+        Some(
+            before
+                .iter()
+                .zip(after)
+                .map(|(a, b)| a + (b - a) * s)
+                .collect(),
+        )
+    }
+
+    /// Write samples as comma-separated values with a header line.
+    ///
+    /// The first column is time `t`.
+    /// Names containing commas, quotes or line breaks are quoted.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "t")?;
+        for name in &self.columns {
+            write!(writer, ",")?;
+            write_csv_field(&mut writer, name)?;
+        }
+        writeln!(writer)?;
+        for index in 0..self.len() {
+            write_number(&mut writer, self.times[index], self.time_single, false)?;
+            for (&value, &single) in self.row(index).iter().zip(&self.single) {
+                write!(writer, ",")?;
+                write_number(&mut writer, value, single, false)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write samples as JSON Lines, one object per sample with time `t` and all columns.
+    ///
+    /// Non-finite values are written as `null`.
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for index in 0..self.len() {
+            write!(writer, "{{\"t\":")?;
+            write_number(&mut writer, self.times[index], self.time_single, true)?;
+            for ((name, &value), &single) in
+                self.columns.iter().zip(self.row(index)).zip(&self.single)
+            {
+                write!(writer, ",")?;
+                write_json_string(&mut writer, name)?;
+                write!(writer, ":")?;
+                write_number(&mut writer, value, single, true)?;
+            }
+            writeln!(writer, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_number<W: Write>(writer: &mut W, value: f64, single: bool, json: bool) -> io::Result<()> {
+    if json && !value.is_finite() {
+        write!(writer, "null")
+    } else if single {
+        write!(writer, "{}", value as f32)
+    } else {
+        write!(writer, "{}", value)
+    }
+}
+
+fn write_csv_field<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    if s.contains([',', '"', '\n', '\r']) {
+        write!(writer, "\"{}\"", s.replace('"', "\"\""))
+    } else {
+        write!(writer, "{}", s)
+    }
+}
+
+fn write_json_string<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            c if c.is_control() => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+/// Column accessor appending flattened components of a value read from a system.
+type Accessor<Y> = Box<dyn Fn(&Y, &mut Vec<f64>)>;
+
+/// Column of a [`Recorder`] before its components are known.
+struct Column<Y> {
+    name: String,
+    single: bool,
+    accessor: Accessor<Y>,
+}
+
+/// Recorder sampling named quantities of a system into a [`Trajectory`].
+///
+/// Each column is defined by a name and an accessor returning a [`Param`], which is
+/// flattened into its components with [`Param::write_flat`]. Values with a single
+/// component are recorded into a column with the given name, while values with several
+/// components, e.g. a `Vec3` recorded as `vel`, produce columns `vel.0`, `vel.1` and
+/// `vel.2`. 3D rotations are recorded as unit quaternions with components `x`, `y`, `z`,
+/// `w`, 2D rotations as angles. Since dynamically sized values like `Vec<P>` only know
+/// their size from the value, the column names are resolved at the first sample.
+///
+/// Samples are taken either manually with [`sample`](Self::sample) or at fixed output
+/// times during integration with [`record`](Self::record) or
+/// [`record_dense`](Self::record_dense).
+///
+/// # Example
+/// ```
+/// use phy::{Recorder, Rk4Dense, Solver, System, Var, Visitor};
+/// use glam::Vec2;
+///
+/// #[derive(Clone)]
+/// struct Projectile<S: Solver> {
+///     pos: Var<Vec2, S>,
+///     vel: Var<Vec2, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Projectile<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -9.8 * Vec2::Y;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply_pair(&mut self.pos, &mut self.vel);
+///     }
+/// }
+///
+/// let mut system = Projectile::<Rk4Dense> {
+///     pos: Var::new(Vec2::ZERO),
+///     vel: Var::new(Vec2::new(1.0, 4.9)),
+/// };
+/// let mut recorder = Recorder::new()
+///     .column("pos", |s: &Projectile<Rk4Dense>| *s.pos)
+///     .column("speed", |s: &Projectile<Rk4Dense>| s.vel.length());
+/// recorder.record_dense(&mut system, &Rk4Dense, 0.0, 1.0, 0.01, 0.5);
+///
+/// let trajectory = recorder.trajectory();
+/// assert_eq!(trajectory.columns(), ["pos.0", "pos.1", "speed"]);
+/// assert_eq!(trajectory.times(), [0.0, 0.5, 1.0]);
+/// // Top of the trajectory
+/// assert!((trajectory.at(0.5).unwrap()[1] - 1.225).abs() < 1e-6);
+///
+/// let mut csv = Vec::new();
+/// trajectory.write_csv(&mut csv).unwrap();
+/// let csv = String::from_utf8(csv).unwrap();
+/// assert!(csv.starts_with("t,pos.0,pos.1,speed\n0,0,0,5.001\n0.5,0.5,1.2250001,1\n"));
+/// ```
+pub struct Recorder<Y, T: Scalar = f32> {
+    columns: Vec<Column<Y>>,
+    trajectory: Trajectory,
+    marker: core::marker::PhantomData<T>,
+}
+
+impl<Y, T: Scalar> Default for Recorder<Y, T> {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            trajectory: Trajectory {
+                time_single: is_single::<T>(),
+                ..Trajectory::default()
+            },
+            marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<Y, T: Scalar> Recorder<Y, T> {
+    /// Create a recorder without columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column, or several columns for values with several components, read by the accessor.
+    ///
+    /// # Panics
+    /// If samples have already been recorded.
+    pub fn column<P: Param>(mut self, name: &str, accessor: impl Fn(&Y) -> P + 'static) -> Self {
+        assert!(
+            self.trajectory.is_empty(),
+            "Columns must be added before recording"
+        );
+        self.columns.push(Column {
+            name: String::from(name),
+            single: is_single::<P::Scalar>(),
+            accessor: Box::new(move |system, out| {
+                let value = accessor(system);
+                let mut flat = vec![P::Scalar::ZERO; value.flat_dim()];
+                value.write_flat(&mut flat);
+                out.extend(flat.into_iter().map(Scalar::to_f64));
+            }),
+        });
+        self
+    }
+
+    /// Record a sample of the system at time `t`.
+    ///
+    /// # Panics
+    /// If the number of components of a column differs from the first sample.
+    pub fn sample(&mut self, t: T, system: &Y) {
+        let first = self.trajectory.is_empty();
+        let values = &mut self.trajectory.values;
+        for column in &self.columns {
+            let start = values.len();
+            (column.accessor)(system, values);
+            if first {
+                let dim = values.len() - start;
+                for index in 0..dim {
+                    self.trajectory.columns.push(if dim == 1 {
+                        column.name.clone()
+                    } else {
+                        std::format!("{}.{}", column.name, index)
+                    });
+                    self.trajectory.single.push(column.single);
+                }
+            }
+        }
+        self.trajectory.times.push(t.to_f64());
+        assert_eq!(
+            values.len(),
+            self.trajectory.times.len() * self.trajectory.columns.len(),
+            "Number of components of a column has changed"
+        );
+    }
+
+    /// Integrate the system from `t0` to `t1` with step `dt`, sampling it every `output_dt`.
+    ///
+    /// Samples are taken at `t0 + k * output_dt` up to `t1` inclusive. Steps are shortened
+    /// to end at each output time, so that any solver can be used. Returns the number of
+    /// steps taken.
+    pub fn record<S>(
+        &mut self,
+        system: &mut Y,
+        solver: &S,
+        t0: T,
+        t1: T,
+        dt: T,
+        output_dt: T,
+    ) -> usize
+    where
+        S: Solver<T>,
+        Y: System<S, T>,
+    {
+        let outputs = output_count(t0, t1, output_dt);
+        self.sample(t0, system);
+        let mut start = t0;
+        let mut steps = 0;
+        for k in 1..=outputs {
+            let output = (t0 + T::from_f64(k as f64) * output_dt).min(t1);
+            steps += integrate(system, solver, start, output, dt, |_, _| {});
+            self.sample(output, system);
+            start = output;
+        }
+        steps + integrate(system, solver, start, t1, dt, |_, _| {})
+    }
+
+    /// Integrate the system from `t0` to `t1` with step `dt`, sampling it every `output_dt`
+    /// using dense output of the solver.
+    ///
+    /// Samples are taken at `t0 + k * output_dt` up to `t1` inclusive, regardless of the
+    /// step size. Unlike [`record`](Self::record), steps are not affected by output times,
+    /// and times between steps are sampled by interpolating a clone of the system.
+    /// Returns the number of steps taken.
+    pub fn record_dense<S>(
+        &mut self,
+        system: &mut Y,
+        solver: &S,
+        t0: T,
+        t1: T,
+        dt: T,
+        output_dt: T,
+    ) -> usize
+    where
+        S: DenseSolver<T>,
+        Y: System<S, T> + Clone,
+    {
+        let outputs = output_count(t0, t1, output_dt);
+        self.sample(t0, system);
+        let mut next = 1;
+        let mut start = t0;
+        integrate(system, solver, t0, t1, dt, |t, system| {
+            let h = t - start;
+            while next <= outputs {
+                let output = (t0 + T::from_f64(next as f64) * output_dt).min(t1);
+                if output > t {
+                    break;
+                }
+                if output == t {
+                    self.sample(output, system);
+                } else {
+                    let mut state = system.clone();
+                    solver.interpolate(&mut state, h, (output - start) / h);
+                    self.sample(output, &state);
+                }
+                next += 1;
+            }
+            start = t;
+        })
+    }
+
+    /// Recorded samples.
+    pub fn trajectory(&self) -> &Trajectory {
+        &self.trajectory
+    }
+
+    /// Stop recording and take the recorded samples.
+    pub fn into_trajectory(self) -> Trajectory {
+        self.trajectory
+    }
+}
+
+/// Number of output times after `t0` up to `t1`, allowing for rounding errors.
+fn output_count<T: Scalar>(t0: T, t1: T, output_dt: T) -> usize {
+    assert!(output_dt > T::ZERO, "Output interval must be positive");
+    if t1 >= t0 {
+        libm::floor(((t1 - t0) / output_dt).to_f64() + 1e-3) as usize
+    } else {
+        0
+    }
+}
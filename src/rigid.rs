@@ -0,0 +1,402 @@
+//! Rigid body dynamics.
+//!
+//! [`RigidBody2`] and [`RigidBody3`] are ready-made systems of a single rigid body
+//! driven by forces and torques. They can be integrated on their own or nested into
+//! larger systems, which apply forces before calling
+//! [`compute_derivs`](crate::System::compute_derivs) of each body.
+//!
+//! # State
+//! A body is described by its pose (position of the center of mass and orientation)
+//! and its momentum (linear and angular momentum in the world frame). Momenta are
+//! chosen over velocities because they are conserved in the absence of external
+//! forces, so a free body keeps them exactly with any solver.
+//!
+//! Position and orientation are separate variables rather than a single
+//! [`Iso3`](crate::Iso3) pose. A pose is stepped along a screw motion with constant
+//! body-frame twist, while the center of mass of a free body moves along a straight
+//! line regardless of its rotation, so the pose would add an error of order
+//! `(ω × v) dt²` per step to the position of a spinning body. Orientation in 3D is a
+//! [`BodyRot3`], driven by angular velocity in the body frame, where it is computed
+//! from the inertia tensor.
+//!
+//! # Precision
+//! [`RigidBody2`] and [`RigidBody3`] are integrated in `f32`, while [`DRigidBody2`]
+//! and [`DRigidBody3`] are the same bodies in `f64`.
+//!
+//! # Forces
+//! Forces and torques are accumulated between evaluations of derivatives with
+//! [`apply_force`](RigidBody3::apply_force), [`apply_force_at`](RigidBody3::apply_force_at)
+//! and [`apply_torque`](RigidBody3::apply_torque). Accumulators are not cleared
+//! automatically, so constant forces may be applied once. Forces depending on the
+//! state must be recomputed for every evaluation, after
+//! [`clear_forces`](RigidBody3::clear_forces).
+//!
+//! # Example
+//! ```
+//! use phy::{Rk4, Solver, rigid::RigidBody3};
+//! use glam::{Mat3, Vec3};
+//!
+//! // Unit cube thrown upwards with a spin
+//! let mut body = RigidBody3::<Rk4>::new(1.0, Mat3::from_diagonal(Vec3::splat(1.0 / 6.0)))
+//!     .with_velocity(Vec3::new(1.0, 0.0, 5.0))
+//!     .with_angular_velocity(Vec3::new(0.0, 2.0, 0.0));
+//! body.apply_force(Vec3::new(0.0, 0.0, -9.8));
+//!
+//! let mut t = 0.0;
+//! for _ in 0..100 {
+//!     Rk4.solve_step(&mut body, &mut t, 0.01);
+//! }
+//! assert!(body.velocity().abs_diff_eq(Vec3::new(1.0, 0.0, 5.0 - 9.8 * t), 1e-4));
+//! assert!(body.angular_velocity().abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-4));
+//! ```
+
+use crate::{BodyRot3, DBodyRot3, DRot2, DRot3, Rot2, Rot3, Solver, System, Var, Vars, Visitor};
+use glam::{DMat3, DVec2, DVec3, Mat3, Vec2, Vec3};
+
+macro_rules! impl_rigid2 {
+    ($(#[$meta:meta])* $body:ident, $scalar:ty, $vec:ident, $rot:ident) => {
+        $(#[$meta])*
+        pub struct $body<S: Solver<$scalar>> {
+            /// Mass of the body.
+            pub mass: $scalar,
+            /// Moment of inertia about the center of mass.
+            pub inertia: $scalar,
+            /// Position of the center of mass.
+            pub pos: Var<$vec, S>,
+            /// Orientation of the body.
+            pub rot: Var<$rot, S>,
+            /// Linear momentum.
+            pub momentum: Var<$vec, S>,
+            /// Angular momentum about the center of mass.
+            pub angular_momentum: Var<$scalar, S>,
+            /// Accumulated force.
+            pub force: $vec,
+            /// Accumulated torque about the center of mass.
+            pub torque: $scalar,
+        }
+
+        impl<S: Solver<$scalar>> $body<S> {
+            /// Create a body at rest at the origin.
+            pub fn new(mass: $scalar, inertia: $scalar) -> Self {
+                Self {
+                    mass,
+                    inertia,
+                    pos: Var::default(),
+                    rot: Var::default(),
+                    momentum: Var::default(),
+                    angular_momentum: Var::default(),
+                    force: $vec::ZERO,
+                    torque: 0.0,
+                }
+            }
+
+            /// Set position of the center of mass.
+            pub fn with_pos(mut self, pos: $vec) -> Self {
+                *self.pos = pos;
+                self
+            }
+
+            /// Set orientation.
+            ///
+            /// Angular velocity does not depend on orientation in 2D, so this may be
+            /// called before or after [`with_angular_velocity`](Self::with_angular_velocity).
+            pub fn with_rot(mut self, rot: $rot) -> Self {
+                *self.rot = rot;
+                self
+            }
+
+            /// Set velocity of the center of mass.
+            pub fn with_velocity(mut self, vel: $vec) -> Self {
+                *self.momentum = self.mass * vel;
+                self
+            }
+
+            /// Set angular speed.
+            pub fn with_angular_velocity(mut self, angular: $scalar) -> Self {
+                *self.angular_momentum = self.inertia * angular;
+                self
+            }
+
+            /// Velocity of the center of mass.
+            pub fn velocity(&self) -> $vec {
+                *self.momentum / self.mass
+            }
+
+            /// Angular speed in radians per unit time.
+            pub fn angular_velocity(&self) -> $scalar {
+                *self.angular_momentum / self.inertia
+            }
+
+            /// Velocity of a point fixed to the body, given in the world frame.
+            pub fn point_velocity(&self, point: $vec) -> $vec {
+                self.velocity() + self.angular_velocity() * (point - *self.pos).perp()
+            }
+
+            /// Kinetic energy of translation and rotation.
+            pub fn kinetic_energy(&self) -> $scalar {
+                0.5 * (self.momentum.length_squared() / self.mass
+                    + *self.angular_momentum * *self.angular_momentum / self.inertia)
+            }
+
+            /// Apply force at the center of mass.
+            pub fn apply_force(&mut self, force: $vec) {
+                self.force += force;
+            }
+
+            /// Apply force at a point given in the world frame, producing torque as well.
+            pub fn apply_force_at(&mut self, force: $vec, point: $vec) {
+                self.force += force;
+                self.torque += (point - *self.pos).perp_dot(force);
+            }
+
+            /// Apply torque.
+            pub fn apply_torque(&mut self, torque: $scalar) {
+                self.torque += torque;
+            }
+
+            /// Reset accumulated force and torque.
+            pub fn clear_forces(&mut self) {
+                self.force = $vec::ZERO;
+                self.torque = 0.0;
+            }
+        }
+
+        impl<S: Solver<$scalar>> Vars<S, $scalar> for $body<S> {
+            fn visit_vars<V: Visitor<S, $scalar>>(&mut self, visitor: &mut V) {
+                visitor.apply(&mut self.pos);
+                visitor.apply(&mut self.rot);
+                visitor.apply(&mut self.momentum);
+                visitor.apply(&mut self.angular_momentum);
+            }
+        }
+
+        impl<S: Solver<$scalar>> System<S, $scalar> for $body<S> {
+            /// Compute derivatives from the accumulated force and torque.
+            fn compute_derivs(&mut self, _: &S::Context) {
+                self.pos.deriv = self.velocity();
+                self.rot.deriv = self.angular_velocity();
+                self.momentum.deriv = self.force;
+                self.angular_momentum.deriv = self.torque;
+            }
+
+            fn visit_vars<V: Visitor<S, $scalar>>(&mut self, visitor: &mut V) {
+                Vars::visit_vars(self, visitor);
+            }
+        }
+    };
+}
+
+macro_rules! impl_rigid3 {
+    (
+        $(#[$meta:meta])* $body:ident, $scalar:ty, $vec:ident, $mat:ident, $rot:ident, $body_rot:ident
+    ) => {
+        $(#[$meta])*
+        pub struct $body<S: Solver<$scalar>> {
+            /// Mass of the body.
+            pub mass: $scalar,
+            /// Inertia tensor about the center of mass in the body frame.
+            inertia: $mat,
+            /// Inverse of the inertia tensor.
+            inv_inertia: $mat,
+            /// Position of the center of mass.
+            pub pos: Var<$vec, S>,
+            /// Orientation of the body, driven by angular velocity in the body frame.
+            pub rot: Var<$body_rot, S>,
+            /// Linear momentum.
+            pub momentum: Var<$vec, S>,
+            /// Angular momentum about the center of mass.
+            pub angular_momentum: Var<$vec, S>,
+            /// Accumulated force.
+            pub force: $vec,
+            /// Accumulated torque about the center of mass.
+            pub torque: $vec,
+        }
+
+        impl<S: Solver<$scalar>> $body<S> {
+            /// Create a body at rest at the origin.
+            ///
+            /// # Arguments
+            /// * `mass` - Mass of the body.
+            /// * `inertia` - Inertia tensor about the center of mass in the body frame,
+            ///   must be invertible.
+            pub fn new(mass: $scalar, inertia: $mat) -> Self {
+                Self {
+                    mass,
+                    inertia,
+                    inv_inertia: inertia.inverse(),
+                    pos: Var::default(),
+                    rot: Var::default(),
+                    momentum: Var::default(),
+                    angular_momentum: Var::default(),
+                    force: $vec::ZERO,
+                    torque: $vec::ZERO,
+                }
+            }
+
+            /// Set position of the center of mass.
+            pub fn with_pos(mut self, pos: $vec) -> Self {
+                *self.pos = pos;
+                self
+            }
+
+            /// Set orientation.
+            ///
+            /// Angular velocity in the world frame is kept and angular momentum is
+            /// recomputed, so the result does not depend on whether this is called
+            /// before or after [`with_angular_velocity`](Self::with_angular_velocity).
+            pub fn with_rot(mut self, rot: $rot) -> Self {
+                let angular = self.angular_velocity();
+                *self.rot = rot.into();
+                self.with_angular_velocity(angular)
+            }
+
+            /// Set velocity of the center of mass.
+            pub fn with_velocity(mut self, vel: $vec) -> Self {
+                *self.momentum = self.mass * vel;
+                self
+            }
+
+            /// Set angular velocity in the world frame.
+            pub fn with_angular_velocity(mut self, angular: $vec) -> Self {
+                *self.angular_momentum = self.world_inertia() * angular;
+                self
+            }
+
+            /// Inertia tensor in the body frame.
+            pub fn inertia(&self) -> $mat {
+                self.inertia
+            }
+
+            /// Set inertia tensor in the body frame.
+            ///
+            /// Angular momentum is kept, so angular velocity changes.
+            pub fn set_inertia(&mut self, inertia: $mat) {
+                self.inertia = inertia;
+                self.inv_inertia = inertia.inverse();
+            }
+
+            /// Inertia tensor in the world frame: `R I Rᵀ`.
+            pub fn world_inertia(&self) -> $mat {
+                let rot = self.rot.matrix();
+                rot * self.inertia * rot.transpose()
+            }
+
+            /// Velocity of the center of mass.
+            pub fn velocity(&self) -> $vec {
+                *self.momentum / self.mass
+            }
+
+            /// Angular velocity in the body frame: `I⁻¹ Rᵀ L`.
+            pub fn body_angular_velocity(&self) -> $vec {
+                self.inv_inertia
+                    .mul_vec3(self.rot.world_to_body(*self.angular_momentum))
+            }
+
+            /// Angular velocity in the world frame.
+            pub fn angular_velocity(&self) -> $vec {
+                self.rot.body_to_world(self.body_angular_velocity())
+            }
+
+            /// Velocity of a point fixed to the body, given in the world frame.
+            pub fn point_velocity(&self, point: $vec) -> $vec {
+                self.velocity() + self.angular_velocity().cross(point - *self.pos)
+            }
+
+            /// Kinetic energy of translation and rotation.
+            pub fn kinetic_energy(&self) -> $scalar {
+                0.5 * (self.momentum.length_squared() / self.mass
+                    + self.angular_velocity().dot(*self.angular_momentum))
+            }
+
+            /// Apply force at the center of mass.
+            pub fn apply_force(&mut self, force: $vec) {
+                self.force += force;
+            }
+
+            /// Apply force at a point given in the world frame, producing torque as well.
+            pub fn apply_force_at(&mut self, force: $vec, point: $vec) {
+                self.force += force;
+                self.torque += (point - *self.pos).cross(force);
+            }
+
+            /// Apply torque in the world frame.
+            pub fn apply_torque(&mut self, torque: $vec) {
+                self.torque += torque;
+            }
+
+            /// Reset accumulated force and torque.
+            pub fn clear_forces(&mut self) {
+                self.force = $vec::ZERO;
+                self.torque = $vec::ZERO;
+            }
+        }
+
+        impl<S: Solver<$scalar>> Vars<S, $scalar> for $body<S> {
+            fn visit_vars<V: Visitor<S, $scalar>>(&mut self, visitor: &mut V) {
+                visitor.apply(&mut self.pos);
+                visitor.apply(&mut self.rot);
+                visitor.apply(&mut self.momentum);
+                visitor.apply(&mut self.angular_momentum);
+            }
+        }
+
+        impl<S: Solver<$scalar>> System<S, $scalar> for $body<S> {
+            /// Compute derivatives from the accumulated force and torque.
+            fn compute_derivs(&mut self, _: &S::Context) {
+                self.pos.deriv = self.velocity();
+                self.rot.deriv = self.body_angular_velocity();
+                self.momentum.deriv = self.force;
+                self.angular_momentum.deriv = self.torque;
+            }
+
+            fn visit_vars<V: Visitor<S, $scalar>>(&mut self, visitor: &mut V) {
+                Vars::visit_vars(self, visitor);
+            }
+        }
+    };
+}
+
+impl_rigid2!(
+    /// Rigid body moving in a plane.
+    ///
+    /// Variables are the position of the center of mass, orientation, linear momentum
+    /// and angular momentum about the center of mass.
+    RigidBody2,
+    f32,
+    Vec2,
+    Rot2
+);
+impl_rigid2!(
+    /// Rigid body moving in a plane in double precision, see [`RigidBody2`].
+    DRigidBody2,
+    f64,
+    DVec2,
+    DRot2
+);
+
+impl_rigid3!(
+    /// Rigid body moving in space.
+    ///
+    /// Variables are the position of the center of mass, orientation, linear momentum
+    /// and angular momentum about the center of mass. Momenta are given in the world
+    /// frame.
+    ///
+    /// The inertia tensor is given in the body frame and is rotated with the body, so that
+    /// angular velocity `ω = R I⁻¹ Rᵀ L` changes even without torque. This accounts for
+    /// the gyroscopic term `ω × Iω` of Euler's equations, which are not integrated directly.
+    RigidBody3,
+    f32,
+    Vec3,
+    Mat3,
+    Rot3,
+    BodyRot3
+);
+impl_rigid3!(
+    /// Rigid body moving in space in double precision, see [`RigidBody3`].
+    DRigidBody3,
+    f64,
+    DVec3,
+    DMat3,
+    DRot3,
+    DBodyRot3
+);
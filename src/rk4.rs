@@ -1,4 +1,4 @@
-use crate::{Context, Param, Solver, System, Var, Visitor};
+use crate::{Context, DenseSolver, Deriv, Param, Scalar, Solver, System, Var, Visitor};
 
 /// The classical fourth-order Runge–Kutta method (RK4).
 ///
@@ -23,18 +23,91 @@ use crate::{Context, Param, Solver, System, Var, Visitor};
 /// RK4 requires additional storage per variable to hold intermediate
 /// computations between stages. This is automatically managed by the
 /// [`Rk4Storage`] type.
+///
+/// # Dense Output
+///
+/// Only the accumulated update is kept between stages, so the state cannot be
+/// interpolated within the step. Use [`Rk4Dense`] when dense output is needed.
+#[derive(Clone, Copy, Default, Debug)]
 pub struct Rk4;
 
+/// The classical fourth-order Runge–Kutta method with dense output.
+///
+/// Steps are exactly the same as those of [`Rk4`], but the stage derivatives are
+/// retained after the step in [`Rk4DenseStorage`], so the state can be interpolated
+/// anywhere within it with [`DenseSolver::interpolate`] using the third-order
+/// continuous extension of the method. This costs two more derivatives per variable.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Rk4Dense;
+
 /// Storage required by the RK4 solver for each variable.
 ///
 /// This stores the initial value `y_n` and accumulates weighted derivatives
 /// incrementally across RK4 stages to compute the final update.
+///
+/// # Dynamic Size
+///
+/// The storage is overwritten from the current value at the first stage of each
+/// step, so the size of a dynamically sized parameter (e.g. `Vec<P>`) may change
+/// between steps, but not within a step. [`Solver::restore_step`] restores the
+/// value at the beginning of the last step, including its size.
+///
+/// Variables created after the last step, e.g. pushed into a `Vec<Var<P, S>>`, have
+/// no step to restore, so [`Solver::restore_step`] keeps their current values.
 #[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, P::Deriv: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, P::Deriv: serde::Deserialize<'de>"
+    ))
+)]
 pub struct Rk4Storage<P: Param> {
     /// The initial value at the beginning of the RK4 step (y_n)
     init_value: P,
     /// Accumulated weighted derivatives: k1 + 2*k2 + 2*k3 + k4
     weighted_accum: P::Deriv,
+    /// Whether the variable has been stepped, so that `init_value` is meaningful
+    stepped: bool,
+}
+
+/// Storage required by the [`Rk4Dense`] solver for each variable.
+///
+/// This stores the initial value `y_n` and the stage derivatives. Derivatives
+/// of the second and third stages are only needed as a sum, both for the final
+/// update and for the dense output, so they are accumulated together.
+///
+/// # Dynamic Size
+///
+/// The storage is overwritten from the current value at the first stage of each
+/// step, so the size of a dynamically sized parameter (e.g. `Vec<P>`) may change
+/// between steps, but not within a step. Dense output refers to the last step,
+/// so interpolating a variable whose size has changed since then panics.
+///
+/// Variables created after the last step, e.g. pushed into a `Vec<Var<P, S>>`, have
+/// no step to interpolate, so interpolation and [`Solver::restore_step`] keep their
+/// current values.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, P::Deriv: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, P::Deriv: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Rk4DenseStorage<P: Param> {
+    /// The initial value at the beginning of the RK4 step (y_n)
+    init_value: P,
+    /// Derivative at the first stage (k1)
+    k1: P::Deriv,
+    /// Sum of derivatives at the second and third stages (k2 + k3)
+    k23: P::Deriv,
+    /// Derivative at the last stage (k4)
+    k4: P::Deriv,
+    /// Whether the variable has been stepped, so that the other fields describe the last step
+    stepped: bool,
 }
 
 /// The four stages of the RK4 algorithm.
@@ -51,97 +124,259 @@ enum Rk4Stage {
 }
 
 /// Visitor that applies a single RK4 stage to variables.
-pub struct Rk4Step {
+pub struct Rk4Step<T: Scalar = f32> {
     stage: Rk4Stage,
-    dt: f32,
+    /// Time at the beginning of the step (t_n)
+    t: T,
+    dt: T,
 }
 
-impl Context<Rk4> for Rk4Step {
-    /// Returns the time step used in this stage.
-    ///
-    /// Used as the `dt` parameter passed to `System::compute_derivs`
-    /// **before** the stage computations.
-    fn time_step(&self) -> f32 {
+impl<T: Scalar> Rk4Step<T> {
+    /// Time of the current stage: `t_n` for the first stage, `t_n + h/2` for the
+    /// second and third ones and `t_n + h` for the last one.
+    fn stage_time(&self) -> T {
         match self.stage {
-            Rk4Stage::Stage1 => self.dt / 2.0,
-            Rk4Stage::Stage2 => self.dt / 2.0,
-            Rk4Stage::Stage3 => self.dt,
-            Rk4Stage::Stage4 => self.dt,
+            Rk4Stage::Stage1 => self.t,
+            Rk4Stage::Stage2 | Rk4Stage::Stage3 => self.t + self.dt * T::from_f32(0.5),
+            Rk4Stage::Stage4 => self.t + self.dt,
         }
     }
 }
 
-impl Visitor<Rk4> for Rk4Step {
-    fn apply<P: Param>(&mut self, var: &mut Var<P, Rk4>) {
+impl<T: Scalar> Context<Rk4, T> for Rk4Step<T> {
+    /// Returns the time of the current stage.
+    fn time(&self) -> T {
+        self.stage_time()
+    }
+
+    fn time_step(&self) -> T {
+        self.dt
+    }
+}
+
+impl<T: Scalar> Context<Rk4Dense, T> for Rk4Step<T> {
+    /// Returns the time of the current stage.
+    fn time(&self) -> T {
+        self.stage_time()
+    }
+
+    fn time_step(&self) -> T {
+        self.dt
+    }
+}
+
+impl<T: Scalar> Visitor<Rk4, T> for Rk4Step<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Rk4>) {
         let y = &mut var.value;
         let dy_dt = &mut var.deriv;
-        let init_y = &mut var.storage.init_value;
-        let accum = &mut var.storage.weighted_accum;
+        let storage = &mut var.storage;
+        let init_y = &mut storage.init_value;
+        let accum = &mut storage.weighted_accum;
         let dt = self.dt;
+        let half_dt = T::from_f32(0.5) * dt;
+        let two = T::from_f32(2.0);
 
         match self.stage {
             Rk4Stage::Stage1 => {
                 // k1 = f(t_n, y_n)
                 // Save initial value y_n for use in subsequent stages
                 init_y.clone_from(y);
+                storage.stepped = true;
                 // Start accumulation with k1
                 accum.clone_from(dy_dt);
                 // Prepare state for stage 2: y = y_n + k1 * dt / 2
-                y.step(dy_dt, 0.5 * dt);
+                y.step(dy_dt, half_dt);
             }
             Rk4Stage::Stage2 => {
                 // k2 = f(t_n + dt/2, y_n + k1*dt/2)
                 // Prepare state for stage 3: y = y_n + k2 * dt / 2
                 y.clone_from(init_y);
-                y.step(dy_dt, 0.5 * dt);
+                y.step(dy_dt, half_dt);
                 // Accumulate: k1 + 2*k2
-                *dy_dt *= 2.0;
-                *accum += dy_dt;
+                accum.add_scaled(dy_dt, two);
             }
             Rk4Stage::Stage3 => {
                 // k3 = f(t_n + dt/2, y_n + k2*dt/2)
                 // Prepare state for stage 4: y = y_n + k3 * dt
-                *y = init_y.clone();
+                y.clone_from(init_y);
                 y.step(dy_dt, dt);
                 // Accumulate: k1 + 2*k2 + 2*k3
-                *dy_dt *= 2.0;
-                *accum += dy_dt;
+                accum.add_scaled(dy_dt, two);
             }
             Rk4Stage::Stage4 => {
                 // k4 = f(t_n + dt, y_n + k3*dt)
                 // Complete accumulation: (k1 + 2*k2 + 2*k3 + k4) / 6
-                *accum += dy_dt;
-                *accum *= 1.0 / 6.0;
+                accum.add_scaled(dy_dt, T::ONE);
+                accum.scale(T::from_f64(1.0 / 6.0));
                 // Final update: y_{n+1} = y_n + accum * dt
-                *y = init_y.clone();
+                y.clone_from(init_y);
                 y.step(accum, dt);
             }
         }
 
         // Reset derivative for next stage
-        *dy_dt = P::Deriv::default();
+        *dy_dt = y.zero_deriv();
     }
 }
 
-impl Solver for Rk4 {
-    type Context = Rk4Step;
-    type Storage<P: Param> = Rk4Storage<P>;
+impl<T: Scalar> Visitor<Rk4Dense, T> for Rk4Step<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Rk4Dense>) {
+        let y = &mut var.value;
+        let dy_dt = &mut var.deriv;
+        let storage = &mut var.storage;
+        let init_y = &mut storage.init_value;
+        let dt = self.dt;
+        let half_dt = T::from_f32(0.5) * dt;
 
-    fn solve_step<S: System<Self>>(&self, system: &mut S, dt: f32) {
-        // Execute the four RK4 stages in sequence
-        for stage in [
-            Rk4Stage::Stage1,
-            Rk4Stage::Stage2,
-            Rk4Stage::Stage3,
-            Rk4Stage::Stage4,
-        ] {
-            let mut step = Rk4Step { stage, dt };
+        match self.stage {
+            Rk4Stage::Stage1 => {
+                // k1 = f(t_n, y_n)
+                // Save initial value y_n for use in subsequent stages
+                init_y.clone_from(y);
+                storage.stepped = true;
+                storage.k1.clone_from(dy_dt);
+                // Prepare state for stage 2: y = y_n + k1 * dt / 2
+                y.step(dy_dt, half_dt);
+            }
+            Rk4Stage::Stage2 => {
+                // k2 = f(t_n + dt/2, y_n + k1*dt/2)
+                storage.k23.clone_from(dy_dt);
+                // Prepare state for stage 3: y = y_n + k2 * dt / 2
+                y.clone_from(init_y);
+                y.step(dy_dt, half_dt);
+            }
+            Rk4Stage::Stage3 => {
+                // k3 = f(t_n + dt/2, y_n + k2*dt/2)
+                storage.k23.add_scaled(dy_dt, T::ONE);
+                // Prepare state for stage 4: y = y_n + k3 * dt
+                y.clone_from(init_y);
+                y.step(dy_dt, dt);
+            }
+            Rk4Stage::Stage4 => {
+                // k4 = f(t_n + dt, y_n + k3*dt)
+                storage.k4.clone_from(dy_dt);
+                // Final update: y_{n+1} = y_n + (k1 + 2*(k2 + k3) + k4) * dt / 6
+                let mut incr = storage.k23.clone();
+                incr.scale(T::from_f32(2.0));
+                incr.add_scaled(&storage.k1, T::ONE);
+                incr.add_scaled(&storage.k4, T::ONE);
+                incr.scale(T::from_f64(1.0 / 6.0));
+                y.clone_from(init_y);
+                y.step(&incr, dt);
+            }
+        }
 
-            // Compute derivatives with the appropriate time step for this stage
-            system.compute_derivs(&step);
+        // Reset derivative for next stage
+        *dy_dt = y.zero_deriv();
+    }
+}
+
+impl<P: Param> Rk4DenseStorage<P> {
+    /// Weighted sum of the stage derivatives `sum(b_i(θ) * k_i)` at `θ` within the step.
+    ///
+    /// Uses the third-order continuous extension of RK4:
+    /// ```text
+    /// b_1(θ) = θ - 3θ²/2 + 2θ³/3
+    /// b_2(θ) = b_3(θ) = θ² - 2θ³/3
+    /// b_4(θ) = -θ²/2 + 2θ³/3
+    /// ```
+    /// At `θ = 1` these are the usual weights `1/6, 1/3, 1/3, 1/6`.
+    fn interpolate(&self, theta: P::Scalar) -> P::Deriv {
+        let c = P::Scalar::from_f64;
+        let (t2, t3) = (theta * theta, theta * theta * theta);
+        let mut sum = self.k1.clone();
+        sum.scale(theta - c(1.5) * t2 + c(2.0 / 3.0) * t3);
+        sum.add_scaled(&self.k23, t2 - c(2.0 / 3.0) * t3);
+        sum.add_scaled(&self.k4, c(-0.5) * t2 + c(2.0 / 3.0) * t3);
+        sum
+    }
+}
 
-            // Apply the RK4 stage to all variables
-            system.visit_vars(&mut step);
+/// Visitor that moves variables to the interpolated state within the last step.
+struct Rk4Interpolate<T: Scalar> {
+    dt: T,
+    theta: T,
+}
+
+impl<T: Scalar> Visitor<Rk4Dense, T> for Rk4Interpolate<T> {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Rk4Dense>) {
+        let storage = &var.storage;
+        if storage.stepped {
+            assert_eq!(
+                var.value.zero_deriv().dim(),
+                storage.init_value.zero_deriv().dim(),
+                "Variable size has changed since the last step"
+            );
+            var.value.clone_from(&storage.init_value);
+            var.value.step(&storage.interpolate(self.theta), self.dt);
         }
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+/// Visitor that restores variables to their values at the beginning of the step.
+///
+/// Variables which have not been stepped yet keep their values.
+struct Rk4Restore;
+
+impl<T: Scalar> Visitor<Rk4, T> for Rk4Restore {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Rk4>) {
+        if var.storage.stepped {
+            var.value.clone_from(&var.storage.init_value);
+        }
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+impl<T: Scalar> Visitor<Rk4Dense, T> for Rk4Restore {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Rk4Dense>) {
+        if var.storage.stepped {
+            var.value.clone_from(&var.storage.init_value);
+        }
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+/// Execute the four RK4 stages in sequence.
+macro_rules! impl_rk4_solver {
+    ($solver:ty, $storage:ident) => {
+        impl<T: Scalar> Solver<T> for $solver {
+            type Context = Rk4Step<T>;
+            type Storage<P: Param<Scalar = T>> = $storage<P>;
+
+            fn solve_step<S: System<Self, T>>(&self, system: &mut S, t: &mut T, dt: T) {
+                for stage in [
+                    Rk4Stage::Stage1,
+                    Rk4Stage::Stage2,
+                    Rk4Stage::Stage3,
+                    Rk4Stage::Stage4,
+                ] {
+                    let mut step = Rk4Step { stage, t: *t, dt };
+
+                    // Compute derivatives at the time of this stage
+                    system.compute_derivs(&step);
+
+                    // Apply the RK4 stage to all variables
+                    system.visit_vars(&mut step);
+                }
+
+                *t += dt;
+            }
+
+            fn restore_step<S: System<Self, T>>(&self, system: &mut S) {
+                system.visit_vars(&mut Rk4Restore);
+            }
+        }
+    };
+}
+
+impl_rk4_solver!(Rk4, Rk4Storage);
+impl_rk4_solver!(Rk4Dense, Rk4DenseStorage);
+
+impl<T: Scalar> DenseSolver<T> for Rk4Dense {
+    const DENSE_ORDER: u32 = 3;
+
+    fn interpolate<S: System<Self, T>>(&self, system: &mut S, dt: T, theta: T) {
+        system.visit_vars(&mut Rk4Interpolate { dt, theta });
     }
 }
@@ -1,12 +1,21 @@
 use crate::Param;
-use core::f32::consts::PI;
-use glam::{Mat2, Mat3, Quat, Vec2, Vec3};
+use core::{f32::consts::PI, f64::consts::PI as PI64};
+use glam::{DMat2, DMat3, DQuat, DVec2, DVec3, Mat2, Mat3, Quat, Vec2, Vec3};
 
 /// 2D rotation represented by an angle in radians.
 ///
 /// The angle is stored in radians and is kept within the range [0, 2π)
 /// through modulo arithmetic. This ensures consistent representation
 /// while preserving the mathematical properties of rotation.
+///
+/// # Examples
+/// ```
+/// use phy::Rot2;
+/// use core::f32::consts::PI;
+///
+/// let rot = Rot2::from_angle(3.0 * PI);
+/// assert!((rot.angle() - PI).abs() < 1e-6);
+/// ```
 #[derive(Clone, Copy, Default, Debug)]
 pub struct Rot2(f32);
 
@@ -14,188 +23,412 @@ pub struct Rot2(f32);
 ///
 /// Uses `glam::Quat` internally to represent 3D rotations.
 /// The quaternion is always normalized to maintain unit length.
+///
+/// # Examples
+/// ```
+/// use phy::Rot3;
+/// use glam::Vec3;
+///
+/// // 90-degree rotation around the z-axis
+/// let rot = Rot3::from_scaled_axis(Vec3::Z * std::f32::consts::FRAC_PI_2);
+/// assert!(rot.transform(Vec3::X).abs_diff_eq(Vec3::Y, 1e-6));
+/// ```
 #[derive(Clone, Copy, Debug)]
 pub struct Rot3(Quat);
 
-impl From<f32> for Rot2 {
-    fn from(value: f32) -> Self {
-        Self(value)
-    }
-}
-impl From<Rot2> for f32 {
-    fn from(value: Rot2) -> Self {
-        value.0
-    }
-}
+/// 2D rotation in double precision, see [`Rot2`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DRot2(f64);
 
-impl From<Quat> for Rot3 {
-    fn from(value: Quat) -> Self {
-        Self(value)
-    }
-}
-impl From<Rot3> for Quat {
-    fn from(value: Rot3) -> Self {
-        value.0
-    }
-}
+/// 3D rotation in double precision represented by `glam::DQuat`, see [`Rot3`].
+#[derive(Clone, Copy, Debug)]
+pub struct DRot3(DQuat);
 
-impl Default for Rot3 {
-    fn default() -> Self {
-        Self(Quat::IDENTITY)
-    }
-}
+/// 3D rotation whose derivative is angular velocity in the body frame.
+///
+/// The same rotation as [`Rot3`], but [`Param::step`] multiplies the increment
+/// from the right, so that the derivative is the angular velocity expressed in the
+/// rotating frame, as produced by Euler's equations of rigid body motion.
+/// Use [`world_to_body`](Self::world_to_body) and [`body_to_world`](Self::body_to_world)
+/// to convert vectors between frames.
+#[derive(Clone, Copy, Debug)]
+pub struct BodyRot3(Quat);
+
+/// Body-frame 3D rotation in double precision, see [`BodyRot3`].
+#[derive(Clone, Copy, Debug)]
+pub struct DBodyRot3(DQuat);
+
+// Rotation operations common for single and double precision
+
+macro_rules! impl_rot2 {
+    ($rot:ident, $scalar:ident, $vec:ty, $mat:ident, $pi:expr) => {
+        impl From<$scalar> for $rot {
+            fn from(value: $scalar) -> Self {
+                Self(value)
+            }
+        }
+        impl From<$rot> for $scalar {
+            fn from(value: $rot) -> Self {
+                value.0
+            }
+        }
+
+        impl $rot {
+            /// Wrap an angle to the range [0, 2π) using Euclidean remainder.
+            #[cfg(feature = "std")]
+            fn wrap(angle: $scalar) -> $scalar {
+                angle.rem_euclid(2.0 * $pi)
+            }
+
+            /// Wrap an angle to the range [0, 2π) using Euclidean remainder.
+            ///
+            /// The same as `rem_euclid`, which is not available without `std`.
+            #[cfg(not(feature = "std"))]
+            fn wrap(angle: $scalar) -> $scalar {
+                let r = angle % (2.0 * $pi);
+                if r < 0.0 { r + 2.0 * $pi } else { r }
+            }
+
+            /// Create a 2D rotation from an angle in radians.
+            ///
+            /// The angle is wrapped into the range [0, 2π) using Euclidean
+            /// remainder for improved numerical stability.
+            pub fn from_angle(angle: $scalar) -> Self {
+                Self(Self::wrap(angle))
+            }
+
+            /// Get the angle in radians, in the range [0, 2π)
+            pub fn angle(self) -> $scalar {
+                self.0
+            }
+
+            /// Get the angle in degrees, in the range [0, 360)
+            pub fn angle_degrees(self) -> $scalar {
+                (180.0 / $pi) * self.angle()
+            }
+
+            /// Get the 2D rotation matrix.
+            ///
+            /// Returns a 2x2 rotation matrix that can be used to transform vectors.
+            pub fn matrix(self) -> $mat {
+                $mat::from_angle(self.0)
+            }
+
+            /// Transform a 2D vector by this rotation.
+            ///
+            /// Applies the rotation to the given vector, returning the rotated vector.
+            pub fn transform(&self, v: $vec) -> $vec {
+                self.matrix().mul_vec2(v)
+            }
+
+            /// Chain this rotation with another rotation.
+            ///
+            /// Returns a new rotation that represents applying `self` then `other`.
+            /// The resulting angle is wrapped to [0, 2π).
+            pub fn chain(self, other: Self) -> Self {
+                Self(Self::wrap(self.0 + other.0))
+            }
+
+            /// Get the inverse rotation.
+            ///
+            /// Returns a rotation that undoes this rotation.
+            pub fn inverse(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Param for $rot {
+            type Scalar = $scalar;
+
+            /// Angular speed in radians per unit time.
+            type Deriv = $scalar;
 
-/// Wrap an angle to the range [0, 2π) using Euclidean remainder.
-#[cfg(feature = "std")]
-fn wrap_angle(angle: f32) -> f32 {
-    angle.rem_euclid(2.0 * PI)
+            /// Advance the rotation by integrating angular velocity over time.
+            ///
+            /// Computes: `angle_{n+1} = angle_n + ω * dt` (modulo 2π)
+            /// where ω is the angular velocity (`dp`).
+            fn step(&mut self, dp: &$scalar, dt: $scalar) {
+                *self = self.chain(Self::from_angle(dp * dt));
+            }
+
+            /// Rotations have no natural scale, so errors are measured in radians.
+            fn magnitude(&self) -> $scalar {
+                1.0
+            }
+
+            /// Flattened to the angle in radians.
+            fn flat_dim(&self) -> usize {
+                1
+            }
+
+            fn write_flat(&self, out: &mut [$scalar]) {
+                out[0] = self.angle();
+            }
+
+            fn read_flat(&mut self, data: &[$scalar]) {
+                *self = Self::from_angle(data[0]);
+            }
+        }
+    };
 }
 
-/// Wrap an angle to the range [0, 2π) using Euclidean remainder.
-#[cfg(not(feature = "std"))]
-fn wrap_angle(angle: f32) -> f32 {
-    libm::remainderf(angle, 2.0 * PI)
+macro_rules! impl_rot3 {
+    ($rot:ident, $scalar:ty, $quat:ident, $vec:ty, $mat:ident) => {
+        impl From<$quat> for $rot {
+            fn from(value: $quat) -> Self {
+                Self(value)
+            }
+        }
+        impl From<$rot> for $quat {
+            fn from(value: $rot) -> Self {
+                value.0
+            }
+        }
+
+        impl Default for $rot {
+            fn default() -> Self {
+                Self($quat::IDENTITY)
+            }
+        }
+
+        impl $rot {
+            /// Create a 3D rotation from an axis-angle representation.
+            ///
+            /// # Arguments
+            /// * `v` - A vector where the direction represents the rotation axis
+            ///   and the magnitude represents the rotation angle in radians.
+            pub fn from_scaled_axis(v: $vec) -> Self {
+                Self($quat::from_scaled_axis(v))
+            }
+
+            /// Get the 3D rotation matrix.
+            ///
+            /// Returns a 3x3 rotation matrix that can be used to transform vectors.
+            pub fn matrix(self) -> $mat {
+                $mat::from_quat(self.0)
+            }
+
+            /// Transform a 3D vector by this rotation, i.e. from the body frame to the world frame.
+            ///
+            /// Applies the rotation to the given vector, returning the rotated vector.
+            pub fn transform(self, v: $vec) -> $vec {
+                self.0.mul_vec3(v)
+            }
+
+            /// Chain this rotation with another rotation.
+            ///
+            /// Returns a new rotation that represents applying `self` then `other`.
+            /// The resulting quaternion is normalized to maintain unit length.
+            ///
+            /// # Arguments
+            /// * `other` - The rotation to apply after this one.
+            pub fn chain(self, other: Self) -> Self {
+                Self(other.0.mul_quat(self.0).normalize())
+            }
+
+            /// Get the inverse rotation.
+            ///
+            /// Returns a rotation that undoes this rotation.
+            pub fn inverse(self) -> Self {
+                Self(self.0.inverse())
+            }
+
+            /// Convert a vector (e.g. angular velocity) from the world frame to the body frame.
+            pub fn world_to_body(self, v: $vec) -> $vec {
+                self.0.inverse().mul_vec3(v)
+            }
+
+            /// Convert a vector (e.g. angular velocity) from the body frame to the world frame.
+            ///
+            /// The same as [`transform`](Self::transform).
+            pub fn body_to_world(self, v: $vec) -> $vec {
+                self.transform(v)
+            }
+        }
+    };
 }
 
-impl Rot2 {
-    /// Create a 2D rotation from an angle in radians.
-    ///
-    /// The angle is wrapped into the range [0, 2π) using Euclidean
-    /// remainder for improved numerical stability.
-    ///
-    /// # Examples
-    /// ```
-    /// use phy::Rot2;
-    /// use core::f32::consts::PI;
-    ///
-    /// let rot = Rot2::from_angle(3.0 * PI);
-    /// assert!((rot.angle() - PI).abs() < 1e-6);
-    /// ```
-    pub fn from_angle(angle: f32) -> Self {
-        Self(wrap_angle(angle))
-    }
-
-    /// Get the angle in radians, in the range [0, 2π)
-    pub fn angle(self) -> f32 {
-        self.0
-    }
-
-    /// Get the angle in degrees, in the range [0, 360)
-    pub fn angle_degrees(self) -> f32 {
-        (180.0 / PI) * self.angle()
-    }
-
-    /// Get the 2D rotation matrix.
-    ///
-    /// Returns a 2x2 rotation matrix that can be used to transform vectors.
-    pub fn matrix(self) -> Mat2 {
-        Mat2::from_angle(self.0)
-    }
-
-    /// Transform a 2D vector by this rotation.
-    ///
-    /// Applies the rotation to the given vector, returning the rotated vector.
-    pub fn transform(&self, v: Vec2) -> Vec2 {
-        self.matrix().mul_vec2(v)
-    }
-
-    /// Chain this rotation with another rotation.
-    ///
-    /// Returns a new rotation that represents applying `self` then `other`.
-    /// The resulting angle is wrapped to [0, 2π).
-    pub fn chain(self, other: Self) -> Self {
-        Self(wrap_angle(self.0 + other.0))
-    }
-
-    /// Get the inverse rotation.
-    ///
-    /// Returns a rotation that undoes this rotation.
-    pub fn inverse(self) -> Self {
-        Self(-self.0)
-    }
+/// Implement [`Param`] for a 3D rotation, whose derivative is angular velocity
+/// in the world frame (`world`) or in the body frame (`body`).
+macro_rules! impl_param_rot3 {
+    (@step world, $rot:ident, $incr:expr) => {
+        *$rot = $rot.chain($incr)
+    };
+    (@step body, $rot:ident, $incr:expr) => {
+        *$rot = $incr.chain(*$rot)
+    };
+    ($rot:ident, $scalar:ty, $quat:ident, $vec:ty, $frame:ident, $step_doc:literal) => {
+        impl Param for $rot {
+            type Scalar = $scalar;
+
+            /// Angular velocity vector.
+            ///
+            /// The direction is the axis of rotation, and the magnitude is the
+            /// angular speed in radians per unit time.
+            type Deriv = $vec;
+
+            /// Advance the rotation by integrating angular velocity over time.
+            ///
+            #[doc = $step_doc]
+            fn step(&mut self, dp: &$vec, dt: $scalar) {
+                impl_param_rot3!(@step $frame, self, Self::from_scaled_axis(dp * dt));
+            }
+
+            /// Rotations have no natural scale, so errors are measured in radians.
+            fn magnitude(&self) -> $scalar {
+                1.0
+            }
+
+            /// Flattened to the quaternion components `x`, `y`, `z`, `w`.
+            fn flat_dim(&self) -> usize {
+                4
+            }
+
+            fn write_flat(&self, out: &mut [$scalar]) {
+                self.0.write_to_slice(out);
+            }
+
+            /// The quaternion is normalized after reading.
+            fn read_flat(&mut self, data: &[$scalar]) {
+                self.0 = $quat::from_slice(data).normalize();
+            }
+        }
+    };
 }
 
-impl Rot3 {
-    /// Create a 3D rotation from an axis-angle representation.
-    ///
-    /// # Arguments
-    /// * `v` - A vector where the direction represents the rotation axis
-    ///   and the magnitude represents the rotation angle in radians.
-    ///
-    /// # Examples
-    /// ```
-    /// use phy::Rot3;
-    /// use glam::Vec3;
-    ///
-    /// // 90-degree rotation around the z-axis
-    /// let rot = Rot3::from_scaled_axis(Vec3::Z * std::f32::consts::FRAC_PI_2);
-    /// ```
-    pub fn from_scaled_axis(v: Vec3) -> Self {
-        Self(Quat::from_scaled_axis(v))
-    }
-
-    /// Get the 3D rotation matrix.
-    ///
-    /// Returns a 3x3 rotation matrix that can be used to transform vectors.
-    pub fn matrix(self) -> Mat3 {
-        Mat3::from_quat(self.0)
-    }
-
-    /// Transform a 3D vector by this rotation.
-    ///
-    /// Applies the rotation to the given vector, returning the rotated vector.
-    pub fn transform(self, v: Vec3) -> Vec3 {
-        self.0.mul_vec3(v)
-    }
-
-    /// Chain this rotation with another rotation.
-    ///
-    /// Returns a new rotation that represents applying `self` then `other`.
-    /// The resulting quaternion is normalized to maintain unit length.
-    ///
-    /// # Arguments
-    /// * `other` - The rotation to apply after this one.
-    pub fn chain(self, other: Self) -> Self {
-        Self(other.0.mul_quat(self.0).normalize())
-    }
-
-    /// Get the inverse rotation.
-    ///
-    /// Returns a rotation that undoes this rotation.
-    pub fn inverse(self) -> Self {
-        Self(self.0.inverse())
-    }
+/// Conversions between world-frame and body-frame representations of the same rotation.
+macro_rules! impl_body_rot3 {
+    ($body:ident, $rot:ident) => {
+        impl From<$rot> for $body {
+            fn from(value: $rot) -> Self {
+                Self(value.0)
+            }
+        }
+        impl From<$body> for $rot {
+            fn from(value: $body) -> Self {
+                Self(value.0)
+            }
+        }
+    };
 }
 
-impl Param for Rot2 {
-    /// Angular speed in radians per unit time.
-    type Deriv = f32;
-
-    /// Advance the rotation by integrating angular velocity over time.
-    ///
-    /// Computes: `angle_{n+1} = angle_n + ω * dt` (modulo 2π)
-    /// where ω is the angular velocity (`dp`).
-    fn step(&mut self, dp: &f32, dt: f32) {
-        *self = self.chain(Rot2::from_angle(dp * dt));
-    }
+impl_rot2!(Rot2, f32, Vec2, Mat2, PI);
+impl_rot2!(DRot2, f64, DVec2, DMat2, PI64);
+
+impl_rot3!(Rot3, f32, Quat, Vec3, Mat3);
+impl_rot3!(DRot3, f64, DQuat, DVec3, DMat3);
+impl_rot3!(BodyRot3, f32, Quat, Vec3, Mat3);
+impl_rot3!(DBodyRot3, f64, DQuat, DVec3, DMat3);
+
+impl_param_rot3!(
+    Rot3,
+    f32,
+    Quat,
+    Vec3,
+    world,
+    "The rotation increment from the axis-angle representation of `dp * dt` \
+     is applied after the current rotation: `q_{n+1} = exp(dp * dt) * q_n`."
+);
+impl_param_rot3!(
+    DRot3,
+    f64,
+    DQuat,
+    DVec3,
+    world,
+    "The rotation increment from the axis-angle representation of `dp * dt` \
+     is applied after the current rotation: `q_{n+1} = exp(dp * dt) * q_n`."
+);
+impl_param_rot3!(
+    BodyRot3,
+    f32,
+    Quat,
+    Vec3,
+    body,
+    "The derivative is expressed in the body frame, so the rotation increment from \
+     the axis-angle representation of `dp * dt` is applied before the current rotation: \
+     `q_{n+1} = q_n * exp(dp * dt)`."
+);
+impl_param_rot3!(
+    DBodyRot3,
+    f64,
+    DQuat,
+    DVec3,
+    body,
+    "The derivative is expressed in the body frame, so the rotation increment from \
+     the axis-angle representation of `dp * dt` is applied before the current rotation: \
+     `q_{n+1} = q_n * exp(dp * dt)`."
+);
+
+impl_body_rot3!(BodyRot3, Rot3);
+impl_body_rot3!(DBodyRot3, DRot3);
+
+/// Maximum deviation of the norm of a deserialized quaternion from one.
+#[cfg(feature = "serde")]
+const UNIT_TOLERANCE: f64 = 1e-3;
+
+/// Serialize 2D rotations as angles in radians, wrapping them on load.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_rot2 {
+    ($rot:ident, $scalar:ty) => {
+        impl serde::Serialize for $rot {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $rot {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let angle = <$scalar as serde::Deserialize>::deserialize(deserializer)?;
+                if !angle.is_finite() {
+                    return Err(serde::de::Error::custom("rotation angle is not finite"));
+                }
+                Ok(Self::from_angle(angle))
+            }
+        }
+    };
 }
 
-impl Param for Rot3 {
-    /// Angular velocity vector.
-    ///
-    /// The direction is the axis of rotation, and the magnitude is the
-    /// angular speed in radians per unit time.
-    type Deriv = Vec3;
-
-    /// Advance the rotation by integrating angular velocity over time.
-    ///
-    /// Computes a rotation increment from the axis-angle representation
-    /// of `dp * dt` and chains it with the current rotation.
-    fn step(&mut self, dp: &Vec3, dt: f32) {
-        *self = self.chain(Rot3::from_scaled_axis(dp * dt));
-    }
+/// Serialize 3D rotations as normalized quaternions `[x, y, z, w]`.
+///
+/// Quaternions are validated on load: their components must be finite and their
+/// norm must not deviate from one by more than [`UNIT_TOLERANCE`].
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_rot3 {
+    ($rot:ident, $quat:ident) => {
+        impl serde::Serialize for $rot {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.0.normalize(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $rot {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let quat = <$quat as serde::Deserialize>::deserialize(deserializer)?;
+                if !quat.is_finite() {
+                    return Err(serde::de::Error::custom("quaternion is not finite"));
+                }
+                if (quat.length() as f64 - 1.0).abs() > UNIT_TOLERANCE {
+                    return Err(serde::de::Error::custom("quaternion is not normalized"));
+                }
+                Ok(Self(quat.normalize()))
+            }
+        }
+    };
 }
 
+#[cfg(feature = "serde")]
+impl_serde_rot2!(Rot2, f32);
+#[cfg(feature = "serde")]
+impl_serde_rot2!(DRot2, f64);
+#[cfg(feature = "serde")]
+impl_serde_rot3!(Rot3, Quat);
+#[cfg(feature = "serde")]
+impl_serde_rot3!(DRot3, DQuat);
+#[cfg(feature = "serde")]
+impl_serde_rot3!(BodyRot3, Quat);
+#[cfg(feature = "serde")]
+impl_serde_rot3!(DBodyRot3, DQuat);
+
 /// Compute the moment of force (torque) in 2D.
 ///
 /// In 2D, torque is a scalar representing the magnitude of rotational force.
@@ -0,0 +1,151 @@
+use core::{
+    fmt::Debug,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// Floating-point type used for time and parameter components.
+///
+/// Implemented for `f32` and `f64`. Solvers are generic over the scalar type,
+/// so that systems which need more precision (e.g. orbital simulations over
+/// long periods of time) can be integrated entirely in `f64`.
+pub trait Scalar:
+    Copy
+    + Default
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+    + 'static
+{
+    /// Zero value.
+    const ZERO: Self;
+    /// Unit value.
+    const ONE: Self;
+    /// Machine epsilon.
+    const EPSILON: Self;
+
+    /// Convert from `f32`, used for tolerances and step control settings.
+    fn from_f32(value: f32) -> Self;
+
+    /// Convert from `f64`, possibly losing precision.
+    ///
+    /// Used for method coefficients, which are stored in `f64`.
+    fn from_f64(value: f64) -> Self;
+
+    /// Convert to `f32`, possibly losing precision.
+    fn to_f32(self) -> f32;
+
+    /// Convert to `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Absolute value.
+    fn abs(self) -> Self;
+
+    /// Square root.
+    fn sqrt(self) -> Self;
+
+    /// Raise to a floating-point power.
+    fn powf(self, exponent: Self) -> Self;
+
+    /// Whether the value is NaN.
+    fn is_nan(self) -> bool;
+
+    /// Whether the value is neither infinite nor NaN.
+    fn is_finite(self) -> bool;
+
+    /// Maximum of two values, ignoring NaN.
+    fn max(self, other: Self) -> Self;
+
+    /// Minimum of two values, ignoring NaN.
+    fn min(self, other: Self) -> Self;
+}
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const EPSILON: Self = f32::EPSILON;
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+    fn powf(self, exponent: Self) -> Self {
+        libm::powf(self, exponent)
+    }
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+    fn min(self, other: Self) -> Self {
+        f32::min(self, other)
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const EPSILON: Self = f64::EPSILON;
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+    fn powf(self, exponent: Self) -> Self {
+        libm::pow(self, exponent)
+    }
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+    fn min(self, other: Self) -> Self {
+        f64::min(self, other)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{DenseSolver, Scalar, Solver, System};
+
+/// Result of advancing a [`Simulation`] by one frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame<T: Scalar = f32> {
+    /// Number of fixed steps taken.
+    pub steps: usize,
+    /// Position of the frame time between the last two steps, in the range `[0, 1)`.
+    ///
+    /// Used to blend the states before and after the last step for rendering,
+    /// see [`Simulation::interpolated`].
+    pub alpha: T,
+    /// Time that was not simulated because of the limit on the number of steps.
+    pub dropped: T,
+}
+
+/// Driver integrating a system with a fixed time step in real time.
+///
+/// Frames of varying duration are accumulated and consumed by steps of the fixed
+/// size `dt`, so that results do not depend on the frame rate. Time remaining
+/// after the last step is carried over to the next frame and is reported as the
+/// interpolation factor `alpha` ("fix your timestep" pattern).
+///
+/// If the simulation cannot keep up, the number of steps per frame is limited by
+/// [`max_steps`](Self::max_steps) and the rest of the frame time is dropped, so the
+/// simulation slows down instead of taking more and more steps each frame.
+///
+/// # Example
+/// ```
+/// use phy::{Rk4Dense, Simulation, Solver, System, Var, Visitor};
+///
+/// #[derive(Clone)]
+/// struct Decay<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Decay<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = -*self.x;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let mut sim = Simulation::new(Decay { x: Var::new(1.0) }, Rk4Dense, 0.01);
+/// // Frames of 60 Hz
+/// for _ in 0..60 {
+///     let frame = sim.advance(1.0 / 60.0);
+///     assert!(frame.steps <= 2);
+///     // Render the state at the frame time
+///     let state = sim.interpolated();
+///     assert!((*state.x - (-sim.render_time()).exp()).abs() < 1e-5);
+/// }
+/// assert!((sim.time() - 1.0).abs() < 0.01);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Simulation<Sys, Solv, T: Scalar = f32> {
+    system: Sys,
+    solver: Solv,
+    time: T,
+    dt: T,
+    accumulator: T,
+    stepped: bool,
+    /// Maximum number of steps taken in a single frame, 16 by default.
+    ///
+    /// Should be large enough to cover the longest expected frame.
+    pub max_steps: usize,
+}
+
+impl<Sys, Solv, T: Scalar> Simulation<Sys, Solv, T>
+where
+    Solv: Solver<T>,
+    Sys: System<Solv, T>,
+{
+    /// Create a simulation starting at zero time.
+    ///
+    /// # Arguments
+    /// * `system` - The system to integrate.
+    /// * `solver` - The solver used for integration.
+    /// * `dt` - Fixed time step, must be positive.
+    pub fn new(system: Sys, solver: Solv, dt: T) -> Self {
+        assert!(dt > T::ZERO, "Time step must be positive");
+        Self {
+            system,
+            solver,
+            time: T::ZERO,
+            dt,
+            accumulator: T::ZERO,
+            stepped: false,
+            max_steps: 16,
+        }
+    }
+
+    /// Set the maximum number of steps taken in a single frame.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Set the starting time.
+    pub fn with_time(mut self, time: T) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Advance the simulation by the real time elapsed since the last frame.
+    ///
+    /// Takes as many fixed steps as fit into the accumulated time, but no more
+    /// than [`max_steps`](Self::max_steps).
+    pub fn advance(&mut self, frame_dt: T) -> Frame<T> {
+        self.accumulator += frame_dt;
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps {
+            self.step();
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        let mut dropped = T::ZERO;
+        if self.accumulator >= self.dt {
+            // Keep only the fraction of a step, the remainder is computed exactly
+            let kept = T::from_f64(libm::fmod(self.accumulator.to_f64(), self.dt.to_f64()));
+            dropped = self.accumulator - kept;
+            self.accumulator = kept;
+        }
+        Frame {
+            steps,
+            alpha: self.alpha(),
+            dropped,
+        }
+    }
+
+    /// Take a single fixed step, regardless of accumulated time.
+    pub fn step(&mut self) {
+        self.solver
+            .solve_step(&mut self.system, &mut self.time, self.dt);
+        self.stepped = true;
+    }
+
+    /// Fraction of the step accumulated but not yet simulated, in the range `[0, 1)`.
+    pub fn alpha(&self) -> T {
+        self.accumulator / self.dt
+    }
+
+    /// Time of the state to be rendered.
+    ///
+    /// Rendering lags the simulation by up to one step, so that the frame time
+    /// lies between the last two steps: `time - (1 - alpha) * dt`.
+    /// Before the first step this is the starting time.
+    pub fn render_time(&self) -> T {
+        if self.stepped {
+            self.time - (T::ONE - self.alpha()) * self.dt
+        } else {
+            self.time
+        }
+    }
+
+    /// Current state of the system.
+    pub fn system(&self) -> &Sys {
+        &self.system
+    }
+
+    /// Mutable access to the system, e.g. to apply user input.
+    ///
+    /// Changes made to the state between steps are not reflected by
+    /// [`interpolated`](Self::interpolated) until the next step.
+    pub fn system_mut(&mut self) -> &mut Sys {
+        &mut self.system
+    }
+
+    /// Stop the simulation and take the system out of it.
+    pub fn into_system(self) -> Sys {
+        self.system
+    }
+
+    /// Solver used for integration.
+    pub fn solver(&self) -> &Solv {
+        &self.solver
+    }
+
+    /// Simulated time at the end of the last step.
+    pub fn time(&self) -> T {
+        self.time
+    }
+
+    /// Fixed time step.
+    pub fn dt(&self) -> T {
+        self.dt
+    }
+}
+
+impl<Sys, Solv, T: Scalar> Simulation<Sys, Solv, T>
+where
+    Solv: DenseSolver<T>,
+    Sys: System<Solv, T> + Clone,
+{
+    /// State of the system at [`render_time`](Self::render_time), interpolated within
+    /// the last step with the dense output of the solver.
+    ///
+    /// Before the first step the current state is returned.
+    pub fn interpolated(&self) -> Sys {
+        let mut system = self.system.clone();
+        if self.stepped {
+            self.solver.interpolate(&mut system, self.dt, self.alpha());
+        }
+        system
+    }
+}
@@ -0,0 +1,80 @@
+use crate::Scalar;
+
+/// Saved state of a system together with its time.
+///
+/// The whole system is cloned, so that variables with their derivatives and solver
+/// storage are saved along with the fields which are not variables, e.g. inputs or
+/// counters. Restoring a snapshot makes further steps reproduce the same results bit for
+/// bit, e.g. for rollback and replay or for branching into alternative futures. Unlike
+/// [`flatten_state`](crate::flatten_state), the state is opaque and can only be restored
+/// into a system of the same type.
+///
+/// # Example
+/// ```
+/// use phy::{Rk4, Snapshot, Solver, System, Var, Visitor};
+///
+/// #[derive(Clone)]
+/// struct Decay<S: Solver> {
+///     x: Var<f32, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Decay<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.x.deriv = -*self.x;
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply(&mut self.x);
+///     }
+/// }
+///
+/// let mut system = Decay::<Rk4> { x: Var::new(1.0) };
+/// let mut t = 0.0;
+/// let snapshot = Snapshot::new(&system, t);
+/// Rk4.solve_step(&mut system, &mut t, 0.1);
+/// let x = *system.x;
+///
+/// snapshot.restore(&mut system, &mut t);
+/// assert_eq!((t, *system.x), (0.0, 1.0));
+/// Rk4.solve_step(&mut system, &mut t, 0.1);
+/// assert_eq!(*system.x, x);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snapshot<Y, T: Scalar = f32> {
+    system: Y,
+    t: T,
+}
+
+impl<Y: Clone, T: Scalar> Snapshot<Y, T> {
+    /// Capture the current state of the system at time `t`.
+    pub fn new(system: &Y, t: T) -> Self {
+        Self {
+            system: system.clone(),
+            t,
+        }
+    }
+
+    /// Replace the saved state with the current state of the system at time `t`.
+    ///
+    /// Buffers of the saved system are reused where its `Clone` implementation allows.
+    pub fn save(&mut self, system: &Y, t: T) {
+        self.system.clone_from(system);
+        self.t = t;
+    }
+
+    /// Restore the saved state into the system and the saved time into `t`.
+    pub fn restore(&self, system: &mut Y, t: &mut T) {
+        system.clone_from(&self.system);
+        *t = self.t;
+    }
+
+    /// Saved state of the system.
+    pub fn system(&self) -> &Y {
+        &self.system
+    }
+
+    /// Saved time.
+    pub fn time(&self) -> T {
+        self.t
+    }
+}
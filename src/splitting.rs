@@ -0,0 +1,151 @@
+//! Ready-made splitting methods for [`Symplectic`](crate::Symplectic) solver.
+//!
+//! # Symplectic Euler
+//! - [`KickDrift`]: velocities are updated first, then positions with the new velocities.
+//! - [`DriftKick`]: positions are updated first, then velocities at the new positions.
+//!
+//! # Compositions of leapfrog
+//! - [`ForestRuth`]: fourth order, three evaluations of accelerations per step.
+//! - [`Yoshida6`]: sixth order, seven evaluations per step.
+//! - [`Yoshida8`]: eighth order, fifteen evaluations per step.
+//!
+//! Compositions apply the drift-kick-drift leapfrog with steps `w_k*h`, where some of
+//! the weights `w_k` are negative. Adjacent half drifts are merged, so the number of
+//! evaluations equals the number of leapfrog steps. All of them are time-symmetric.
+
+use crate::Splitting;
+
+/// Symplectic Euler method updating velocities before positions.
+///
+/// ```text
+/// v_{n+1} = v_n + a(x_n) * h
+/// x_{n+1} = x_n + v_{n+1} * h
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KickDrift;
+
+impl Splitting for KickDrift {
+    const ORDER: u32 = 1;
+    const DRIFT: &'static [f64] = &[0.0, 1.0];
+    const KICK: &'static [f64] = &[1.0, 0.0];
+}
+
+/// Symplectic Euler method updating positions before velocities.
+///
+/// ```text
+/// x_{n+1} = x_n + v_n * h
+/// v_{n+1} = v_n + a(x_{n+1}) * h
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DriftKick;
+
+impl Splitting for DriftKick {
+    const ORDER: u32 = 1;
+    const DRIFT: &'static [f64] = &[1.0];
+    const KICK: &'static [f64] = &[1.0];
+}
+
+/// Forest–Ruth fourth-order method.
+///
+/// Composition of three leapfrog steps with weights `θ, 1 - 2θ, θ`,
+/// where `θ = 1 / (2 - 2^(1/3))`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ForestRuth;
+
+/// Weight of the outer steps of the Forest–Ruth method.
+const THETA: f64 = 1.3512071919596578;
+
+impl Splitting for ForestRuth {
+    const ORDER: u32 = 4;
+    const DRIFT: &'static [f64] = &[
+        THETA / 2.0,
+        (1.0 - THETA) / 2.0,
+        (1.0 - THETA) / 2.0,
+        THETA / 2.0,
+    ];
+    const KICK: &'static [f64] = &[THETA, 1.0 - 2.0 * THETA, THETA, 0.0];
+}
+
+/// Yoshida's sixth-order method (solution A).
+///
+/// Composition of seven leapfrog steps with weights `w3, w2, w1, w0, w1, w2, w3`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Yoshida6;
+
+/// Leapfrog weights of [`Yoshida6`].
+mod y6 {
+    pub const W1: f64 = -1.17767998417887;
+    pub const W2: f64 = 0.235573213359357;
+    pub const W3: f64 = 0.784513610477560;
+    pub const W0: f64 = 1.0 - 2.0 * (W1 + W2 + W3);
+}
+
+impl Splitting for Yoshida6 {
+    const ORDER: u32 = 6;
+    const DRIFT: &'static [f64] = {
+        use y6::*;
+        &[
+            W3 / 2.0,
+            (W3 + W2) / 2.0,
+            (W2 + W1) / 2.0,
+            (W1 + W0) / 2.0,
+            (W0 + W1) / 2.0,
+            (W1 + W2) / 2.0,
+            (W2 + W3) / 2.0,
+            W3 / 2.0,
+        ]
+    };
+    const KICK: &'static [f64] = {
+        use y6::*;
+        &[W3, W2, W1, W0, W1, W2, W3, 0.0]
+    };
+}
+
+/// Yoshida's eighth-order method (solution D).
+///
+/// Composition of fifteen leapfrog steps with weights `w7, ..., w1, w0, w1, ..., w7`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Yoshida8;
+
+/// Leapfrog weights of [`Yoshida8`].
+mod y8 {
+    pub const W1: f64 = 0.102799849391985;
+    pub const W2: f64 = -1.96061023297549;
+    pub const W3: f64 = 1.93813913762276;
+    pub const W4: f64 = -0.158240635368243;
+    pub const W5: f64 = -1.44485223686048;
+    pub const W6: f64 = 0.253693336566229;
+    pub const W7: f64 = 0.914844246229740;
+    pub const W0: f64 = 1.0 - 2.0 * (W1 + W2 + W3 + W4 + W5 + W6 + W7);
+}
+
+impl Splitting for Yoshida8 {
+    const ORDER: u32 = 8;
+    const DRIFT: &'static [f64] = {
+        use y8::*;
+        &[
+            W7 / 2.0,
+            (W7 + W6) / 2.0,
+            (W6 + W5) / 2.0,
+            (W5 + W4) / 2.0,
+            (W4 + W3) / 2.0,
+            (W3 + W2) / 2.0,
+            (W2 + W1) / 2.0,
+            (W1 + W0) / 2.0,
+            (W0 + W1) / 2.0,
+            (W1 + W2) / 2.0,
+            (W2 + W3) / 2.0,
+            (W3 + W4) / 2.0,
+            (W4 + W5) / 2.0,
+            (W5 + W6) / 2.0,
+            (W6 + W7) / 2.0,
+            W7 / 2.0,
+        ]
+    };
+    const KICK: &'static [f64] = {
+        use y8::*;
+        &[
+            W7, W6, W5, W4, W3, W2, W1, W0, W1, W2, W3, W4, W5, W6, W7, 0.0,
+        ]
+    };
+}
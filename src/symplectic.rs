@@ -0,0 +1,252 @@
+use crate::{Context, Param, Scalar, Solver, System, Var, Visitor, splitting::KickDrift};
+use core::marker::PhantomData;
+
+/// Coefficients of a splitting method for separable second-order systems.
+///
+/// A step of size `h` is composed of alternating drifts, which move positions
+/// with constant velocities, and kicks, which change velocities with constant
+/// accelerations:
+/// ```text
+/// x = x + c_i*h * v
+/// v = v + d_i*h * a(x),  i = 1..s
+/// ```
+/// Each drift and kick is an exact flow of the kinetic and potential part of a
+/// separable Hamiltonian, so their composition is symplectic.
+///
+/// See [`splitting`](crate::splitting) module for ready-made methods.
+pub trait Splitting {
+    /// Order of accuracy of the method.
+    const ORDER: u32;
+
+    /// Drift weights `c_i`, applied before the corresponding kicks.
+    ///
+    /// Coefficients are stored in double precision and converted to the
+    /// [`Scalar`] type of the system being integrated. Zero weights are skipped.
+    const DRIFT: &'static [f64];
+
+    /// Kick weights `d_i`, of the same length as [`DRIFT`](Self::DRIFT).
+    ///
+    /// Accelerations are only evaluated for non-zero weights, so a method
+    /// ending with a drift has a zero last weight.
+    const KICK: &'static [f64];
+}
+
+/// Splitting method for second-order systems defined by its coefficients.
+///
+/// The method generalizes [`Verlet`](crate::Verlet) to symplectic integrators of
+/// other orders, from the first-order [`SymplecticEuler`] to the eighth-order
+/// [`Yoshida8`](crate::splitting::Yoshida8). See [`Splitting`] for the algorithm.
+///
+/// # Position-Velocity Pairs
+///
+/// Like Verlet, the method needs positions and velocities to be visited together
+/// with [`Visitor::apply_pair`]. Drifts and kicks are separate passes over all
+/// variables, so in a drift pass only positions are moved, and in a kick pass only
+/// velocities are changed by the accelerations just computed. The derivative of the
+/// position variable is ignored.
+///
+/// Variables visited with plain [`Visitor::apply`] are stepped by their derivatives
+/// in each kick, which is only first-order accurate.
+///
+/// Accelerations are evaluated with velocities of the previous kick, so the method
+/// stays symplectic only if acceleration does not depend on velocity.
+///
+/// # Example
+/// ```
+/// use phy::{Solver, Symplectic, System, Var, Visitor, splitting::Yoshida6};
+/// use glam::DVec2;
+///
+/// struct Planet<S: Solver<f64>> {
+///     pos: Var<DVec2, S>,
+///     vel: Var<DVec2, S>,
+/// }
+///
+/// impl<S: Solver<f64>> System<S, f64> for Planet<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+///     }
+///
+///     fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+///         visitor.apply_pair(&mut self.pos, &mut self.vel);
+///     }
+/// }
+///
+/// let solver = Symplectic::<Yoshida6>::new();
+/// let mut planet = Planet {
+///     pos: Var::new(DVec2::X),
+///     vel: Var::new(DVec2::Y),
+/// };
+/// let mut t = 0.0;
+/// for _ in 0..100 {
+///     solver.solve_step(&mut planet, &mut t, 0.1);
+/// }
+/// // Circular orbit keeps its radius
+/// assert!((planet.pos.length() - 1.0).abs() < 1e-6);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Symplectic<T: Splitting>(PhantomData<T>);
+
+/// Symplectic Euler method, kicking velocities before drifting positions.
+///
+/// See [`KickDrift`] and [`DriftKick`](crate::splitting::DriftKick) for both orderings.
+pub type SymplecticEuler = Symplectic<KickDrift>;
+
+impl<T: Splitting> Symplectic<T> {
+    /// Create a solver of the given splitting method.
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Storage required by the symplectic solver for each variable.
+///
+/// Holds the value at the beginning of the step, to restore an interrupted step.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>"
+    ))
+)]
+pub struct SymplecticStorage<P: Param> {
+    /// The initial value at the beginning of the step (y_n)
+    init_value: P,
+}
+
+/// Kind of a pass over variables.
+#[derive(Clone, Copy)]
+enum Pass {
+    /// Move positions with their velocities.
+    Drift,
+    /// Change velocities with their accelerations.
+    Kick,
+}
+
+/// Visitor that applies a single drift or kick to variables.
+pub struct SymplecticStep<T: Splitting, R: Scalar = f32> {
+    pass: Pass,
+    /// Weight of the current pass.
+    weight: f64,
+    /// Sum of drift weights so far, offset of the current time in units of time step.
+    offset: f64,
+    /// Whether this is the first pass of the step.
+    first: bool,
+    /// Time at the beginning of the step (t_n)
+    t: R,
+    dt: R,
+    _splitting: PhantomData<T>,
+}
+
+impl<T: Splitting, R: Scalar> SymplecticStep<T, R> {
+    fn save<P: Param<Scalar = R>>(&self, var: &mut Var<P, Symplectic<T>>) {
+        if self.first {
+            var.storage.init_value.clone_from(&var.value);
+        }
+    }
+}
+
+impl<T: Splitting, R: Scalar> Context<Symplectic<T>, R> for SymplecticStep<T, R> {
+    /// Returns the time of positions: `t_n + sum(c_j, j <= i) * h`.
+    fn time(&self) -> R {
+        self.t + R::from_f64(self.offset) * self.dt
+    }
+
+    fn time_step(&self) -> R {
+        self.dt
+    }
+}
+
+impl<T: Splitting, R: Scalar> Visitor<Symplectic<T>, R> for SymplecticStep<T, R> {
+    /// Step a standalone variable by its derivative in kicks.
+    fn apply<P: Param<Scalar = R>>(&mut self, var: &mut Var<P, Symplectic<T>>) {
+        self.save(var);
+        if let Pass::Kick = self.pass {
+            var.value
+                .step(&var.deriv, R::from_f64(self.weight) * self.dt);
+            var.deriv = var.value.zero_deriv();
+        }
+    }
+
+    /// Drift the position or kick the velocity of a pair.
+    fn apply_pair<P: Param<Scalar = R>>(
+        &mut self,
+        pos: &mut Var<P, Symplectic<T>>,
+        vel: &mut Var<P::Deriv, Symplectic<T>>,
+    ) where
+        P::Deriv: Param<Scalar = R>,
+    {
+        self.save(pos);
+        self.save(vel);
+        let h = R::from_f64(self.weight) * self.dt;
+        match self.pass {
+            // Drift: x = x + v * c_i * dt
+            Pass::Drift => pos.value.step(&vel.value, h),
+            Pass::Kick => {
+                // Kick: v = v + a * d_i * dt
+                vel.value.step(&vel.deriv, h);
+
+                // Reset derivatives for next kick
+                pos.deriv = pos.value.zero_deriv();
+                vel.deriv = vel.value.zero_deriv();
+            }
+        }
+    }
+}
+
+/// Visitor that restores variables to their values at the beginning of the step.
+struct SymplecticRestore;
+
+impl<T: Splitting, R: Scalar> Visitor<Symplectic<T>, R> for SymplecticRestore {
+    fn apply<P: Param<Scalar = R>>(&mut self, var: &mut Var<P, Symplectic<T>>) {
+        var.value.clone_from(&var.storage.init_value);
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+impl<T: Splitting, R: Scalar> Solver<R> for Symplectic<T> {
+    type Context = SymplecticStep<T, R>;
+    type Storage<P: Param<Scalar = R>> = SymplecticStorage<P>;
+
+    /// Perform one step of the splitting method for the given system.
+    ///
+    /// Each drift is a pass over variables, and each kick is an evaluation
+    /// of derivatives followed by a pass. Passes with zero weights are skipped.
+    fn solve_step<S: System<Self, R>>(&self, system: &mut S, t: &mut R, dt: R) {
+        let mut step = SymplecticStep {
+            pass: Pass::Drift,
+            weight: 0.0,
+            offset: 0.0,
+            first: true,
+            t: *t,
+            dt,
+            _splitting: PhantomData,
+        };
+
+        for (&drift, &kick) in T::DRIFT.iter().zip(T::KICK) {
+            if drift != 0.0 {
+                step.pass = Pass::Drift;
+                step.weight = drift;
+                system.visit_vars(&mut step);
+                step.first = false;
+                step.offset += drift;
+            }
+            if kick != 0.0 {
+                step.pass = Pass::Kick;
+                step.weight = kick;
+                // Compute accelerations at current positions
+                system.compute_derivs(&step);
+                system.visit_vars(&mut step);
+                step.first = false;
+            }
+        }
+
+        *t += dt;
+    }
+
+    fn restore_step<S: System<Self, R>>(&self, system: &mut S) {
+        system.visit_vars(&mut SymplecticRestore);
+    }
+}
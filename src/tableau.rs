@@ -0,0 +1,336 @@
+//! Ready-made Butcher tableaus for [`ButcherRk`](crate::ButcherRk) solver.
+//!
+//! # Fixed-step methods
+//! - [`Midpoint`], [`Ralston`]: second order, two stages.
+//! - [`Kutta3`]: Kutta's third-order method.
+//! - [`ClassicRk4`], [`ThreeEighths`]: fourth order, four stages.
+//!
+//! # Methods with embedded error estimate
+//! - [`Heun`]: second order with embedded Euler method.
+//! - [`BogackiShampine`]: third order with embedded second-order solution.
+//! - [`Fehlberg`]: fourth order with embedded fifth-order solution (RKF45).
+//! - [`CashKarp`]: fifth order with embedded fourth-order solution.
+//! - [`DormandPrince`]: fifth order with embedded fourth-order solution.
+//!
+//! # Methods with dense output
+//! - [`ClassicRk4`]: third-order continuous extension.
+//! - [`DormandPrince`]: fourth-order continuous extension.
+
+use crate::{DenseTableau, EmbeddedTableau, Tableau};
+
+/// Explicit midpoint method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Midpoint;
+
+impl Tableau for Midpoint {
+    type Stages<D: Clone + Default> = [D; 2];
+    const ORDER: u32 = 2;
+    const A: &'static [&'static [f64]] = &[&[], &[1.0 / 2.0]];
+    const B: &'static [f64] = &[0.0, 1.0];
+    const C: &'static [f64] = &[0.0, 1.0 / 2.0];
+}
+
+/// Heun's method (explicit trapezoidal rule).
+///
+/// The embedded solution is the first-order Euler method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Heun;
+
+impl Tableau for Heun {
+    type Stages<D: Clone + Default> = [D; 2];
+    const ORDER: u32 = 2;
+    const A: &'static [&'static [f64]] = &[&[], &[1.0]];
+    const B: &'static [f64] = &[1.0 / 2.0, 1.0 / 2.0];
+    const C: &'static [f64] = &[0.0, 1.0];
+}
+
+impl EmbeddedTableau for Heun {
+    const EMBEDDED_ORDER: u32 = 1;
+    const E: &'static [f64] = &[1.0 / 2.0 - 1.0, 1.0 / 2.0];
+}
+
+/// Ralston's second-order method with minimal truncation error.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Ralston;
+
+impl Tableau for Ralston {
+    type Stages<D: Clone + Default> = [D; 2];
+    const ORDER: u32 = 2;
+    const A: &'static [&'static [f64]] = &[&[], &[2.0 / 3.0]];
+    const B: &'static [f64] = &[1.0 / 4.0, 3.0 / 4.0];
+    const C: &'static [f64] = &[0.0, 2.0 / 3.0];
+}
+
+/// Kutta's third-order method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Kutta3;
+
+impl Tableau for Kutta3 {
+    type Stages<D: Clone + Default> = [D; 3];
+    const ORDER: u32 = 3;
+    const A: &'static [&'static [f64]] = &[&[], &[1.0 / 2.0], &[-1.0, 2.0]];
+    const B: &'static [f64] = &[1.0 / 6.0, 2.0 / 3.0, 1.0 / 6.0];
+    const C: &'static [f64] = &[0.0, 1.0 / 2.0, 1.0];
+}
+
+/// The classical fourth-order Runge–Kutta method.
+///
+/// Same method as [`Rk4`](crate::Rk4), but retaining all stage derivatives.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ClassicRk4;
+
+impl Tableau for ClassicRk4 {
+    type Stages<D: Clone + Default> = [D; 4];
+    const ORDER: u32 = 4;
+    const A: &'static [&'static [f64]] = &[&[], &[1.0 / 2.0], &[0.0, 1.0 / 2.0], &[0.0, 0.0, 1.0]];
+    const B: &'static [f64] = &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0];
+    const C: &'static [f64] = &[0.0, 1.0 / 2.0, 1.0 / 2.0, 1.0];
+}
+
+impl DenseTableau for ClassicRk4 {
+    const DENSE_ORDER: u32 = 3;
+    const BI: &'static [&'static [f64]] = &[
+        &[1.0, -3.0 / 2.0, 2.0 / 3.0],
+        &[0.0, 1.0, -2.0 / 3.0],
+        &[0.0, 1.0, -2.0 / 3.0],
+        &[0.0, -1.0 / 2.0, 2.0 / 3.0],
+    ];
+}
+
+/// Kutta's 3/8-rule fourth-order method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ThreeEighths;
+
+impl Tableau for ThreeEighths {
+    type Stages<D: Clone + Default> = [D; 4];
+    const ORDER: u32 = 4;
+    const A: &'static [&'static [f64]] =
+        &[&[], &[1.0 / 3.0], &[-1.0 / 3.0, 1.0], &[1.0, -1.0, 1.0]];
+    const B: &'static [f64] = &[1.0 / 8.0, 3.0 / 8.0, 3.0 / 8.0, 1.0 / 8.0];
+    const C: &'static [f64] = &[0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0];
+}
+
+/// Bogacki–Shampine 3(2) method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct BogackiShampine;
+
+impl Tableau for BogackiShampine {
+    type Stages<D: Clone + Default> = [D; 4];
+    const ORDER: u32 = 3;
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 2.0],
+        &[0.0, 3.0 / 4.0],
+        &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0],
+    ];
+    const B: &'static [f64] = &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0, 0.0];
+    const C: &'static [f64] = &[0.0, 1.0 / 2.0, 3.0 / 4.0, 1.0];
+}
+
+impl EmbeddedTableau for BogackiShampine {
+    const EMBEDDED_ORDER: u32 = 2;
+    const E: &'static [f64] = &[
+        2.0 / 9.0 - 7.0 / 24.0,
+        1.0 / 3.0 - 1.0 / 4.0,
+        4.0 / 9.0 - 1.0 / 3.0,
+        -1.0 / 8.0,
+    ];
+}
+
+/// Runge–Kutta–Fehlberg 4(5) method.
+///
+/// The step is advanced with the fourth-order solution.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Fehlberg;
+
+impl Tableau for Fehlberg {
+    type Stages<D: Clone + Default> = [D; 6];
+    const ORDER: u32 = 4;
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 4.0],
+        &[3.0 / 32.0, 9.0 / 32.0],
+        &[1932.0 / 2197.0, -7200.0 / 2197.0, 7296.0 / 2197.0],
+        &[439.0 / 216.0, -8.0, 3680.0 / 513.0, -845.0 / 4104.0],
+        &[
+            -8.0 / 27.0,
+            2.0,
+            -3544.0 / 2565.0,
+            1859.0 / 4104.0,
+            -11.0 / 40.0,
+        ],
+    ];
+    const B: &'static [f64] = &[
+        25.0 / 216.0,
+        0.0,
+        1408.0 / 2565.0,
+        2197.0 / 4104.0,
+        -1.0 / 5.0,
+        0.0,
+    ];
+    const C: &'static [f64] = &[0.0, 1.0 / 4.0, 3.0 / 8.0, 12.0 / 13.0, 1.0, 1.0 / 2.0];
+}
+
+impl EmbeddedTableau for Fehlberg {
+    const EMBEDDED_ORDER: u32 = 5;
+    const E: &'static [f64] = &[
+        25.0 / 216.0 - 16.0 / 135.0,
+        0.0,
+        1408.0 / 2565.0 - 6656.0 / 12825.0,
+        2197.0 / 4104.0 - 28561.0 / 56430.0,
+        -1.0 / 5.0 + 9.0 / 50.0,
+        -2.0 / 55.0,
+    ];
+}
+
+/// Cash–Karp 5(4) method.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CashKarp;
+
+impl Tableau for CashKarp {
+    type Stages<D: Clone + Default> = [D; 6];
+    const ORDER: u32 = 5;
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[3.0 / 10.0, -9.0 / 10.0, 6.0 / 5.0],
+        &[-11.0 / 54.0, 5.0 / 2.0, -70.0 / 27.0, 35.0 / 27.0],
+        &[
+            1631.0 / 55296.0,
+            175.0 / 512.0,
+            575.0 / 13824.0,
+            44275.0 / 110592.0,
+            253.0 / 4096.0,
+        ],
+    ];
+    const B: &'static [f64] = &[
+        37.0 / 378.0,
+        0.0,
+        250.0 / 621.0,
+        125.0 / 594.0,
+        0.0,
+        512.0 / 1771.0,
+    ];
+    const C: &'static [f64] = &[0.0, 1.0 / 5.0, 3.0 / 10.0, 3.0 / 5.0, 1.0, 7.0 / 8.0];
+}
+
+impl EmbeddedTableau for CashKarp {
+    const EMBEDDED_ORDER: u32 = 4;
+    const E: &'static [f64] = &[
+        37.0 / 378.0 - 2825.0 / 27648.0,
+        0.0,
+        250.0 / 621.0 - 18575.0 / 48384.0,
+        125.0 / 594.0 - 13525.0 / 55296.0,
+        -277.0 / 14336.0,
+        512.0 / 1771.0 - 1.0 / 4.0,
+    ];
+}
+
+/// Dormand–Prince 5(4) method.
+///
+/// The last stage is evaluated at the new state (first same as last),
+/// so it is only needed to estimate the error.
+///
+/// Dense output uses the quartic interpolant of Shampine (1986),
+/// which also requires the last stage.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DormandPrince;
+
+impl Tableau for DormandPrince {
+    type Stages<D: Clone + Default> = [D; 7];
+    const ORDER: u32 = 5;
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+        &[
+            19372.0 / 6561.0,
+            -25360.0 / 2187.0,
+            64448.0 / 6561.0,
+            -212.0 / 729.0,
+        ],
+        &[
+            9017.0 / 3168.0,
+            -355.0 / 33.0,
+            46732.0 / 5247.0,
+            49.0 / 176.0,
+            -5103.0 / 18656.0,
+        ],
+        &[
+            35.0 / 384.0,
+            0.0,
+            500.0 / 1113.0,
+            125.0 / 192.0,
+            -2187.0 / 6784.0,
+            11.0 / 84.0,
+        ],
+    ];
+    const B: &'static [f64] = &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+        0.0,
+    ];
+    const C: &'static [f64] = &[0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+}
+
+impl EmbeddedTableau for DormandPrince {
+    const EMBEDDED_ORDER: u32 = 4;
+    const E: &'static [f64] = &[
+        71.0 / 57600.0,
+        0.0,
+        -71.0 / 16695.0,
+        71.0 / 1920.0,
+        -17253.0 / 339200.0,
+        22.0 / 525.0,
+        -1.0 / 40.0,
+    ];
+}
+
+impl DenseTableau for DormandPrince {
+    const DENSE_ORDER: u32 = 4;
+    const BI: &'static [&'static [f64]] = &[
+        &[
+            1.0,
+            -8048581381.0 / 2820520608.0,
+            8663915743.0 / 2820520608.0,
+            -12715105075.0 / 11282082432.0,
+        ],
+        &[],
+        &[
+            0.0,
+            131558114200.0 / 32700410799.0,
+            -68118460800.0 / 10900136933.0,
+            87487479700.0 / 32700410799.0,
+        ],
+        &[
+            0.0,
+            -1754552775.0 / 470086768.0,
+            14199869525.0 / 1410260304.0,
+            -10690763975.0 / 1880347072.0,
+        ],
+        &[
+            0.0,
+            127303824393.0 / 49829197408.0,
+            -318862633887.0 / 49829197408.0,
+            701980252875.0 / 199316789632.0,
+        ],
+        &[
+            0.0,
+            -282668133.0 / 205662961.0,
+            2019193451.0 / 616988883.0,
+            -1453857185.0 / 822651844.0,
+        ],
+        &[
+            0.0,
+            40617522.0 / 29380423.0,
+            -110615467.0 / 29380423.0,
+            69997945.0 / 29380423.0,
+        ],
+    ];
+}
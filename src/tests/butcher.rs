@@ -0,0 +1,298 @@
+//! Tests for the generic Butcher tableau solver and the ready-made tableaus.
+
+use crate::{
+    AdaptiveSolver, ButcherRk, EmbeddedTableau, Rk4, Solver, System, Tableau, Tolerance, Var,
+    Visitor, tableau::*,
+};
+
+/// A system with exponential growth: dx/dt = k*x
+struct ExponentialSystem<S: Solver> {
+    x: Var<f32, S>,
+    growth_rate: f32,
+}
+
+impl<S: Solver> ExponentialSystem<S> {
+    fn new(initial_value: f32, growth_rate: f32) -> Self {
+        Self {
+            x: Var::new(initial_value),
+            growth_rate,
+        }
+    }
+}
+
+impl<S: Solver> System<S> for ExponentialSystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = self.growth_rate * *self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Logistic growth: dx/dt = x * (1 - x)
+///
+/// Unlike linear systems, it distinguishes methods that share the same stability polynomial.
+struct LogisticSystem<S: Solver> {
+    x: Var<f32, S>,
+    growth_rate: f32,
+}
+
+impl<S: Solver> System<S> for LogisticSystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = self.growth_rate * *self.x * (1.0 - *self.x);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Global error of integrating logistic growth up to `t = 4` in `steps` steps.
+fn logistic_error<T: Tableau>(steps: usize) -> f32 {
+    let (initial_value, growth_rate, total_time) = (0.1, 2.0, 4.0);
+    let mut system = LogisticSystem::<ButcherRk<T>> {
+        x: Var::new(initial_value),
+        growth_rate,
+    };
+    let solver = ButcherRk::<T>::default();
+    let dt = total_time / steps as f32;
+
+    let mut t = 0.0;
+    for _ in 0..steps {
+        solver.solve_step(&mut system, &mut t, dt);
+    }
+
+    // Analytical solution: x(t) = 1 / (1 + (1/x0 - 1) * exp(-k*t))
+    let expected = 1.0 / (1.0 + (1.0 / initial_value - 1.0) * (-growth_rate * total_time).exp());
+    (*system.x - expected).abs()
+}
+
+/// Check that doubling the number of steps reduces the error at least as dt^ORDER.
+///
+/// Number of steps should be chosen so that errors are well above rounding errors.
+fn check_convergence<T: Tableau>(name: &str, steps: usize) {
+    let errors = [steps, 2 * steps].map(logistic_error::<T>);
+    let order = (errors[0] / errors[1]).log2();
+    assert!(
+        order > T::ORDER as f32 - 0.5,
+        "Convergence rate of {} is not of order {}: measured {}, errors={:?}",
+        name,
+        T::ORDER,
+        order,
+        errors
+    );
+}
+
+/// Test convergence order of all ready-made tableaus.
+#[test]
+fn test_tableau_convergence_order() {
+    check_convergence::<Midpoint>("Midpoint", 16);
+    check_convergence::<Heun>("Heun", 16);
+    check_convergence::<Ralston>("Ralston", 16);
+    check_convergence::<Kutta3>("Kutta3", 8);
+    check_convergence::<BogackiShampine>("BogackiShampine", 8);
+    check_convergence::<ClassicRk4>("ClassicRk4", 8);
+    check_convergence::<ThreeEighths>("ThreeEighths", 8);
+    check_convergence::<Fehlberg>("Fehlberg", 8);
+    check_convergence::<CashKarp>("CashKarp", 4);
+    check_convergence::<DormandPrince>("DormandPrince", 4);
+}
+
+/// Test that the generic solver with the classical tableau reproduces [`Rk4`].
+#[test]
+fn test_classic_rk4_matches_rk4() {
+    let mut generic = ExponentialSystem::<ButcherRk<ClassicRk4>>::new(1.0, -1.5);
+    let mut reference = ExponentialSystem::<Rk4>::new(1.0, -1.5);
+    let dt = 0.1;
+
+    let (mut t_generic, mut t_reference) = (0.0, 0.0);
+    for _ in 0..20 {
+        ButcherRk::<ClassicRk4>::default().solve_step(&mut generic, &mut t_generic, dt);
+        Rk4.solve_step(&mut reference, &mut t_reference, dt);
+    }
+
+    assert!(
+        (*generic.x - *reference.x).abs() < 1e-6,
+        "ClassicRk4 differs from Rk4: {} vs {}",
+        *generic.x,
+        *reference.x
+    );
+}
+
+/// Integrate exponential decay up to `t = 2` adaptively and return the error and number of steps.
+fn adaptive_decay<T: EmbeddedTableau>(tolerance: f32) -> (f32, usize) {
+    let mut system = ExponentialSystem::<ButcherRk<T>>::new(1.0, -1.0);
+    let solver = ButcherRk::<T>::new(Tolerance::new(tolerance, tolerance));
+    let total_time = 2.0f32;
+
+    let (mut t, mut dt, mut steps) = (0.0, 0.1f32, 0);
+    while t < total_time {
+        dt = dt.min(total_time - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        dt = step.next;
+        steps += 1;
+    }
+
+    ((*system.x - (-total_time).exp()).abs(), steps)
+}
+
+/// Check that adaptive integration keeps the global error close to the tolerance,
+/// and that tightening the tolerance costs more steps.
+fn check_adaptive<T: EmbeddedTableau>(name: &str) {
+    let (loose_error, loose_steps) = adaptive_decay::<T>(1e-3);
+    let (tight_error, tight_steps) = adaptive_decay::<T>(1e-5);
+
+    assert!(
+        loose_error < 1e-2 && tight_error < 1e-4,
+        "{} errors too large: {} and {}",
+        name,
+        loose_error,
+        tight_error
+    );
+    assert!(
+        tight_steps > loose_steps,
+        "{} did not take more steps with tighter tolerance: {} vs {}",
+        name,
+        tight_steps,
+        loose_steps
+    );
+}
+
+/// Test adaptive stepping with all tableaus that have an embedded solution.
+#[test]
+fn test_embedded_tableaus_adaptive() {
+    check_adaptive::<Heun>("Heun");
+    check_adaptive::<BogackiShampine>("BogackiShampine");
+    check_adaptive::<Fehlberg>("Fehlberg");
+    check_adaptive::<CashKarp>("CashKarp");
+    check_adaptive::<DormandPrince>("DormandPrince");
+}
+
+/// Test that stages only used for the error estimate are skipped in fixed steps.
+#[test]
+fn test_error_stages_skipped() {
+    /// Counts derivative evaluations.
+    struct Counter<S: Solver> {
+        x: Var<f32, S>,
+        evaluations: usize,
+    }
+
+    impl<S: Solver> System<S> for Counter<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = 1.0;
+            self.evaluations += 1;
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+        }
+    }
+
+    let mut system = Counter::<ButcherRk<DormandPrince>> {
+        x: Var::new(0.0),
+        evaluations: 0,
+    };
+    let solver = ButcherRk::<DormandPrince>::default();
+
+    let mut t = 0.0;
+    solver.solve_step(&mut system, &mut t, 0.5);
+    assert_eq!(system.evaluations, 6);
+    assert!((*system.x - 0.5).abs() < 1e-6);
+
+    solver.adaptive_step(&mut system, &mut t, 0.5);
+    assert_eq!(system.evaluations, 13);
+    assert!((*system.x - 1.0).abs() < 1e-6);
+}
+
+/// Test adaptive integration backward in time with negative steps.
+#[test]
+fn test_adaptive_backward() {
+    let mut system = ExponentialSystem::<ButcherRk<DormandPrince>>::new(1.0, -1.0);
+    let solver = ButcherRk::<DormandPrince>::new(Tolerance::new(1e-6, 1e-6));
+    let total_time = -2.0f32;
+
+    let (mut t, mut dt, mut steps) = (0.0, -0.5f32, 0);
+    while t > total_time {
+        dt = dt.max(total_time - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        assert!(
+            step.taken < 0.0 && step.next < 0.0,
+            "Sign of step flipped: {:?}",
+            step
+        );
+        dt = step.next;
+        steps += 1;
+    }
+
+    // Error is controlled, so a large initial step is shrunk
+    let expected = (-total_time).exp();
+    assert!(
+        (*system.x - expected).abs() < 1e-4 * expected,
+        "Backward integration error too large: {} vs {}",
+        *system.x,
+        expected
+    );
+    assert!(
+        steps > 4,
+        "Steps were accepted without error control: {}",
+        steps
+    );
+}
+
+/// Test that steps at the smallest size are flagged when the tolerance is not met.
+#[test]
+fn test_adaptive_forced_step() {
+    let mut system = ExponentialSystem::<ButcherRk<DormandPrince>>::new(1.0, 5.0);
+    let mut solver = ButcherRk::<DormandPrince>::new(Tolerance::new(1e-9, 1e-9));
+    solver.min_step_ratio = 0.5;
+
+    let mut t = 0.0;
+    let step = solver.adaptive_step(&mut system, &mut t, 1.0);
+    assert!(step.forced, "Step was not forced: {:?}", step);
+    assert!(step.taken <= 0.5 && step.taken > 0.0);
+    assert_eq!(t, step.taken);
+
+    // Steps within tolerance are not flagged
+    let solver = ButcherRk::<DormandPrince>::new(Tolerance::new(1e-3, 1e-3));
+    let step = solver.adaptive_step(&mut system, &mut t, 0.01);
+    assert!(!step.forced);
+}
+
+/// Test that steps with a non-finite error are never accepted.
+#[test]
+fn test_adaptive_non_finite() {
+    /// Motion towards a wall, whose speed is not defined beyond it.
+    struct Wall<S: Solver> {
+        x: Var<f32, S>,
+    }
+
+    impl<S: Solver> System<S> for Wall<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = (1.0 - *self.x).sqrt();
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+        }
+    }
+
+    let solver = ButcherRk::<DormandPrince>::default();
+    let mut system = Wall::<ButcherRk<DormandPrince>> { x: Var::new(0.0) };
+    let mut t = 0.0f32;
+    for _ in 0..100 {
+        let step = solver.adaptive_step(&mut system, &mut t, 1.0);
+        assert!(system.x.is_finite(), "Accepted a non-finite state");
+        if step.taken == 0.0 {
+            assert!(step.forced);
+            break;
+        }
+    }
+
+    // Stepping from beyond the wall is refused
+    *system.x = 2.0;
+    let t0 = t;
+    let step = solver.adaptive_step(&mut system, &mut t, 0.1);
+    assert_eq!((step.taken, step.forced), (0.0, true));
+    assert_eq!((t, *system.x), (t0, 2.0));
+}
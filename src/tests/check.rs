@@ -0,0 +1,203 @@
+//! Tests for checked stepping.
+
+use crate::{
+    CheckStage, CheckedPart, DenseSolver, Euler, FiniteCheck, NonFiniteError, Param, Rk4, Rk4Dense,
+    Solver, System, Var, Visitor,
+};
+use glam::Vec2;
+use std::{cell::Cell, rc::Rc};
+
+/// Motion towards a wall, whose speed is not defined beyond it.
+#[derive(Clone)]
+struct Wall<S: Solver> {
+    pos: Var<Vec2, S>,
+    x: Var<f32, S>,
+    /// Shared between clones, so that rollback does not reset it.
+    evaluations: Rc<Cell<usize>>,
+}
+
+impl<S: Solver> Wall<S> {
+    fn new(x: f32) -> Self {
+        Self {
+            pos: Var::new(Vec2::ZERO),
+            x: Var::new(x),
+            evaluations: Rc::default(),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Wall<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = Vec2::new(1.0, *self.x);
+        self.x.deriv = (1.0 - *self.x).sqrt();
+        self.evaluations.set(self.evaluations.get() + 1);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pos);
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Test that finite steps are the same as unchecked ones.
+#[test]
+fn test_checked_finite() {
+    let mut system = Wall::<Rk4>::new(0.0);
+    let mut reference = system.clone();
+    let mut check = FiniteCheck::new();
+    let (mut t, mut t_reference) = (0.0, 0.0);
+    for _ in 0..10 {
+        check.solve_step(&Rk4, &mut system, &mut t, 0.05).unwrap();
+        Rk4.solve_step(&mut reference, &mut t_reference, 0.05);
+    }
+    assert_eq!(t, t_reference);
+    assert_eq!((*system.pos, *system.x), (*reference.pos, *reference.x));
+}
+
+/// Test that an error at an intermediate stage restores the state before the step.
+#[test]
+fn test_checked_stage_error() {
+    let mut system = Wall::<Rk4Dense>::new(0.0);
+    let mut check = FiniteCheck::new();
+    let mut t = 0.0;
+    for _ in 0..4 {
+        check
+            .solve_step(&Rk4Dense, &mut system, &mut t, 0.2)
+            .unwrap();
+    }
+    let before = system.clone();
+    system.evaluations.set(0);
+
+    // The second stage moves beyond the wall
+    let error = check
+        .solve_step(&Rk4Dense, &mut system, &mut t, 2.0)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        NonFiniteError {
+            var: 1,
+            stage: CheckStage::Derivs(1),
+            part: CheckedPart::Deriv,
+        }
+    );
+    // The remaining stages are skipped
+    assert_eq!(system.evaluations.get(), 2);
+    assert_eq!(t, 0.8);
+    assert_eq!((*system.pos, *system.x), (*before.pos, *before.x));
+    assert_eq!(system.x.deriv, before.x.deriv);
+
+    // Storage still describes the last successful step
+    let mut expected = before.clone();
+    Rk4Dense.interpolate(&mut expected, 0.2, 0.5);
+    let mut restored = system.clone();
+    Rk4Dense.interpolate(&mut restored, 0.2, 0.5);
+    assert_eq!(*restored.x, *expected.x);
+
+    // Retrying with a smaller step
+    check
+        .solve_step(&Rk4Dense, &mut system, &mut t, 0.01)
+        .unwrap();
+    assert!(*system.x > *before.x);
+}
+
+/// Test that overflow in the update is detected.
+#[test]
+fn test_checked_update_error() {
+    let mut system = Wall::<Euler>::new(-3e38);
+    let mut check = FiniteCheck::new();
+    let mut t = 0.0;
+    let error = check
+        .solve_step(&Euler, &mut system, &mut t, 1e20)
+        .unwrap_err();
+    assert_eq!(
+        (error.var, error.stage, error.part),
+        (0, CheckStage::Update, CheckedPart::Value)
+    );
+    assert_eq!((t, *system.pos, *system.x), (0.0, Vec2::ZERO, -3e38));
+}
+
+/// Test that non-finite initial values are reported.
+#[test]
+fn test_checked_initial_value() {
+    let mut system = Wall::<Euler>::new(0.0);
+    *system.pos = Vec2::new(0.0, f32::INFINITY);
+    let error = FiniteCheck::new()
+        .solve_step(&Euler, &mut system, &mut 0.0, 0.1)
+        .unwrap_err();
+    assert_eq!(
+        (error.var, error.stage, error.part),
+        (0, CheckStage::Derivs(0), CheckedPart::Value)
+    );
+    assert_eq!(
+        error.to_string(),
+        "Non-finite value of variable 0 at evaluation 0 of derivatives"
+    );
+}
+
+/// Custom parameter with a component which is not stepped.
+#[derive(Clone, Default)]
+struct Gauge {
+    reading: f32,
+    scale: f32,
+}
+
+impl Param for Gauge {
+    type Scalar = f32;
+    type Deriv = f32;
+    fn step(&mut self, deriv: &f32, dt: f32) {
+        self.reading += deriv * dt;
+    }
+    fn magnitude(&self) -> f32 {
+        self.reading.abs()
+    }
+    fn flat_dim(&self) -> usize {
+        2
+    }
+    fn write_flat(&self, out: &mut [f32]) {
+        out.copy_from_slice(&[self.reading, self.scale]);
+    }
+    fn read_flat(&mut self, data: &[f32]) {
+        self.reading = data[0];
+        self.scale = data[1];
+    }
+}
+
+/// System with a custom parameter.
+#[derive(Clone)]
+struct Gauged<S: Solver> {
+    x: Var<f32, S>,
+    gauge: Var<Gauge, S>,
+}
+
+impl<S: Solver> System<S> for Gauged<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = 1.0;
+        self.gauge.deriv = 1.0;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+        visitor.apply(&mut self.gauge);
+    }
+}
+
+/// Test that all components of custom parameters are checked.
+#[test]
+fn test_checked_custom_param() {
+    let mut system = Gauged::<Euler> {
+        x: Var::new(0.0),
+        gauge: Var::new(Gauge {
+            reading: 0.0,
+            scale: f32::NAN,
+        }),
+    };
+    let mut t = 0.0;
+    let error = FiniteCheck::new()
+        .solve_step(&Euler, &mut system, &mut t, 0.1)
+        .unwrap_err();
+    assert_eq!(
+        (error.var, error.stage, error.part),
+        (1, CheckStage::Derivs(0), CheckedPart::Value)
+    );
+    assert_eq!((t, *system.x, system.gauge.reading), (0.0, 0.0, 0.0));
+}
@@ -0,0 +1,220 @@
+//! Tests for dense output of Runge–Kutta solvers.
+
+use crate::{
+    AdaptiveSolver, ButcherRk, DenseSolver, DenseTableau, Dopri5, Dopri5Dense, Rk4Dense, Rot2,
+    Rot3, Solver, System, Tolerance, Var, Visitor, tableau::*,
+};
+use approx::assert_abs_diff_eq;
+use glam::Vec3;
+
+/// A system with exponential growth: dx/dt = k*x
+#[derive(Clone)]
+struct ExponentialSystem<S: Solver> {
+    x: Var<f32, S>,
+    growth_rate: f32,
+}
+
+impl<S: Solver> ExponentialSystem<S> {
+    fn new(initial_value: f32, growth_rate: f32) -> Self {
+        Self {
+            x: Var::new(initial_value),
+            growth_rate,
+        }
+    }
+}
+
+impl<S: Solver> System<S> for ExponentialSystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = self.growth_rate * *self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Body spinning with constant angular velocity in 2D and 3D.
+#[derive(Clone)]
+struct Spinner<S: Solver> {
+    rot2: Var<Rot2, S>,
+    rot3: Var<Rot3, S>,
+    omega: Vec3,
+}
+
+impl<S: Solver> System<S> for Spinner<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.rot2.deriv = self.omega.z;
+        self.rot3.deriv = self.omega;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.rot2);
+        visitor.apply(&mut self.rot3);
+    }
+}
+
+/// Interpolation weights must reproduce the step weights at the end of the step.
+fn check_end_weights<T: DenseTableau>() {
+    for (i, coeffs) in T::BI.iter().enumerate() {
+        let b: f64 = coeffs.iter().sum();
+        let expected = T::B.get(i).copied().unwrap_or(0.0);
+        assert_abs_diff_eq!(b, expected, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn test_dense_tableau_end_weights() {
+    check_end_weights::<ClassicRk4>();
+    check_end_weights::<DormandPrince>();
+}
+
+/// Test that interpolation hits the ends of the step and leaves the step repeatable.
+#[test]
+fn test_rk4_dense_endpoints() {
+    let dt = 0.3;
+    let mut system = ExponentialSystem::<Rk4Dense>::new(1.0, 2.0);
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut system, &mut t, dt);
+    let end = *system.x;
+
+    let mut start = system.clone();
+    Rk4Dense.interpolate(&mut start, dt, 0.0);
+    assert_eq!(*start.x, 1.0);
+
+    // Interpolating the same system again starts from the stored step
+    Rk4Dense.interpolate(&mut start, dt, 1.0);
+    assert_abs_diff_eq!(*start.x, end, epsilon = 1e-6);
+}
+
+/// Test that interpolated values are close to the exact solution within the step.
+#[test]
+fn test_rk4_dense_accuracy() {
+    let dt = 0.1;
+    let mut system = ExponentialSystem::<Rk4Dense>::new(1.0, 2.0);
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut system, &mut t, dt);
+
+    for theta in [0.1, 0.25, 0.5, 0.75, 0.9] {
+        let mut state = system.clone();
+        Rk4Dense.interpolate(&mut state, dt, theta);
+        let expected = (2.0 * theta * dt).exp();
+        assert_abs_diff_eq!(*state.x, expected, epsilon = 1e-4);
+        assert_eq!(state.x.deriv, 0.0);
+    }
+}
+
+/// Test that the interpolation error decreases with step size as dt^4 (third order).
+#[test]
+fn test_rk4_dense_convergence() {
+    let error = |dt: f32| {
+        let mut system = ExponentialSystem::<Rk4Dense>::new(1.0, 2.0);
+        let mut t = 0.0;
+        Rk4Dense.solve_step(&mut system, &mut t, dt);
+        Rk4Dense.interpolate(&mut system, dt, 0.5);
+        (*system.x - (dt as f64).exp() as f32).abs()
+    };
+
+    let ratio = error(0.4) / error(0.2);
+    assert!(
+        ratio > 10.0,
+        "Interpolant is not third-order: ratio={}",
+        ratio
+    );
+}
+
+/// Test that `ButcherRk<ClassicRk4>` interpolates exactly like `Rk4Dense`.
+#[test]
+fn test_classic_rk4_dense_matches_rk4() {
+    let dt = 0.2;
+    let solver = ButcherRk::<ClassicRk4>::default().with_dense_output();
+    let mut system = ExponentialSystem::<ButcherRk<ClassicRk4, true>>::new(1.0, -1.5);
+    let mut reference = ExponentialSystem::<Rk4Dense>::new(1.0, -1.5);
+    let (mut t, mut t_ref) = (0.0, 0.0);
+    solver.solve_step(&mut system, &mut t, dt);
+    Rk4Dense.solve_step(&mut reference, &mut t_ref, dt);
+
+    for theta in [0.2, 0.5, 0.8] {
+        let mut state = system.clone();
+        let mut state_ref = reference.clone();
+        solver.interpolate(&mut state, dt, theta);
+        Rk4Dense.interpolate(&mut state_ref, dt, theta);
+        assert_abs_diff_eq!(*state.x, *state_ref.x, epsilon = 1e-6);
+    }
+}
+
+/// Test fourth-order dense output of adaptive Dormand–Prince steps.
+#[test]
+fn test_dopri5_dense_adaptive() {
+    let solver = Dopri5::new(Tolerance::new(1e-7, 1e-7)).with_dense_output();
+    let mut system = ExponentialSystem::<Dopri5Dense>::new(1.0, -1.0);
+
+    let mut t = 0.0;
+    let mut dt = 0.5f32;
+    while t < 2.0 {
+        let t0 = t;
+        dt = dt.min(2.0 - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        dt = step.next;
+
+        // Sample the trajectory at "frames" within the step
+        for theta in [0.25, 0.5, 0.75] {
+            let mut state = system.clone();
+            solver.interpolate(&mut state, step.taken, theta);
+            let expected = (-(t0 + theta * step.taken)).exp();
+            assert_abs_diff_eq!(*state.x, expected, epsilon = 1e-5);
+        }
+    }
+}
+
+/// Test that fixed-step Dormand–Prince computes the last stage needed for dense output.
+#[test]
+fn test_dopri5_dense_fixed_step() {
+    let dt = 0.2;
+    let solver = Dopri5Dense::default();
+    let mut system = ExponentialSystem::<Dopri5Dense>::new(1.0, 2.0);
+    let mut t = 0.0;
+    // Fill the last stage with garbage from a previous step with another growth rate
+    system.growth_rate = -10.0;
+    solver.solve_step(&mut system, &mut t, dt);
+    *system.x = 1.0;
+    system.growth_rate = 2.0;
+    t = 0.0;
+    solver.solve_step(&mut system, &mut t, dt);
+
+    solver.interpolate(&mut system, dt, 0.5);
+    assert_abs_diff_eq!(*system.x, (2.0 * 0.5 * dt).exp(), epsilon = 1e-5);
+}
+
+/// Test that rotations are interpolated on their manifold.
+#[test]
+fn test_dense_rotations() {
+    let dt = 0.5;
+    let omega = Vec3::new(0.3, -0.4, 1.2);
+    let mut system = Spinner::<Rk4Dense> {
+        rot2: Var::new(Rot2::from_angle(6.0)),
+        rot3: Var::default(),
+        omega,
+    };
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut system, &mut t, dt);
+
+    for theta in [0.1, 0.5, 0.9] {
+        let mut state = system.clone();
+        Rk4Dense.interpolate(&mut state, dt, theta);
+
+        // Uniform rotation is interpolated exactly and the angle is wrapped
+        let angle = Rot2::from_angle(6.0 + omega.z * theta * dt).angle();
+        assert_abs_diff_eq!(state.rot2.angle(), angle, epsilon = 1e-5);
+
+        let expected = Rot3::from_scaled_axis(omega * theta * dt);
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert!(
+            state
+                .rot3
+                .transform(v)
+                .abs_diff_eq(expected.transform(v), 1e-5)
+        );
+        let q: glam::Quat = (*state.rot3).into();
+        assert_abs_diff_eq!(q.length(), 1.0, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,175 @@
+//! Tests for the Dormand–Prince solver, including step size control.
+
+use crate::{AdaptiveSolver, Dopri5, Rk4, Solver, System, Tolerance, Var, Visitor};
+
+/// A system with exponential growth: dx/dt = k*x
+struct ExponentialSystem<S: Solver> {
+    x: Var<f32, S>,
+    growth_rate: f32,
+}
+
+impl<S: Solver> ExponentialSystem<S> {
+    fn new(initial_value: f32, growth_rate: f32) -> Self {
+        Self {
+            x: Var::new(initial_value),
+            growth_rate,
+        }
+    }
+}
+
+impl<S: Solver> System<S> for ExponentialSystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = self.growth_rate * *self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Ball falling onto a stiff spring-damper ground, as in the bouncing ball example.
+struct StiffBall<S: Solver> {
+    pos: Var<f32, S>,
+    vel: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for StiffBall<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -9.8 + (1000.0 - 100.0 * *self.vel) * (-self.pos.min(0.0));
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pos);
+        visitor.apply(&mut self.vel);
+    }
+}
+
+/// Test fixed-step Dormand–Prince with constant derivative.
+#[test]
+fn test_dopri5_constant_derivative() {
+    let mut system = ExponentialSystem::<Dopri5>::new(1.0, 0.0);
+    let solver = Dopri5::default();
+
+    let mut t = 0.0;
+    solver.solve_step(&mut system, &mut t, 0.5);
+    assert!((*system.x - 1.0).abs() < 1e-6);
+
+    // Derivative is reset after the step
+    assert!(system.x.deriv.abs() < 1e-6);
+}
+
+/// Test that the fixed-step method is more accurate than RK4.
+#[test]
+fn test_dopri5_vs_rk4_accuracy() {
+    let total_time = 1.0;
+    let dt = 0.2;
+    let steps = (total_time / dt) as usize;
+    let expected = (2.0f32 * total_time).exp();
+
+    let mut system_dopri = ExponentialSystem::<Dopri5>::new(1.0, 2.0);
+    let solver_dopri = Dopri5::default();
+    let mut t = 0.0;
+    for _ in 0..steps {
+        solver_dopri.solve_step(&mut system_dopri, &mut t, dt);
+    }
+
+    let mut system_rk4 = ExponentialSystem::<Rk4>::new(1.0, 2.0);
+    let mut t = 0.0;
+    for _ in 0..steps {
+        Rk4.solve_step(&mut system_rk4, &mut t, dt);
+    }
+
+    let error_dopri = (*system_dopri.x - expected).abs();
+    let error_rk4 = (*system_rk4.x - expected).abs();
+    assert!(
+        error_dopri < error_rk4 / 4.0,
+        "Dopri5 error={}, RK4 error={}",
+        error_dopri,
+        error_rk4
+    );
+}
+
+/// Test that adaptive stepping keeps the error within tolerance.
+#[test]
+fn test_dopri5_adaptive_accuracy() {
+    let solver = Dopri5::new(Tolerance::new(1e-5, 1e-5));
+    let mut system = ExponentialSystem::<Dopri5>::new(1.0, -1.0);
+
+    let total_time = 2.0;
+    let (mut t, mut dt) = (0.0, 1.0f32);
+    while t < total_time {
+        dt = dt.min(total_time - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        assert!(step.taken > 0.0 && step.next > 0.0);
+        dt = step.next;
+    }
+
+    let expected = (-total_time).exp();
+    let error = (*system.x - expected).abs();
+    assert!(error < 1e-4, "Error too large: {}", error);
+}
+
+/// Test that a too large step is rejected and shrunk.
+#[test]
+fn test_dopri5_step_rejection() {
+    let solver = Dopri5::new(Tolerance::new(1e-6, 1e-6));
+    let mut system = ExponentialSystem::<Dopri5>::new(1.0, 5.0);
+
+    let mut t = 0.0;
+    let step = solver.adaptive_step(&mut system, &mut t, 1.0);
+    assert!(step.taken < 1.0, "Step was not rejected: {:?}", step);
+    assert!(step.next <= step.taken);
+
+    // State corresponds to the step that was actually taken
+    let expected = (5.0 * step.taken).exp();
+    assert!((*system.x - expected).abs() / expected < 1e-5);
+}
+
+/// Test that the step grows on smooth problems.
+#[test]
+fn test_dopri5_step_growth() {
+    let solver = Dopri5::new(Tolerance::new(1e-3, 1e-3));
+    let mut system = ExponentialSystem::<Dopri5>::new(1.0, -0.1);
+
+    let mut t = 0.0;
+    let step = solver.adaptive_step(&mut system, &mut t, 0.01);
+    assert_eq!(step.taken, 0.01);
+    assert!(step.next > step.taken);
+}
+
+/// Test that small steps are only used during stiff ground contact.
+#[test]
+fn test_dopri5_contact_efficiency() {
+    let solver = Dopri5::new(Tolerance::new(1e-4, 1e-4));
+    let mut system = StiffBall::<Dopri5> {
+        pos: Var::new(10.0),
+        vel: Var::new(0.0),
+    };
+
+    let total_time = 4.0;
+    let (mut t, mut dt) = (0.0, 0.01f32);
+    let mut steps = 0;
+    let mut min_step = f32::INFINITY;
+    let mut max_step = 0.0f32;
+    while t < total_time {
+        dt = dt.min(total_time - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        dt = step.next;
+        steps += 1;
+        min_step = min_step.min(step.taken);
+        max_step = max_step.max(step.taken);
+    }
+
+    // Fixed-step RK4 in the example uses 400 steps of 0.01 for this time span
+    assert!(steps < 400, "Too many steps: {}", steps);
+    assert!(min_step < 0.01, "Contact was not resolved: {}", min_step);
+    assert!(
+        max_step > 0.1,
+        "Free fall steps are too small: {}",
+        max_step
+    );
+
+    // Ball stays near the ground and loses energy
+    assert!(*system.pos > -1.0 && *system.pos < 10.0);
+}
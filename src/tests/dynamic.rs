@@ -0,0 +1,291 @@
+//! Tests for dynamically sized state.
+
+use crate::{
+    ButcherRk, DenseSolver, Deriv, Dopri5, Euler, ImplicitEuler, Param, Rk4, Rk4Dense, Solver,
+    System, Var, Vars, Verlet, Visitor, tableau::Kutta3,
+};
+use glam::Vec2;
+
+/// Acceleration of a particle: gravity and drag.
+fn accel(vel: Vec2) -> Vec2 {
+    -Vec2::Y - 0.5 * vel
+}
+
+/// Particles stored in a single pair of variables.
+struct Cloud<S: Solver> {
+    pos: Var<Vec<Vec2>, S>,
+    vel: Var<Vec<Vec2>, S>,
+}
+
+impl<S: Solver> Cloud<S> {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(Vec::new()),
+            vel: Var::new(Vec::new()),
+        }
+    }
+
+    fn spawn(&mut self, pos: Vec2, vel: Vec2) {
+        self.pos.push(pos);
+        self.vel.push(vel);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.pos.remove(index);
+        self.vel.remove(index);
+    }
+}
+
+impl<S: Solver> System<S> for Cloud<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv.clone_from(&self.vel);
+        self.vel.deriv = self.vel.iter().copied().map(accel).collect();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+/// Particle with its own variables.
+struct Particle<S: Solver> {
+    pos: Var<Vec2, S>,
+    vel: Var<Vec2, S>,
+}
+
+impl<S: Solver> Particle<S> {
+    fn new(pos: Vec2, vel: Vec2) -> Self {
+        Self {
+            pos: Var::new(pos),
+            vel: Var::new(vel),
+        }
+    }
+}
+
+impl<S: Solver> Vars<S> for Particle<S> {
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+impl<S: Solver> System<S> for Particle<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = accel(*self.vel);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        Vars::visit_vars(self, visitor);
+    }
+}
+
+/// Particles stored in a vector of variables.
+struct Particles<S: Solver> {
+    particles: Vec<Particle<S>>,
+}
+
+impl<S: Solver> System<S> for Particles<S> {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        for particle in &mut self.particles {
+            particle.compute_derivs(ctx);
+        }
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        self.particles.visit_vars(visitor);
+    }
+}
+
+/// Test `Param` and `Deriv` operations of vectors.
+#[test]
+fn test_vec_param() {
+    let mut v = vec![1.0f32, 2.0, 3.0];
+    v.step(&vec![2.0, 2.0], 0.5);
+    assert_eq!(v, [2.0, 3.0, 3.0]);
+    assert_eq!(v.zero_deriv(), [0.0; 3]);
+    assert!((v.magnitude() - 22.0f32.sqrt()).abs() < 1e-6);
+
+    // Default derivative is zero of any length
+    let mut d = Vec::<Vec2>::default();
+    d.add_scaled(&vec![Vec2::X, Vec2::Y], 2.0);
+    assert_eq!(d, [Vec2::new(2.0, 0.0), Vec2::new(0.0, 2.0)]);
+    d.add_scaled(&vec![Vec2::ONE], 1.0);
+    d.scale(0.5);
+    assert_eq!(d, [Vec2::new(1.5, 0.5), Vec2::new(0.0, 1.0)]);
+    assert_eq!(d.dim(), 4);
+    assert_eq!(d.component(3), 1.0);
+    *d.component_mut(2) = 4.0;
+    assert_eq!(d[1], Vec2::new(4.0, 1.0));
+    assert!((d.norm() - (2.5f32 + 17.0).sqrt()).abs() < 1e-6);
+
+    let nested = vec![vec![1.0f32], vec![1.0, 2.0]];
+    assert_eq!(nested.zero_deriv(), [vec![0.0], vec![0.0, 0.0]]);
+}
+
+/// Test that particles spawned and removed between steps are integrated correctly.
+#[test]
+fn test_vec_var_resize() {
+    fn check<S: Solver>(solver: S) {
+        let dt = 0.05;
+        let mut cloud = Cloud::<S>::new();
+        // Reference particles integrated separately
+        let mut reference = Vec::new();
+        let mut t = 0.0;
+        for step in 0..40 {
+            if step % 5 == 0 {
+                let (pos, vel) = (Vec2::new(step as f32, 0.0), Vec2::new(1.0, 2.0));
+                cloud.spawn(pos, vel);
+                reference.push(Particle::<S>::new(pos, vel));
+            }
+            if step % 15 == 14 {
+                cloud.remove(1);
+                reference.remove(1);
+            }
+            solver.solve_step(&mut cloud, &mut t, dt);
+            for particle in &mut reference {
+                let mut t = 0.0;
+                solver.solve_step(particle, &mut t, dt);
+            }
+        }
+
+        assert_eq!(cloud.pos.len(), reference.len());
+        for (i, particle) in reference.iter().enumerate() {
+            assert!(cloud.pos[i].abs_diff_eq(*particle.pos, 1e-5));
+            assert!(cloud.vel[i].abs_diff_eq(*particle.vel, 1e-5));
+        }
+    }
+
+    check(Euler);
+    check(Rk4);
+    check(Rk4Dense);
+    check(Verlet);
+    check(Dopri5::default());
+    check(ButcherRk::<Kutta3>::default());
+    check(ImplicitEuler::default());
+}
+
+/// Test visiting a vector of variables which changes its length between steps.
+#[test]
+fn test_slice_of_vars() {
+    let dt = 0.05;
+    let mut system = Particles::<Rk4> {
+        particles: Vec::new(),
+    };
+    let mut t = 0.0;
+    for step in 0..20 {
+        if step % 4 == 0 {
+            system
+                .particles
+                .push(Particle::new(Vec2::ZERO, Vec2::new(1.0, 0.0)));
+        }
+        if step == 10 {
+            system.particles.remove(0);
+        }
+        Rk4.solve_step(&mut system, &mut t, dt);
+    }
+
+    // Particles spawned at steps 4, 8, 12 and 16 have lived for 16, 12, 8 and 4 steps
+    assert_eq!(system.particles.len(), 4);
+    for (particle, steps) in system.particles.iter().zip([16, 12, 8, 4]) {
+        let mut single = Particle::<Rk4>::new(Vec2::ZERO, Vec2::new(1.0, 0.0));
+        let mut t = 0.0;
+        for _ in 0..steps {
+            Rk4.solve_step(&mut single, &mut t, dt);
+        }
+        assert_eq!(*particle.pos, *single.pos);
+        assert_eq!(*particle.vel, *single.vel);
+    }
+}
+
+/// Test that restoring a step also restores the size of variables.
+#[test]
+fn test_vec_var_restore() {
+    fn check<S: Solver>(solver: S) {
+        let dt = 0.1;
+        let mut cloud = Cloud::<S>::new();
+        cloud.spawn(Vec2::ZERO, Vec2::X);
+        let mut t = 0.0;
+        solver.solve_step(&mut cloud, &mut t, dt);
+        cloud.spawn(Vec2::Y, Vec2::Y);
+
+        solver.restore_step(&mut cloud);
+        assert_eq!(*cloud.pos, [Vec2::ZERO]);
+        assert_eq!(*cloud.vel, [Vec2::X]);
+        assert_eq!(cloud.pos.deriv.len(), 1);
+
+        // Size changes between steps
+        cloud.spawn(Vec2::Y, Vec2::Y);
+        solver.solve_step(&mut cloud, &mut t, dt);
+        let mut reference = Particle::<S>::new(Vec2::Y, Vec2::Y);
+        solver.solve_step(&mut reference, &mut 0.0, dt);
+        assert_eq!(cloud.pos.len(), 2);
+        assert_eq!(cloud.pos[1], *reference.pos);
+    }
+
+    check(Rk4);
+    check(Rk4Dense);
+}
+
+/// Test dense output of dynamically sized variables.
+#[test]
+fn test_vec_var_dense() {
+    let dt = 0.1;
+    let mut cloud = Cloud::<Rk4Dense>::new();
+    cloud.spawn(Vec2::ZERO, Vec2::X);
+    cloud.spawn(Vec2::Y, Vec2::Y);
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut cloud, &mut t, dt);
+
+    let mut start = Cloud::<Rk4Dense> {
+        pos: cloud.pos.clone(),
+        vel: cloud.vel.clone(),
+    };
+    Rk4Dense.interpolate(&mut start, dt, 0.0);
+    assert_eq!(*start.pos, [Vec2::ZERO, Vec2::Y]);
+    assert_eq!(start.pos.deriv.len(), 2);
+}
+
+/// Test that dense output is refused after the size of a variable has changed.
+#[test]
+#[should_panic(expected = "Variable size has changed since the last step")]
+fn test_vec_var_dense_resized() {
+    let mut cloud = Cloud::<Rk4Dense>::new();
+    cloud.spawn(Vec2::ZERO, Vec2::X);
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut cloud, &mut t, 0.1);
+    cloud.spawn(Vec2::Y, Vec2::Y);
+    Rk4Dense.interpolate(&mut cloud, 0.1, 0.5);
+}
+
+/// Test that variables added after the last step keep their values when interpolated or restored.
+#[test]
+fn test_var_added_after_step() {
+    let mut system = Particles::<Rk4Dense> {
+        particles: vec![Particle::new(Vec2::ZERO, Vec2::X)],
+    };
+    let mut t = 0.0;
+    Rk4Dense.solve_step(&mut system, &mut t, 0.1);
+    let pos = *system.particles[0].pos;
+    system
+        .particles
+        .push(Particle::new(Vec2::new(2.0, 3.0), Vec2::Y));
+
+    Rk4Dense.interpolate(&mut system, 0.1, 0.5);
+    assert!(system.particles[0].pos.x > 0.0 && system.particles[0].pos.x < pos.x);
+    assert_eq!(*system.particles[1].pos, Vec2::new(2.0, 3.0));
+    assert_eq!(*system.particles[1].vel, Vec2::Y);
+
+    Rk4Dense.restore_step(&mut system);
+    assert_eq!(*system.particles[0].pos, Vec2::ZERO);
+    assert_eq!(*system.particles[1].pos, Vec2::new(2.0, 3.0));
+
+    // The same for plain RK4
+    let mut system = Particles::<Rk4> {
+        particles: vec![Particle::new(Vec2::ZERO, Vec2::X)],
+    };
+    Rk4.solve_step(&mut system, &mut t, 0.1);
+    system.particles.push(Particle::new(Vec2::ONE, Vec2::Y));
+    Rk4.restore_step(&mut system);
+    assert_eq!(*system.particles[0].pos, Vec2::ZERO);
+    assert_eq!(*system.particles[1].pos, Vec2::ONE);
+}
@@ -35,9 +35,10 @@ fn test_euler_constant_derivative() {
     let dt = 0.1;
     let steps = 10;
 
+    let mut t = 0.0;
     // Integrate for 10 steps
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // After 10 steps of dt=0.1 with dx/dt=1.0
@@ -79,9 +80,10 @@ fn test_euler_exponential_growth() {
     let dt = 0.01;
     let steps = 100;
 
+    let mut t = 0.0;
     // Integrate for 100 steps of dt=0.01 (total time = 1.0)
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // Analytical solution: x(t) = x0 * exp(k*t)
@@ -136,9 +138,10 @@ fn test_euler_multiple_variables() {
     let dt = 0.1;
     let steps = 10;
 
+    let mut t = 0.0;
     // Integrate for 10 steps
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // After 10 steps of dt=0.1 with constant velocity (1.0, 2.0)
@@ -156,14 +159,15 @@ fn test_euler_derivative_reset() {
     let mut system = SimpleSystem::new(0.0);
     let solver = Euler;
 
+    let mut t = 0.0;
     // First step
-    solver.solve_step(&mut system, 0.1);
+    solver.solve_step(&mut system, &mut t, 0.1);
 
     // After step, derivative should be reset to default (0.0)
     assert!(system.x.deriv.abs() < 1e-6);
 
     // Second step - compute_derivs will set it to 1.0 again
-    solver.solve_step(&mut system, 0.1);
+    solver.solve_step(&mut system, &mut t, 0.1);
 
     // After second step, derivative should be reset again
     assert!(system.x.deriv.abs() < 1e-6);
@@ -175,7 +179,8 @@ fn test_euler_zero_dt() {
     let mut system = SimpleSystem::new(5.0);
     let solver = Euler;
 
+    let mut t = 0.0;
     // With dt = 0, value should not change
-    solver.solve_step(&mut system, 0.0);
+    solver.solve_step(&mut system, &mut t, 0.0);
     assert!((*system.x - 5.0).abs() < 1e-6);
 }
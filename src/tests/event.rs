@@ -0,0 +1,375 @@
+//! Tests for zero-crossing event detection.
+
+use crate::{
+    AdaptiveSolver, Direction, Dopri5, Dopri5Dense, Event, EventLocator, EventSystem, Rk4,
+    Rk4Dense, Solver, System, Var, Visitor,
+};
+use core::f32::consts::PI;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Ball falling under gravity and bouncing off the ground with restitution.
+#[derive(Clone)]
+struct Ball<S: Solver> {
+    pos: Var<f32, S>,
+    vel: Var<f32, S>,
+    restitution: f32,
+    direction: Direction,
+    terminal: bool,
+}
+
+impl<S: Solver> Ball<S> {
+    fn new(height: f32, restitution: f32) -> Self {
+        Self {
+            pos: Var::new(height),
+            vel: Var::new(0.0),
+            restitution,
+            direction: Direction::Falling,
+            terminal: false,
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Ball<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -9.8;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pos);
+        visitor.apply(&mut self.vel);
+    }
+}
+
+impl<S: Solver> EventSystem<S> for Ball<S> {
+    fn event_count(&self) -> usize {
+        1
+    }
+
+    fn event(&self, _: usize, _: f32) -> Event {
+        let event = Event {
+            direction: self.direction,
+            ..Event::new(*self.pos)
+        };
+        if self.terminal {
+            event.terminal()
+        } else {
+            event
+        }
+    }
+
+    fn handle_event(&mut self, _: usize, _: f32) {
+        *self.vel *= -self.restitution;
+    }
+}
+
+/// Harmonic oscillator x = cos(t) with events at given levels of x.
+#[derive(Clone)]
+struct Oscillator<S: Solver> {
+    x: Var<f32, S>,
+    v: Var<f32, S>,
+    levels: [f32; 2],
+    direction: Direction,
+    /// Number of evaluations of derivatives, shared between clones.
+    evaluations: Rc<Cell<usize>>,
+}
+
+impl<S: Solver> Oscillator<S> {
+    fn new(levels: [f32; 2], direction: Direction) -> Self {
+        Self {
+            x: Var::new(1.0),
+            v: Var::new(0.0),
+            levels,
+            direction,
+            evaluations: Rc::default(),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Oscillator<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = *self.v;
+        self.v.deriv = -*self.x;
+        self.evaluations.set(self.evaluations.get() + 1);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+        visitor.apply(&mut self.v);
+    }
+}
+
+impl<S: Solver> EventSystem<S> for Oscillator<S> {
+    fn event_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    fn event(&self, index: usize, _: f32) -> Event {
+        Event {
+            direction: self.direction,
+            ..Event::new(*self.x - self.levels[index]).terminal()
+        }
+    }
+}
+
+/// Test crossing detection in each direction.
+#[test]
+fn test_direction_is_crossing() {
+    assert!(Direction::Both.is_crossing(1.0, -1.0));
+    assert!(Direction::Both.is_crossing(-1.0, 0.0));
+    assert!(Direction::Rising.is_crossing(-1.0, 1.0));
+    assert!(!Direction::Rising.is_crossing(1.0, -1.0));
+    assert!(Direction::Falling.is_crossing(1.0, 0.0));
+    assert!(!Direction::Falling.is_crossing(-1.0, 1.0));
+    // Leaving zero is not a crossing
+    assert!(!Direction::Both.is_crossing(0.0, 1.0));
+    assert!(!Direction::Both.is_crossing(0.0, -1.0));
+}
+
+/// Test that a terminal event stops integration at the impact time.
+#[test]
+fn test_terminal_event() {
+    let mut ball = Ball::<Rk4> {
+        terminal: true,
+        ..Ball::new(4.9, 1.0)
+    };
+    let mut t = 0.0;
+    let hit = EventLocator::default()
+        .integrate(&Rk4, &mut ball, &mut t, 10.0, 0.03)
+        .expect("Ball did not hit the ground");
+
+    // Falling from 4.9 m takes exactly 1 s
+    assert!(hit.terminal);
+    assert_eq!(hit.index, 0);
+    assert_eq!(hit.time, t);
+    assert!((t - 1.0).abs() < 1e-5, "Impact time: {}", t);
+    // The ball is just below the ground and moves upwards after the impact
+    assert!(
+        *ball.pos <= 0.0 && *ball.pos > -1e-4,
+        "Ball position: {}",
+        *ball.pos
+    );
+    assert!(
+        (*ball.vel - 9.8).abs() < 1e-3,
+        "Ball velocity: {}",
+        *ball.vel
+    );
+}
+
+/// Test that an elastic ball bounces periodically to the same height.
+#[test]
+fn test_bouncing_ball() {
+    let mut ball = Ball::<Rk4>::new(4.9, 1.0);
+    let mut locator = EventLocator::default();
+    let mut t = 0.0;
+
+    // Impacts happen at t = 1, 3, 5, ...
+    for bounce in 0..4 {
+        let mut hit = None;
+        while hit.is_none() {
+            hit = locator.solve_step(&Rk4, &mut ball, &mut t, 0.07);
+        }
+        let expected = 1.0 + 2.0 * bounce as f32;
+        assert!(
+            (t - expected).abs() < 1e-4,
+            "Bounce {} at {}, expected {}",
+            bounce,
+            t,
+            expected
+        );
+    }
+
+    // Reaches the initial height at the apex
+    locator.integrate(&Rk4, &mut ball, &mut t, 8.0, 0.07);
+    assert!((*ball.pos - 4.9).abs() < 1e-3, "Apex height: {}", *ball.pos);
+}
+
+/// Test that restitution reduces bounce height and the ball stays above the ground.
+#[test]
+fn test_inelastic_bounces() {
+    let mut ball = Ball::<Rk4>::new(4.9, 0.5);
+    let mut locator = EventLocator::default();
+    let mut t: f32 = 0.0;
+    let mut bounces = 0;
+    while t < 2.2 {
+        let dt = (2.2 - t).min(0.01);
+        if locator.solve_step(&Rk4, &mut ball, &mut t, dt).is_some() {
+            bounces += 1;
+        }
+        assert!(
+            *ball.pos > -1e-4,
+            "Ball fell through the ground: {}",
+            *ball.pos
+        );
+    }
+
+    // Impacts at t = 1 and 2 with halving velocity, 2.45 m/s after the second one
+    assert_eq!(bounces, 2);
+    let expected = 2.45 - 9.8 * 0.2;
+    assert!(
+        (*ball.vel - expected).abs() < 1e-3,
+        "Ball velocity: {}",
+        *ball.vel
+    );
+}
+
+/// Test that direction filters select crossings.
+#[test]
+fn test_event_direction() {
+    for (direction, expected) in [
+        (Direction::Both, 0.5 * PI),
+        (Direction::Falling, 0.5 * PI),
+        (Direction::Rising, 1.5 * PI),
+    ] {
+        let mut system = Oscillator::<Rk4>::new([0.0, 2.0], direction);
+        let mut t = 0.0;
+        let hit = EventLocator::default()
+            .integrate(&Rk4, &mut system, &mut t, 10.0, 0.1)
+            .expect("Event did not occur");
+        assert_eq!(hit.index, 0);
+        assert!(
+            (t - expected).abs() < 1e-4,
+            "{:?} crossing at {}, expected {}",
+            direction,
+            t,
+            expected
+        );
+    }
+}
+
+/// Test that the earliest of several events within a step is reported.
+#[test]
+fn test_earliest_event() {
+    // x = cos(t) crosses 0.5 at π/3 and -0.5 at 2π/3
+    let mut system = Oscillator::<Rk4>::new([-0.5, 0.5], Direction::Falling);
+    let mut t = 0.0;
+    let hit = EventLocator::default()
+        .solve_step(&Rk4, &mut system, &mut t, 2.5)
+        .expect("Event did not occur");
+    assert_eq!(hit.index, 1);
+    // Single large RK4 step is a coarse interpolant
+    assert!((t - PI / 3.0).abs() < 1e-2, "Crossing at {}", t);
+    assert!((*system.x - 0.5).abs() < 1e-4);
+}
+
+/// Test event location with adaptive steps.
+#[test]
+fn test_adaptive_event() {
+    let solver = Dopri5::default();
+    let mut locator = EventLocator::default();
+    let mut system = Oscillator::<Dopri5>::new([0.0, 2.0], Direction::Both);
+    let mut t = 0.0;
+    let mut dt = 0.5;
+    loop {
+        let (step, hit) = locator.adaptive_step(&solver, &mut system, &mut t, dt);
+        dt = step.next;
+        if let Some(hit) = hit {
+            assert_eq!(hit.time, t);
+            break;
+        }
+        assert!(t < 10.0, "Event did not occur");
+    }
+    assert!((t - 0.5 * PI).abs() < 1e-4, "Crossing at {}", t);
+
+    // The state at the event is consistent with the solver
+    let mut reference = Oscillator::<Dopri5>::new([0.0, 2.0], Direction::Both);
+    let mut t_reference = 0.0;
+    while t_reference < t {
+        let dt = (t - t_reference).min(0.1);
+        solver.adaptive_step(&mut reference, &mut t_reference, dt);
+    }
+    assert!((*system.x - *reference.x).abs() < 1e-4);
+}
+
+/// Test that dense output locates crossings without evaluating derivatives.
+#[test]
+fn test_dense_event() {
+    let mut locator = EventLocator::default();
+    let mut system = Oscillator::<Rk4Dense>::new([0.0, 2.0], Direction::Both);
+    let mut t = 0.0;
+    let hit = locator
+        .integrate_dense(&Rk4Dense, &mut system, &mut t, 10.0, 0.1)
+        .expect("Event did not occur");
+    assert_eq!(hit.index, 0);
+    assert!((t - 0.5 * PI).abs() < 1e-4, "Crossing at {}", t);
+    assert!(system.x.abs() < 1e-4);
+    // Four evaluations for each of the 16 steps
+    assert_eq!(system.evaluations.get(), 64);
+
+    // Re-stepping evaluates derivatives at each iteration
+    let mut system = Oscillator::<Rk4>::new([0.0, 2.0], Direction::Both);
+    let mut t = 0.0;
+    EventLocator::default().integrate(&Rk4, &mut system, &mut t, 10.0, 0.1);
+    assert!((t - 0.5 * PI).abs() < 1e-4, "Crossing at {}", t);
+    assert!(system.evaluations.get() > 64);
+
+    // The earliest of several events within a step, x = cos(t) crosses 0.8 before 0.5
+    let mut system = Oscillator::<Rk4Dense>::new([0.5, 0.8], Direction::Falling);
+    let mut t = 0.0;
+    let hit = EventLocator::default()
+        .solve_step_dense(&Rk4Dense, &mut system, &mut t, 1.2)
+        .expect("Event did not occur");
+    assert_eq!(hit.index, 1);
+    // Dense output of a single large step is only third order
+    assert!((t - 0.8f32.acos()).abs() < 5e-2, "Crossing at {}", t);
+    assert!((*system.x - 0.8).abs() < 1e-4);
+}
+
+/// Test event location with adaptive steps and dense output.
+#[test]
+fn test_adaptive_dense_event() {
+    let solver = Dopri5::default().with_dense_output();
+    let mut locator = EventLocator::default();
+    let mut system = Oscillator::<Dopri5Dense>::new([0.0, 2.0], Direction::Both);
+    let mut t = 0.0;
+    let mut dt = 0.5;
+    loop {
+        let (step, hit) = locator.adaptive_step_dense(&solver, &mut system, &mut t, dt);
+        dt = step.next;
+        if let Some(hit) = hit {
+            assert_eq!(hit.time, t);
+            break;
+        }
+        assert!(t < 10.0, "Event did not occur");
+    }
+    assert!((t - 0.5 * PI).abs() < 1e-4, "Crossing at {}", t);
+    assert!(system.x.abs() < 1e-4);
+}
+
+/// Test that an event with crossings in both directions is handled once at each root.
+#[test]
+fn test_event_handled_once() {
+    for dense in [false, true] {
+        let mut ball = Ball::<Rk4Dense> {
+            direction: Direction::Both,
+            ..Ball::new(1.0, 0.9)
+        };
+        let mut locator = EventLocator::default();
+        let mut t: f32 = 0.0;
+        let mut hits = Vec::new();
+        while t < 1.0 {
+            let dt = (1.0 - t).min(0.01);
+            let hit = if dense {
+                locator.solve_step_dense(&Rk4Dense, &mut ball, &mut t, dt)
+            } else {
+                locator.solve_step(&Rk4Dense, &mut ball, &mut t, dt)
+            };
+            hits.extend(hit.map(|hit| hit.time));
+        }
+
+        // The ball hits the ground at √(2 / 9.8) and rebounds up to 0.81 m
+        assert_eq!(hits.len(), 1, "Events at {:?}", hits);
+        assert!((hits[0] - (2.0f32 / 9.8).sqrt()).abs() < 1e-4);
+        assert!(*ball.pos > 0.5, "Ball position: {}", *ball.pos);
+    }
+}
+
+/// Test that integration with a non-positive step is refused.
+#[test]
+#[should_panic(expected = "Time step must be positive")]
+fn test_integrate_zero_step() {
+    let mut ball = Ball::<Rk4>::new(4.9, 1.0);
+    let mut t = 0.0;
+    EventLocator::default().integrate(&Rk4, &mut ball, &mut t, 1.0, 0.0);
+}
@@ -0,0 +1,217 @@
+//! Tests for flattening state into vectors.
+
+use crate::{
+    DIso3, DRot3, Euler, Iso2, Param, Rk4, Rot2, Rot3, Solver, System, Var, Visitor, flatten_state,
+    unflatten_state,
+};
+use glam::{DVec3, Quat, Vec2, Vec3};
+
+/// Flatten a single parameter.
+fn flat<P: Param>(value: &P) -> Vec<P::Scalar> {
+    let mut out = vec![Default::default(); value.flat_dim()];
+    value.write_flat(&mut out);
+    out
+}
+
+/// Test flattened representations of parameters and their round trip.
+#[test]
+fn test_flat_params() {
+    assert_eq!(flat(&2.5f32), [2.5]);
+    assert_eq!(flat(&Vec3::new(1.0, 2.0, 3.0)), [1.0, 2.0, 3.0]);
+    assert_eq!(flat(&Rot2::from_angle(0.5)), [0.5]);
+    assert_eq!(
+        flat(&[Vec2::new(1.0, 2.0), Vec2::new(3.0, 4.0)]),
+        [1.0, 2.0, 3.0, 4.0]
+    );
+    assert_eq!(flat(&(1.0f32, Vec2::new(2.0, 3.0))), [1.0, 2.0, 3.0]);
+    assert_eq!(flat(&vec![vec![1.0f32], vec![2.0, 3.0]]), [1.0, 2.0, 3.0]);
+
+    let rot = Rot3::from_scaled_axis(Vec3::new(0.3, -0.2, 1.0));
+    assert_eq!(flat(&rot), Quat::from(rot).to_array());
+    let pose = Iso2::new(Rot2::from_angle(0.25), Vec2::new(-1.0, 2.0));
+    assert_eq!(flat(&pose), [0.25, -1.0, 2.0]);
+
+    // Round trip
+    let pose = DIso3::new(DRot3::from_scaled_axis(DVec3::new(0.1, 0.2, 0.3)), DVec3::X);
+    let mut restored = DIso3::default();
+    restored.read_flat(&flat(&pose));
+    for (a, b) in flat(&restored).iter().zip(flat(&pose)) {
+        assert!((a - b).abs() < 1e-15);
+    }
+
+    let mut values = (Rot2::default(), [Vec3::ZERO; 2]);
+    values.read_flat(&[0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    assert_eq!(values.0.angle(), 0.5);
+    assert_eq!(values.1[1], Vec3::new(4.0, 5.0, 6.0));
+
+    // Quaternions are normalized when read
+    let mut rot = Rot3::default();
+    rot.read_flat(&[0.0, 0.0, 2.0, 0.0]);
+    assert!(rot.transform(Vec3::X).abs_diff_eq(-Vec3::X, 1e-6));
+}
+
+/// Body with different kinds of variables.
+struct Body<S: Solver> {
+    pos: Var<Vec3, S>,
+    vel: Var<Vec3, S>,
+    rot: Var<Rot3, S>,
+    particles: Var<Vec<Vec2>, S>,
+}
+
+impl<S: Solver> Body<S> {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(Vec3::new(1.0, 2.0, 3.0)),
+            vel: Var::new(Vec3::new(0.0, 1.0, 0.0)),
+            rot: Var::new(Rot3::from_scaled_axis(Vec3::Y)),
+            particles: Var::new(vec![Vec2::ONE; 3]),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Body<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos;
+        self.rot.deriv = Vec3::Z;
+        self.particles.deriv = self.particles.iter().map(|p| -*p).collect();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+        visitor.apply(&mut self.rot);
+        visitor.apply(&mut self.particles);
+    }
+}
+
+/// Test the layout of a flattened system.
+#[test]
+fn test_layout() {
+    let mut body = Body::<Rk4>::new();
+    let (state, layout) = flatten_state(&mut body);
+    assert_eq!(state.len(), 16);
+    assert_eq!(layout.dim(), 16);
+    let names: Vec<&str> = layout.vars().iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["var0", "var1", "var2", "var3"]);
+    let ranges: Vec<_> = layout.vars().iter().map(|v| v.range()).collect();
+    assert_eq!(ranges, [0..3, 3..6, 6..10, 10..16]);
+
+    let layout = layout.with_names(["pos", "vel", "rot", "particles"]);
+    assert_eq!(&state[layout.get("vel").unwrap().range()], [0.0, 1.0, 0.0]);
+    assert!(layout.get("var0").is_none());
+}
+
+/// Test that restoring a flattened state reproduces the trajectory.
+#[test]
+fn test_flatten_round_trip() {
+    let mut body = Body::<Rk4>::new();
+    let (start, _) = flatten_state(&mut body);
+    let mut t = 0.0;
+    for _ in 0..10 {
+        Rk4.solve_step(&mut body, &mut t, 0.1);
+    }
+    let (end, _) = flatten_state(&mut body);
+
+    let mut other = Body::<Rk4>::new();
+    *other.pos = Vec3::ZERO;
+    *other.rot = Rot3::default();
+    unflatten_state(&mut other, &start);
+    let mut t = 0.0;
+    for _ in 0..10 {
+        Rk4.solve_step(&mut other, &mut t, 0.1);
+    }
+    let (replayed, _) = flatten_state(&mut other);
+    let difference = end
+        .iter()
+        .zip(&replayed)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f32::max);
+    assert!(difference < 1e-6);
+}
+
+/// Test that state vectors of a wrong size are refused.
+#[test]
+#[should_panic(expected = "State vector is too long")]
+fn test_unflatten_too_long() {
+    let mut body = Body::<Euler>::new();
+    unflatten_state(&mut body, &[0.0; 17]);
+}
+
+/// Test that state vectors of a wrong size are refused.
+#[test]
+#[should_panic(expected = "State vector is too short")]
+fn test_unflatten_too_short() {
+    let mut body = Body::<Euler>::new();
+    unflatten_state(&mut body, &[0.0; 15]);
+}
+
+/// Custom parameter counting up, with a scale applied to its derivative.
+#[derive(Clone, Default)]
+struct Counter {
+    count: f32,
+    rate: f32,
+}
+
+impl Param for Counter {
+    type Scalar = f32;
+    type Deriv = f32;
+    fn step(&mut self, deriv: &f32, dt: f32) {
+        self.count += self.rate * deriv * dt;
+    }
+    fn magnitude(&self) -> f32 {
+        self.count.abs()
+    }
+    fn flat_dim(&self) -> usize {
+        2
+    }
+    fn write_flat(&self, out: &mut [f32]) {
+        out.copy_from_slice(&[self.count, self.rate]);
+    }
+    fn read_flat(&mut self, data: &[f32]) {
+        self.count = data[0];
+        self.rate = data[1];
+    }
+}
+
+/// System with a custom parameter.
+struct Counted<S: Solver> {
+    pos: Var<f32, S>,
+    count: Var<Counter, S>,
+}
+
+impl<S: Solver> System<S> for Counted<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = 1.0;
+        self.count.deriv = 1.0;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.count);
+        visitor.apply(&mut self.pos);
+    }
+}
+
+/// Test that custom parameters are packed and restored.
+#[test]
+fn test_flat_custom() {
+    let mut system = Counted::<Euler> {
+        pos: Var::new(2.0),
+        count: Var::new(Counter {
+            count: 5.0,
+            rate: 0.5,
+        }),
+    };
+    let (state, layout) = flatten_state(&mut system);
+    assert_eq!(state, [5.0, 0.5, 2.0]);
+    let ranges: Vec<_> = layout.vars().iter().map(|v| v.range()).collect();
+    assert_eq!(ranges, [0..2, 2..3]);
+
+    unflatten_state(&mut system, &[7.0, 2.0, 3.0]);
+    assert_eq!(*system.pos, 3.0);
+    assert_eq!((system.count.count, system.count.rate), (7.0, 2.0));
+
+    // The restored state is integrated
+    let mut t = 0.0;
+    Euler.solve_step(&mut system, &mut t, 0.5);
+    assert_eq!(system.count.count, 8.0);
+}
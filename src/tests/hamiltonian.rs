@@ -0,0 +1,280 @@
+//! Tests for energy diagnostics of conservative systems.
+
+use crate::{
+    EnergyMonitor, Hamiltonian, Rk4, Solver, Symplectic, System, Var, Verlet, Visitor,
+    splitting::{ForestRuth, KickDrift, Yoshida6},
+};
+use core::f32::consts::PI;
+
+/// Pendulum from the `pendulum` example.
+struct Pendulum<S: Solver> {
+    theta: Var<f32, S>,
+    omega: Var<f32, S>,
+}
+
+const G: f32 = 9.8;
+const L: f32 = 1.0;
+
+impl<S: Solver> Pendulum<S> {
+    fn new() -> Self {
+        Self {
+            theta: Var::new(2.0 * PI / 3.0),
+            omega: Var::new(0.0),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Pendulum<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.theta.deriv = *self.omega;
+        self.omega.deriv = -(G / L) * self.theta.sin();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.theta, &mut self.omega);
+    }
+}
+
+impl<S: Solver> Hamiltonian for Pendulum<S> {
+    /// Energy of the unit mass: kinetic plus potential relative to the pivot.
+    fn energy(&self) -> f32 {
+        0.5 * (L * *self.omega).powi(2) - G * L * self.theta.cos()
+    }
+}
+
+/// Coupled oscillators from the `coupled_oscillators` example.
+struct CoupledOscillators<S: Solver> {
+    m: [f32; 2],
+    x: Var<[f32; 2], S>,
+    v: Var<[f32; 2], S>,
+}
+
+const WALL: f32 = 3.0;
+const K: f32 = 10.0;
+const SPRING: f32 = 2.0;
+
+impl<S: Solver> CoupledOscillators<S> {
+    fn new() -> Self {
+        Self {
+            m: [1.0, 3.0],
+            x: Var::new([-2.0, 1.0]),
+            v: Var::new([0.0, -3.0]),
+        }
+    }
+
+    /// Extensions of the left, middle and right springs.
+    fn extensions(&self) -> [f32; 3] {
+        let [x1, x2] = *self.x;
+        [x1 + WALL - SPRING, x2 - x1 - SPRING, WALL - SPRING - x2]
+    }
+}
+
+impl<S: Solver> System<S> for CoupledOscillators<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        let [left, middle, right] = self.extensions();
+        self.x.deriv = *self.v;
+        self.v.deriv = [
+            K / self.m[0] * (middle - left),
+            K / self.m[1] * (right - middle),
+        ];
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.x, &mut self.v);
+    }
+}
+
+impl<S: Solver> Hamiltonian for CoupledOscillators<S> {
+    fn energy(&self) -> f32 {
+        let kinetic: f32 = (0..2).map(|i| 0.5 * self.m[i] * self.v[i].powi(2)).sum();
+        let potential: f32 = self.extensions().iter().map(|e| 0.5 * K * e * e).sum();
+        kinetic + potential
+    }
+}
+
+/// Monitor a conservative system stepped by a solver for the given time.
+fn run<S: Solver, Y: System<S> + Hamiltonian>(
+    solver: &S,
+    system: &mut Y,
+    dt: f32,
+    duration: f32,
+) -> EnergyMonitor {
+    let mut monitor = EnergyMonitor::new(system);
+    let mut t = 0.0;
+    for _ in 0..(duration / dt).round() as usize {
+        monitor.solve_step(solver, system, &mut t, dt);
+    }
+    monitor
+}
+
+/// Check that the drift of energy stays within the bound and does not grow over a longer run.
+fn check_bounded(name: &str, short: EnergyMonitor, long: EnergyMonitor, bound: f32) {
+    assert!(
+        long.max_energy_drift() < bound,
+        "{}: drift {} exceeds {}",
+        name,
+        long.max_energy_drift(),
+        bound
+    );
+    assert!(
+        long.max_energy_drift() < 1.1 * short.max_energy_drift(),
+        "{}: drift grows from {} to {}",
+        name,
+        short.max_energy_drift(),
+        long.max_energy_drift()
+    );
+}
+
+/// Check that energy steadily decreases, several times more over a four times longer run.
+fn check_dissipating(name: &str, short: EnergyMonitor, long: EnergyMonitor, bound: f32) {
+    assert!(
+        short.max_energy_drift() < bound,
+        "{}: drift {} exceeds {}",
+        name,
+        short.max_energy_drift(),
+        bound
+    );
+    assert!(short.energy_drift() < 0.0 && long.energy_drift() < 0.0);
+    assert!(
+        long.energy_drift() < 2.5 * short.energy_drift(),
+        "{}: drift {} does not accumulate to {}",
+        name,
+        short.energy_drift(),
+        long.energy_drift()
+    );
+}
+
+/// Test that the monitor records drifts per step and over the run.
+#[test]
+fn test_energy_monitor() {
+    struct State {
+        energy: f32,
+        momentum: [f32; 3],
+    }
+
+    impl Hamiltonian for State {
+        fn energy(&self) -> f32 {
+            self.energy
+        }
+
+        fn momentum(&self) -> Option<[f32; 3]> {
+            Some(self.momentum)
+        }
+    }
+
+    let mut state = State {
+        energy: -2.0,
+        momentum: [1.0, 0.0, 0.0],
+    };
+    let mut monitor = EnergyMonitor::new(&state);
+    assert_eq!(monitor.max_momentum_drift(), Some(0.0));
+    assert_eq!(monitor.max_angular_momentum_drift(), None);
+
+    state.energy = -1.0;
+    state.momentum = [1.0, 3.0, 4.0];
+    assert_eq!(monitor.observe(&state), 0.5);
+    state.energy = -2.5;
+    state.momentum = [1.0, 0.0, 0.0];
+    assert_eq!(monitor.observe(&state), -0.75);
+
+    assert_eq!(monitor.steps(), 2);
+    assert_eq!((monitor.initial_energy(), monitor.energy()), (-2.0, -2.5));
+    assert_eq!(
+        (monitor.step_drift(), monitor.max_step_drift()),
+        (-0.75, 0.75)
+    );
+    assert_eq!(
+        (monitor.energy_drift(), monitor.max_energy_drift()),
+        (-0.25, 0.5)
+    );
+    assert_eq!(monitor.max_momentum_drift(), Some(5.0));
+
+    // Drift of zero energy is absolute
+    let mut state = State {
+        energy: 0.0,
+        momentum: [0.0; 3],
+    };
+    let mut monitor = EnergyMonitor::new(&state);
+    state.energy = 0.25;
+    assert_eq!(monitor.observe(&state), 0.25);
+}
+
+/// Test energy drift of the pendulum with non-symplectic and symplectic solvers.
+#[test]
+fn test_pendulum_energy_drift() {
+    let (dt, short, long) = (0.1, 100.0, 400.0);
+    check_dissipating(
+        "Rk4",
+        run(&Rk4, &mut Pendulum::new(), dt, short),
+        run(&Rk4, &mut Pendulum::new(), dt, long),
+        0.03,
+    );
+    check_bounded(
+        "Verlet",
+        run(&Verlet, &mut Pendulum::new(), dt, short),
+        run(&Verlet, &mut Pendulum::new(), dt, long),
+        0.06,
+    );
+    let solver = Symplectic::<ForestRuth>::new();
+    check_bounded(
+        "ForestRuth",
+        run(&solver, &mut Pendulum::new(), dt, short),
+        run(&solver, &mut Pendulum::new(), dt, long),
+        2e-3,
+    );
+    // Symplectic Euler is only first order, but does not drift either
+    let solver = Symplectic::<KickDrift>::new();
+    check_bounded(
+        "KickDrift",
+        run(&solver, &mut Pendulum::new(), 0.01, short),
+        run(&solver, &mut Pendulum::new(), 0.01, long),
+        0.05,
+    );
+    // Drift of the sixth-order method is dominated by rounding errors
+    let monitor = run(
+        &Symplectic::<Yoshida6>::new(),
+        &mut Pendulum::new(),
+        dt,
+        long,
+    );
+    assert!(monitor.max_energy_drift() < 1e-4);
+}
+
+/// Test energy drift of the coupled oscillators with non-symplectic and symplectic solvers.
+#[test]
+fn test_coupled_oscillators_energy_drift() {
+    let (dt, short, long) = (0.1, 100.0, 400.0);
+    check_dissipating(
+        "Rk4",
+        run(&Rk4, &mut CoupledOscillators::new(), dt, short),
+        run(&Rk4, &mut CoupledOscillators::new(), dt, long),
+        0.1,
+    );
+    check_bounded(
+        "Verlet",
+        run(&Verlet, &mut CoupledOscillators::new(), dt, short),
+        run(&Verlet, &mut CoupledOscillators::new(), dt, long),
+        0.03,
+    );
+    let solver = Symplectic::<ForestRuth>::new();
+    check_bounded(
+        "ForestRuth",
+        run(&solver, &mut CoupledOscillators::new(), dt, short),
+        run(&solver, &mut CoupledOscillators::new(), dt, long),
+        3e-3,
+    );
+    let solver = Symplectic::<KickDrift>::new();
+    check_bounded(
+        "KickDrift",
+        run(&solver, &mut CoupledOscillators::new(), 0.01, short),
+        run(&solver, &mut CoupledOscillators::new(), 0.01, long),
+        0.03,
+    );
+    let monitor = run(
+        &Symplectic::<Yoshida6>::new(),
+        &mut CoupledOscillators::new(),
+        dt,
+        long,
+    );
+    assert!(monitor.max_energy_drift() < 1e-4);
+}
@@ -0,0 +1,288 @@
+//! Tests for the implicit Euler solver on stiff systems.
+
+use crate::{
+    ImplicitEuler, Rk4, Rot3, Solver, System, Var, Visitor, tests::system::ParticleWithDrag,
+};
+use glam::{Quat, Vec2, Vec3};
+
+/// A system with exponential decay: dx/dt = -k*x
+struct DecaySystem<S: Solver> {
+    x: Var<f32, S>,
+    rate: f32,
+}
+
+impl<S: Solver> System<S> for DecaySystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = -self.rate * *self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Test that a linear system is solved exactly: x_{n+1} = x_n / (1 + k*dt).
+#[test]
+fn test_implicit_euler_linear() {
+    let mut system = DecaySystem::<ImplicitEuler> {
+        x: Var::new(1.0),
+        rate: 3.0,
+    };
+    let solver = ImplicitEuler::default();
+    let dt = 0.1;
+
+    let mut expected = 1.0;
+    let mut t = 0.0;
+    for _ in 0..10 {
+        solver.solve_step(&mut system, &mut t, dt);
+        expected /= 1.0 + 3.0 * dt;
+    }
+
+    assert!(
+        (*system.x - expected).abs() < 1e-5,
+        "Implicit Euler result {} differs from {}",
+        *system.x,
+        expected
+    );
+    // Derivative is reset after the step
+    assert_eq!(system.x.deriv, 0.0);
+}
+
+/// Test that Newton iterations solve a nonlinear step: x_{n+1} = x_n - dt * x_{n+1}^3.
+#[test]
+fn test_implicit_euler_nonlinear() {
+    struct CubicDecay<S: Solver> {
+        x: Var<f32, S>,
+    }
+
+    impl<S: Solver> System<S> for CubicDecay<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = -self.x.powi(3);
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+        }
+    }
+
+    let mut system = CubicDecay::<ImplicitEuler> { x: Var::new(2.0) };
+    let dt = 0.5;
+    let mut t = 0.0;
+    ImplicitEuler::default().solve_step(&mut system, &mut t, dt);
+
+    let x = *system.x;
+    let residual = x - 2.0 + dt * x.powi(3);
+    assert!(
+        residual.abs() < 1e-4,
+        "Step equation not solved: {}",
+        residual
+    );
+}
+
+/// Test stability on a very stiff system where explicit methods explode.
+#[test]
+fn test_implicit_euler_stiff_stability() {
+    let rate = 1e4;
+    let dt = 0.01;
+
+    let mut explicit = DecaySystem::<Rk4> {
+        x: Var::new(1.0),
+        rate,
+    };
+    let mut implicit = DecaySystem::<ImplicitEuler> {
+        x: Var::new(1.0),
+        rate,
+    };
+    let (mut t_explicit, mut t_implicit) = (0.0, 0.0);
+    for _ in 0..10 {
+        Rk4.solve_step(&mut explicit, &mut t_explicit, dt);
+        ImplicitEuler::default().solve_step(&mut implicit, &mut t_implicit, dt);
+    }
+
+    assert!(
+        explicit.x.is_nan() || explicit.x.abs() > 1.0,
+        "RK4 should be unstable here"
+    );
+    assert!(
+        implicit.x.abs() < 1e-6,
+        "Implicit Euler did not decay: {}",
+        *implicit.x
+    );
+}
+
+/// Test coupled vector variables against the drag system used by other solvers.
+#[test]
+fn test_implicit_euler_coupled() {
+    let mut system = ParticleWithDrag::<ImplicitEuler>::new(Vec2::ZERO, Vec2::new(1.0, -2.0), 0.5);
+    let solver = ImplicitEuler::default();
+    let dt = 0.01;
+    let steps = 200;
+
+    let mut t = 0.0;
+    for _ in 0..steps {
+        solver.solve_step(&mut system, &mut t, dt);
+    }
+
+    // Analytical solution: v(t) = v0 * exp(-k*t), x(t) = v0 * (1 - exp(-k*t)) / k
+    let t = dt * steps as f32;
+    let decay = (-0.5 * t).exp();
+    let expected_vel = Vec2::new(1.0, -2.0) * decay;
+    let expected_pos = Vec2::new(1.0, -2.0) * (1.0 - decay) / 0.5;
+    assert!((*system.velocity - expected_vel).length() < 1e-2);
+    assert!((*system.position - expected_pos).length() < 1e-2);
+}
+
+/// Test that rotations are solved in their tangent space.
+///
+/// Rotation relaxing towards identity, dθ/dt = -k*θ, where θ is the rotation vector.
+/// For a fixed axis backward Euler gives θ_{n+1} = θ_n / (1 + k*dt) exactly.
+#[test]
+fn test_implicit_euler_rot3() {
+    struct Relaxation<S: Solver> {
+        rot: Var<Rot3, S>,
+        rate: f32,
+    }
+
+    impl<S: Solver> System<S> for Relaxation<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.rot.deriv = -self.rate * Quat::from(*self.rot).to_scaled_axis();
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.rot);
+        }
+    }
+
+    let axis = Vec3::new(1.0, 2.0, -2.0).normalize();
+    let mut system = Relaxation::<ImplicitEuler> {
+        rot: Var::new(Rot3::from_scaled_axis(axis * 2.0)),
+        rate: 1e3,
+    };
+    let dt = 0.01;
+
+    let mut t = 0.0;
+    ImplicitEuler::default().solve_step(&mut system, &mut t, dt);
+
+    let angle = Quat::from(*system.rot).to_scaled_axis();
+    let expected = axis * 2.0 / (1.0 + 1e3 * dt);
+    assert!(
+        (angle - expected).length() < 1e-4,
+        "Rotation {} differs from {}",
+        angle,
+        expected
+    );
+    // Quaternion stays normalized
+    assert!((Quat::from(*system.rot).length() - 1.0).abs() < 1e-6);
+}
+
+/// Ball falling onto a very stiff spring-damper ground.
+struct StiffBall<S: Solver> {
+    pos: Var<f32, S>,
+    vel: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for StiffBall<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        const K: f32 = 1e6;
+        const D: f32 = 1e4;
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -9.8 + (K - D * *self.vel) * (-self.pos.min(0.0));
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+/// Test stiff contact with a time step much larger than the contact time scale.
+#[test]
+fn test_implicit_euler_stiff_contact() {
+    let mut system = StiffBall::<ImplicitEuler> {
+        pos: Var::new(1.0),
+        vel: Var::new(0.0),
+    };
+    let solver = ImplicitEuler::default();
+
+    let mut t = 0.0;
+    for _ in 0..500 {
+        solver.solve_step(&mut system, &mut t, 0.01);
+        assert!(system.pos.is_finite() && system.vel.is_finite());
+    }
+
+    // The ball comes to rest on the ground, compressing it by g/K
+    assert!(
+        (*system.pos + 9.8e-6).abs() < 1e-4,
+        "Ball position: {}",
+        *system.pos
+    );
+    assert!(system.vel.abs() < 1e-2, "Ball velocity: {}", *system.vel);
+}
+
+/// Test that a singular Jacobian falls back to an explicit step instead of freezing the state.
+#[test]
+fn test_implicit_euler_singular_jacobian() {
+    /// With `dt = 1` the Jacobian `1 - dt * ∂(dx/dt)/∂x` of `x` is exactly zero.
+    struct Singular<S: Solver> {
+        x: Var<f32, S>,
+        y: Var<f32, S>,
+    }
+
+    impl<S: Solver> System<S> for Singular<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = *self.x;
+            self.y.deriv = 1.0;
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+            visitor.apply(&mut self.y);
+        }
+    }
+
+    let mut system = Singular::<ImplicitEuler> {
+        x: Var::new(0.0),
+        y: Var::new(2.0),
+    };
+    let mut t = 0.0;
+    ImplicitEuler::default().solve_step(&mut system, &mut t, 1.0);
+
+    assert_eq!(t, 1.0);
+    assert_eq!(*system.x, 0.0);
+    assert_eq!(*system.y, 3.0);
+}
+
+/// Test a stiff variable starting at zero with a purely relative tolerance.
+#[test]
+fn test_implicit_euler_stiff_from_zero() {
+    /// Fast relaxation towards one.
+    struct Relax<S: Solver> {
+        x: Var<f32, S>,
+    }
+
+    impl<S: Solver> System<S> for Relax<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = -1000.0 * (*self.x - 1.0);
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+        }
+    }
+
+    let mut system = Relax::<ImplicitEuler> { x: Var::new(0.0) };
+    let mut solver = ImplicitEuler::default();
+    solver.tolerance.abs = 0.0;
+
+    // x_1 = 100 / 101, instead of 100 with an explicit step
+    let mut t = 0.0;
+    solver.solve_step(&mut system, &mut t, 0.1);
+    assert!(
+        (*system.x - 100.0 / 101.0).abs() < 1e-4,
+        "x = {}",
+        *system.x
+    );
+    for _ in 0..10 {
+        solver.solve_step(&mut system, &mut t, 0.1);
+    }
+    assert!((*system.x - 1.0).abs() < 1e-4, "x = {}", *system.x);
+}
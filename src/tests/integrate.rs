@@ -0,0 +1,96 @@
+//! Tests for integration loops with observers.
+
+use crate::{Dopri5, Euler, Rk4, Solver, System, Var, Visitor, integrate, integrate_adaptive};
+
+/// Exponential decay dx/dt = -x.
+struct Decay<S: Solver> {
+    x: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for Decay<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = -*self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Test that integration ends exactly at the final time with the expected number of steps.
+#[test]
+fn test_integrate_final_time() {
+    for (t0, t1, dt, expected) in [
+        (0.0, 16.0, 0.02, 800),
+        (0.0, 1.0, 0.1, 10),
+        (0.3, 0.9, 0.1, 6),
+        (0.0, 1.05, 0.1, 11),
+        (-1.0, 1.0, 3.0, 1),
+        // Steps shorter than 0.1% of dt are merged into the last step
+        (0.0, 1.00005, 0.1, 10),
+    ] {
+        let mut system = Decay::<Euler> { x: Var::new(1.0) };
+        let mut times = Vec::new();
+        let steps = integrate(&mut system, &Euler, t0, t1, dt, |t, _| times.push(t));
+        assert_eq!(steps, expected, "t1={} dt={}", t1, dt);
+        assert_eq!(times.len(), steps);
+        assert_eq!(*times.last().unwrap(), t1);
+        let mut last = t0;
+        for &t in &times {
+            assert!(t > last && t - last <= dt * 1.001);
+            last = t;
+        }
+    }
+}
+
+/// Test that the observer sees the state after each step.
+#[test]
+fn test_integrate_observer() {
+    let mut system = Decay::<Rk4> { x: Var::new(1.0) };
+    let mut reference = Decay::<Rk4> { x: Var::new(1.0) };
+    let mut t_reference = 0.0;
+    let steps = integrate(&mut system, &Rk4, 0.0, 0.25, 0.1, |t, system| {
+        let dt = 0.1f32.min(0.25 - t_reference);
+        Rk4.solve_step(&mut reference, &mut t_reference, dt);
+        assert!((t - t_reference).abs() < 1e-6);
+        assert_eq!(*system.x, *reference.x);
+    });
+    assert_eq!(steps, 3);
+    assert!((*system.x - (-0.25f32).exp()).abs() < 1e-6);
+
+    // Empty interval
+    let steps = integrate(&mut system, &Rk4, 1.0, 1.0, 0.1, |_, _| panic!());
+    assert_eq!(steps, 0);
+}
+
+/// Test adaptive integration until the final time.
+#[test]
+fn test_integrate_adaptive() {
+    let mut system = Decay::<Dopri5> { x: Var::new(1.0) };
+    let solver = Dopri5::default();
+    let mut times = Vec::new();
+    let steps = integrate_adaptive(&mut system, &solver, 0.0, 3.0, 0.01, |t, system| {
+        assert!((*system.x - (-t).exp()).abs() < 1e-4);
+        times.push(t);
+    });
+    assert_eq!(steps, times.len());
+    assert_eq!(*times.last().unwrap(), 3.0);
+    assert!(times.windows(2).all(|w| w[0] < w[1]));
+    // Step size grows beyond the initial one
+    assert!(steps < 100);
+}
+
+/// Test that integration does not run backward in time.
+#[test]
+fn test_integrate_backward() {
+    let mut system = Decay::<Rk4> { x: Var::new(1.0) };
+    let steps = integrate(&mut system, &Rk4, 1.0, 0.0, 0.1, |_, _| panic!());
+    assert_eq!(steps, 0);
+    assert_eq!(*system.x, 1.0);
+
+    let mut system = Decay::<Dopri5> { x: Var::new(1.0) };
+    let solver = Dopri5::default();
+    let steps = integrate_adaptive(&mut system, &solver, 1.0, 0.0, 0.1, |_, _| panic!());
+    assert_eq!(steps, 0);
+    assert_eq!(*system.x, 1.0);
+}
@@ -0,0 +1,243 @@
+//! Tests for rigid body pose types.
+
+use crate::{
+    DIso3, DRot3, DTwist3, Deriv, Euler, Iso2, Iso3, Param, Rk4, Rot2, Rot3, Solver, System,
+    Twist2, Twist3, Var, Visitor,
+};
+use core::f32::consts::PI;
+use glam::{DVec3, Vec2, Vec3};
+
+fn iso3() -> Iso3 {
+    Iso3::new(
+        Rot3::from_scaled_axis(Vec3::new(0.3, -1.2, 0.5)),
+        Vec3::new(1.0, 2.0, -3.0),
+    )
+}
+
+/// Test composition, inverse and transforms of 2D poses.
+#[test]
+fn test_iso2_algebra() {
+    let a = Iso2::new(Rot2::from_angle(0.5 * PI), Vec2::new(1.0, 0.0));
+    let b = Iso2::new(Rot2::from_angle(0.25), Vec2::new(-2.0, 3.0));
+    let p = Vec2::new(0.5, -1.5);
+
+    assert!(
+        a.transform_point(Vec2::X)
+            .abs_diff_eq(Vec2::new(1.0, 1.0), 1e-6)
+    );
+    assert!(a.transform_vector(Vec2::X).abs_diff_eq(Vec2::Y, 1e-6));
+
+    let ab = a.chain(b);
+    let expected = b.transform_point(a.transform_point(p));
+    assert!(ab.transform_point(p).abs_diff_eq(expected, 1e-5));
+
+    let identity = a.chain(a.inverse());
+    assert!(identity.transform_point(p).abs_diff_eq(p, 1e-6));
+    assert!(
+        a.inverse()
+            .transform_point(a.transform_point(p))
+            .abs_diff_eq(p, 1e-6)
+    );
+}
+
+/// Test composition, inverse and transforms of 3D poses.
+#[test]
+fn test_iso3_algebra() {
+    let a = iso3();
+    let b = Iso3::new(Rot3::from_scaled_axis(Vec3::X), Vec3::Z);
+    let p = Vec3::new(0.5, -1.5, 2.0);
+
+    let ab = a.chain(b);
+    let expected = b.transform_point(a.transform_point(p));
+    assert!(ab.transform_point(p).abs_diff_eq(expected, 1e-5));
+
+    let identity = a.inverse().chain(a);
+    assert!(identity.transform_point(p).abs_diff_eq(p, 1e-5));
+    assert!(
+        a.inverse()
+            .transform_point(a.transform_point(p))
+            .abs_diff_eq(p, 1e-5)
+    );
+    assert!(
+        a.transform_vector(p)
+            .abs_diff_eq(a.transform_point(p) - a.pos, 1e-5)
+    );
+}
+
+/// Test that a constant twist moves a 2D body along a circle.
+#[test]
+fn test_twist2_circle() {
+    let twist = Twist2::new(Vec2::new(2.0, 0.0), 0.5);
+    let radius = 4.0;
+    for t in [0.1, 1.0, 3.0, 10.0] {
+        let pose = twist.exp(t);
+        let angle = 0.5 * t;
+        let expected = radius * Vec2::new(angle.sin(), 1.0 - angle.cos());
+        assert!(pose.pos.abs_diff_eq(expected, 1e-4), "t={}", t);
+        assert!((pose.rot.angle() - Rot2::from_angle(angle).angle()).abs() < 1e-5);
+    }
+
+    // Pure translation
+    let pose = Twist2::new(Vec2::new(1.0, -1.0), 0.0).exp(2.0);
+    assert_eq!(pose.pos, Vec2::new(2.0, -2.0));
+}
+
+/// Test that a constant twist moves a 3D body along a helix.
+#[test]
+fn test_twist3_helix() {
+    // Moving forward along x and up along z while yawing around z
+    let twist = Twist3::new(Vec3::new(1.0, 0.0, 0.3), Vec3::new(0.0, 0.0, 2.0));
+    let start = iso3();
+    for t in [0.01, 0.5, 2.0, 7.0] {
+        let pose = twist.exp(t).chain(start);
+        let angle = 2.0 * t;
+        let local = Vec3::new(0.5 * angle.sin(), 0.5 * (1.0 - angle.cos()), 0.3 * t);
+        assert!(
+            pose.pos.abs_diff_eq(start.transform_point(local), 1e-5),
+            "t={}",
+            t
+        );
+        let expected = Rot3::from_scaled_axis(Vec3::Z * angle).chain(start.rot);
+        assert!(
+            pose.rot
+                .transform(Vec3::X)
+                .abs_diff_eq(expected.transform(Vec3::X), 1e-5)
+        );
+    }
+}
+
+/// Test that the exponential map is continuous across its small-angle branches.
+#[test]
+fn test_twist3_exp_small_angles() {
+    let axis = Vec3::new(1.0, 2.0, 2.0) / 3.0;
+    let linear = Vec3::new(0.3, -0.7, 1.1);
+    let exp = |theta: f64| {
+        let twist = DTwist3::new(linear.as_dvec3(), axis.as_dvec3() * theta);
+        twist.exp(1.0).pos
+    };
+    for theta in [0.1, 1e-3] {
+        let below = exp(theta * (1.0 - 1e-12));
+        let above = exp(theta * (1.0 + 1e-12));
+        assert!(below.abs_diff_eq(above, 1e-12), "theta={}", theta);
+    }
+    assert!(exp(0.0).abs_diff_eq(linear.as_dvec3(), 1e-15));
+    assert!(exp(1e-20).abs_diff_eq(linear.as_dvec3(), 1e-15));
+}
+
+/// Test `Param` and `Deriv` implementations of poses and twists.
+#[test]
+fn test_iso_param() {
+    let mut pose = Iso3::default();
+    let twist = Twist3::new(Vec3::X, Vec3::Z * PI);
+    pose.step(&twist, 0.5);
+    pose.step(&twist, 0.5);
+    // Half a circle of radius 1/π
+    assert!(pose.pos.abs_diff_eq(Vec3::new(0.0, 2.0 / PI, 0.0), 1e-6));
+    assert!((pose.magnitude() - 2.0 / PI).abs() < 1e-6);
+
+    let mut d = twist;
+    d.add_scaled(&Twist3::new(Vec3::Y, Vec3::X), 2.0);
+    d.scale(0.5);
+    assert_eq!(
+        d,
+        Twist3::new(Vec3::new(0.5, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.5 * PI))
+    );
+    assert_eq!(d.dim(), 6);
+    let components: Vec<f32> = (0..6).map(|i| d.component(i)).collect();
+    assert_eq!(components, [0.5, 1.0, 0.0, 1.0, 0.0, 0.5 * PI]);
+    *d.component_mut(4) = 2.0;
+    assert_eq!(d.angular.y, 2.0);
+    assert!((Twist2::new(Vec2::new(3.0, 0.0), 4.0).norm() - 5.0).abs() < 1e-6);
+
+    let pose = Iso2::new(Rot2::from_angle(0.5 * PI), Vec2::ZERO);
+    let twist = pose.body_twist(Vec2::Y, 1.0);
+    assert!(twist.linear.abs_diff_eq(Vec2::X, 1e-6));
+    assert!(pose.world_velocity(&twist).abs_diff_eq(Vec2::Y, 1e-6));
+}
+
+/// Rigid body moving with constant body-frame velocities.
+struct Body<S: Solver> {
+    pose: Var<Iso3, S>,
+    twist: Twist3,
+}
+
+impl<S: Solver> System<S> for Body<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pose.deriv = self.twist;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pose);
+    }
+}
+
+/// The same body with separate position and orientation in the world frame.
+struct SplitBody<S: Solver> {
+    pos: Var<Vec3, S>,
+    rot: Var<Rot3, S>,
+    twist: Twist3,
+}
+
+impl<S: Solver> System<S> for SplitBody<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = self.rot.transform(self.twist.linear);
+        self.rot.deriv = self.rot.transform(self.twist.angular);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pos);
+        visitor.apply(&mut self.rot);
+    }
+}
+
+/// Test that constant twists are integrated exactly by any solver, in agreement
+/// with integration of separate position and orientation.
+#[test]
+fn test_iso_var_integration() {
+    let twist = Twist3::new(Vec3::new(1.0, 0.5, 0.0), Vec3::new(0.2, 0.0, 1.0));
+    let mut body = Body::<Euler> {
+        pose: Var::new(iso3()),
+        twist,
+    };
+    let mut split = SplitBody::<Rk4> {
+        pos: Var::new(iso3().pos),
+        rot: Var::new(iso3().rot),
+        twist,
+    };
+    let (mut t, mut t_split) = (0.0, 0.0);
+    for _ in 0..10 {
+        Euler.solve_step(&mut body, &mut t, 0.2);
+    }
+    for _ in 0..200 {
+        Rk4.solve_step(&mut split, &mut t_split, 0.01);
+    }
+
+    let exact = twist.exp(t).chain(iso3());
+    assert!(body.pose.pos.abs_diff_eq(exact.pos, 1e-5));
+    assert!(split.pos.abs_diff_eq(exact.pos, 1e-4));
+    assert!(
+        split
+            .rot
+            .transform(Vec3::X)
+            .abs_diff_eq(body.pose.rot.transform(Vec3::X), 1e-4)
+    );
+}
+
+/// Test double precision poses.
+#[test]
+fn test_diso3() {
+    let start = DIso3::new(DRot3::from_scaled_axis(DVec3::Y), DVec3::X);
+    let twist = DTwist3::new(DVec3::new(0.0, 1.0, 0.0), DVec3::new(0.0, 0.0, 1.0));
+    let mut pose = start;
+    for _ in 0..1000 {
+        pose.step(&twist, 1e-3);
+    }
+    let exact = twist.exp(1.0).chain(start);
+    assert!(pose.pos.abs_diff_eq(exact.pos, 1e-12));
+    let p = DVec3::new(1.0, 2.0, 3.0);
+    assert!(
+        pose.inverse()
+            .transform_point(pose.transform_point(p))
+            .abs_diff_eq(p, 1e-12)
+    );
+}
@@ -2,13 +2,62 @@
 //!
 //! This module contains unit and integration tests for all major components:
 //! - Param trait implementations
+//! - Error norms and tolerances
 //! - Var struct and its operations
-//! - Euler and RK4 solvers
+//! - Euler, RK4, Dormand–Prince and Verlet solvers
+//! - Symplectic splitting solvers
+//! - Butcher tableau solver and ready-made tableaus
+//! - Dense output of Runge–Kutta solvers
+//! - Implicit Euler solver for stiff systems
+//! - Zero-crossing event detection
+//! - Energy diagnostics of conservative systems
+//! - Checked stepping with detection of non-finite numbers
+//! - Integration loops and fixed time step simulation driver
+//! - Trajectory recording
 //! - Rotation types and utility functions
+//! - Rigid body pose types
+//! - Rigid body dynamics
+//! - Double precision integration
+//! - Dynamically sized state
+//! - Flattening state into vectors
+//! - Snapshots and rollback of complete state
+//! - Serialization of parameters, variables and solver storage
 //! - System trait examples
+//! - Systems with fallible evaluation of derivatives
 
+mod butcher;
+#[cfg(feature = "alloc")]
+mod check;
+mod dense;
+mod dopri5;
+#[cfg(feature = "alloc")]
+mod dynamic;
 mod euler;
+mod event;
+#[cfg(feature = "alloc")]
+mod flat;
+#[cfg(feature = "alloc")]
+mod hamiltonian;
+#[cfg(feature = "alloc")]
+mod implicit_euler;
+#[cfg(feature = "alloc")]
+mod integrate;
+mod iso;
+mod norm;
 mod param;
+mod precision;
+#[cfg(feature = "std")]
+mod record;
+mod rigid;
 mod rk4;
 mod rot;
+#[cfg(feature = "serde")]
+mod serde;
+mod simulation;
+#[cfg(feature = "alloc")]
+mod snapshot;
+#[cfg(feature = "alloc")]
+mod symplectic;
 mod system;
+mod try_system;
+mod verlet;
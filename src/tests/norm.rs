@@ -0,0 +1,177 @@
+//! Tests for error norms and tolerances.
+
+use crate::{
+    AdaptiveSolver, Dopri5, ErrorNorm, Euler, Norm, Rot3, Solver, System, Tolerance, Var, Visitor,
+};
+use glam::Vec3;
+
+/// System whose derivatives are used as error estimates.
+struct ErrorSystem<S: Solver> {
+    x: Var<f32, S>,
+    v: Var<Vec3, S>,
+    r: Var<Rot3, S>,
+}
+
+impl<S: Solver> System<S> for ErrorSystem<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {}
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+        visitor.apply(&mut self.v);
+        visitor.apply(&mut self.r);
+    }
+}
+
+fn error_system() -> ErrorSystem<Euler> {
+    let mut system = ErrorSystem {
+        x: Var::new(3.0),
+        v: Var::new(Vec3::ZERO),
+        r: Var::new(Rot3::default()),
+    };
+    system.x.deriv = 0.4;
+    system.v.deriv = Vec3::new(0.0, 0.3, 0.0);
+    system.r.deriv = Vec3::new(0.0, 0.0, -0.1);
+    system
+}
+
+/// Test tolerance scaling.
+#[test]
+fn test_tolerance_scale() {
+    let tol = Tolerance::new(0.1f32, 0.01);
+    assert!((tol.scale(0.0) - 0.1).abs() < 1e-6);
+    assert!((tol.scale(10.0) - 0.2).abs() < 1e-6);
+
+    // Conversion between precisions
+    let tol = Tolerance::new(1e-12f64, 1e-10).cast::<f32>();
+    assert_eq!(tol, Tolerance::new(1e-12, 1e-10));
+}
+
+/// Test that purely relative tolerances are refused.
+#[test]
+#[should_panic(expected = "Absolute tolerance must be positive")]
+fn test_tolerance_relative_only() {
+    Tolerance::new(0.0f32, 1e-4);
+}
+
+/// Test that errors of double precision variables are scaled and summed in double precision.
+#[test]
+fn test_error_norm_f64() {
+    let mut norm = ErrorNorm::new(Norm::Rms, Tolerance::new(1e-12, 0.0));
+    norm.add(&1.0f64, &1e-12, None);
+    norm.add(&1.0f64, &3e-24, Some(Tolerance::new(1e-24, 0.0)));
+    // Out of range of `f32`
+    norm.add(&0.0f64, &2e-300, Some(Tolerance::new(1e-300, 0.0)));
+    let expected = ((1.0f64 + 9.0 + 4.0) / 3.0).sqrt();
+    assert!((norm.value() - expected).abs() < 1e-12);
+}
+
+/// Test maximum norm of scaled errors.
+#[test]
+fn test_error_norm_max() {
+    let mut system = error_system();
+    let mut norm = ErrorNorm::new(Norm::Max, Tolerance::new(0.1, 0.1));
+    system.visit_vars(&mut norm);
+
+    // x: 0.4 / (0.1 + 0.1 * 3.0) = 1.0, v: 0.3 / 0.1 = 3.0, r: 0.1 / (0.1 + 0.1) = 0.5
+    assert!((norm.value() - 3.0).abs() < 1e-5);
+
+    // Error estimates are consumed
+    assert_eq!(system.x.deriv, 0.0);
+    assert_eq!(system.v.deriv, Vec3::ZERO);
+    assert_eq!(system.r.deriv, Vec3::ZERO);
+}
+
+/// Test RMS norm of scaled errors over all components.
+#[test]
+fn test_error_norm_rms() {
+    let mut system = error_system();
+    let mut norm = ErrorNorm::new(Norm::Rms, Tolerance::new(0.1, 0.1));
+    system.visit_vars(&mut norm);
+
+    // (1.0^2 + 3.0^2 + 0.5^2) / (1 + 3 + 3) components
+    let expected = ((1.0f32 + 9.0 + 0.25) / 7.0).sqrt();
+    assert!((norm.value() - expected).abs() < 1e-5);
+
+    // Empty norm is zero
+    assert_eq!(
+        ErrorNorm::<f32>::new(Norm::Rms, Tolerance::default()).value(),
+        0.0
+    );
+}
+
+/// Test that per-variable tolerance overrides the default one.
+#[test]
+fn test_error_norm_var_tolerance() {
+    let mut system = error_system();
+    system.v = system.v.with_tolerance(Tolerance::new(0.3, 0.0));
+    system.v.deriv = Vec3::new(0.0, 0.3, 0.0);
+
+    let mut norm = ErrorNorm::new(Norm::Max, Tolerance::new(0.1, 0.1));
+    system.visit_vars(&mut norm);
+    assert!((norm.value() - 1.0).abs() < 1e-5);
+}
+
+/// Test that NaN errors are not hidden by the maximum norm.
+#[test]
+fn test_error_norm_nan() {
+    let mut system = error_system();
+    system.x.deriv = f32::NAN;
+
+    let mut norm = ErrorNorm::new(Norm::Max, Tolerance::default());
+    system.visit_vars(&mut norm);
+    assert!(norm.value().is_nan());
+}
+
+/// Harmonic oscillator for adaptive integration.
+struct Oscillator<S: Solver> {
+    x: Var<f32, S>,
+    v: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for Oscillator<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = *self.v;
+        self.v.deriv = -*self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+        visitor.apply(&mut self.v);
+    }
+}
+
+/// Count adaptive steps needed to integrate the oscillator over a period.
+fn count_steps(solver: &Dopri5, mut system: Oscillator<Dopri5>) -> usize {
+    let total_time = 2.0 * core::f32::consts::PI;
+    let (mut t, mut dt, mut steps) = (0.0, 0.1f32, 0);
+    while t < total_time {
+        dt = dt.min(total_time - t);
+        let step = solver.adaptive_step(&mut system, &mut t, dt);
+        dt = step.next;
+        steps += 1;
+    }
+    steps
+}
+
+/// Test that a tighter per-variable tolerance makes adaptive solver take more steps.
+#[test]
+fn test_dopri5_var_tolerance() {
+    let solver = Dopri5::new(Tolerance::new(1e-3, 0.0));
+    let loose = Oscillator {
+        x: Var::new(1.0),
+        v: Var::new(0.0),
+    };
+    let tight = Oscillator {
+        x: Var::new(1.0).with_tolerance(Tolerance::new(1e-6, 0.0)),
+        v: Var::new(0.0),
+    };
+
+    let loose_steps = count_steps(&solver, loose);
+    let tight_steps = count_steps(&solver, tight);
+    assert!(
+        tight_steps > loose_steps,
+        "tight: {}, loose: {}",
+        tight_steps,
+        loose_steps
+    );
+}
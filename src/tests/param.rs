@@ -1,6 +1,6 @@
 //! Tests for the `Param` trait implementations.
 
-use crate::{Param, Rot2, Rot3};
+use crate::{Deriv, Param, Rot2, Rot3};
 use glam::{Vec2, Vec3};
 
 /// Test basic scalar integration for `f32`.
@@ -117,3 +117,153 @@ fn test_rot3_step() {
     let rotated = result.transform(test_vector);
     assert!((rotated.length() - test_vector.length()).abs() < 1e-6);
 }
+
+/// Test derivative norms, dimensions and subtraction.
+#[test]
+fn test_deriv_norm() {
+    let mut x = -3.0f32;
+    assert!((x.norm() - 3.0).abs() < 1e-6);
+    assert_eq!(x.dim(), 1);
+    x -= &1.0;
+    assert!((x - -4.0).abs() < 1e-6);
+
+    let mut v = Vec2::new(3.0, 4.0);
+    assert!((v.norm() - 5.0).abs() < 1e-6);
+    assert_eq!(v.dim(), 2);
+    v -= &Vec2::new(3.0, 4.0);
+    assert!(v.norm() < 1e-6);
+
+    let mut w = Vec3::new(1.0, 2.0, 2.0);
+    assert!((w.norm() - 3.0).abs() < 1e-6);
+    assert_eq!(w.dim(), 3);
+    w -= &Vec3::X;
+    assert!((w - Vec3::new(0.0, 2.0, 2.0)).length() < 1e-6);
+}
+
+/// Test scaling and accumulation of derivatives.
+#[test]
+fn test_deriv_scale_add() {
+    let mut x = 2.0f32;
+    x.scale(3.0);
+    x.add_scaled(&1.0, -2.0);
+    assert_eq!(x, 4.0);
+
+    let mut v = Vec2::new(1.0, 2.0);
+    v.scale(2.0);
+    v.add_scaled(&Vec2::new(1.0, -1.0), 0.5);
+    assert_eq!(v, Vec2::new(2.5, 3.5));
+}
+
+/// Test that arrays of parameters are stepped element-wise.
+#[test]
+fn test_array_param() {
+    let mut a = [Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0), Vec2::ZERO];
+    let da = [Vec2::ONE, Vec2::new(2.0, 0.0), Vec2::new(0.0, -4.0)];
+    a.step(&da, 0.5);
+    assert_eq!(
+        a,
+        [
+            Vec2::new(1.5, 0.5),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, -2.0)
+        ]
+    );
+    assert!((a.magnitude() - (2.5f32 + 2.0 + 4.0).sqrt()).abs() < 1e-6);
+
+    let mut rots = [Rot2::from_angle(0.5); 2];
+    rots.step(&[1.0, -1.0], 0.5);
+    assert!((rots[0].angle() - 1.0).abs() < 1e-6);
+    assert!(rots[1].angle().abs() < 1e-6);
+
+    let mut d = da;
+    d.scale(2.0);
+    d.add_scaled(&da, -1.0);
+    assert_eq!(d, da);
+    assert!((d.norm() - (2.0f32 + 4.0 + 16.0).sqrt()).abs() < 1e-6);
+    assert_eq!(d.dim(), 6);
+    let components: Vec<f32> = (0..d.dim()).map(|i| d.component(i)).collect();
+    assert_eq!(components, [1.0, 1.0, 2.0, 0.0, 0.0, -4.0]);
+    *d.component_mut(3) = 7.0;
+    assert_eq!(d[1], Vec2::new(2.0, 7.0));
+
+    // Larger arrays fit into a single variable
+    let mut chain = [Vec2::ZERO; 32];
+    chain.step(&[Vec2::X; 32], 1.0);
+    assert_eq!(chain, [Vec2::X; 32]);
+}
+
+/// Test that tuples of parameters with different types are stepped element-wise.
+#[test]
+fn test_tuple_param() {
+    let mut p = (1.0f32, Vec3::Z, Rot3::default());
+    let dp = (2.0, Vec3::X, Vec3::Z * core::f32::consts::PI);
+    p.step(&dp, 0.5);
+    assert_eq!(p.0, 2.0);
+    assert_eq!(p.1, Vec3::new(0.5, 0.0, 1.0));
+    assert!(p.2.transform(Vec3::X).abs_diff_eq(Vec3::Y, 1e-6));
+    let expected = (4.0f32 + 1.25 + 1.0).sqrt();
+    assert!((p.magnitude() - expected).abs() < 1e-6);
+
+    let mut d = dp;
+    d.add_scaled(&dp, 1.0);
+    d.scale(0.5);
+    assert_eq!(d, dp);
+    assert_eq!(d.dim(), 7);
+    let components: Vec<f32> = (0..d.dim()).map(|i| d.component(i)).collect();
+    assert_eq!(components[..4], [2.0, 1.0, 0.0, 0.0]);
+    *d.component_mut(0) = -1.0;
+    *d.component_mut(6) = 1.0;
+    assert_eq!((d.0, d.2.z), (-1.0, 1.0));
+
+    let mut single = (Vec2::X,);
+    single.step(&(Vec2::Y,), 2.0);
+    assert_eq!(single.0, Vec2::new(1.0, 2.0));
+}
+
+/// Test parameter magnitudes used for relative tolerances.
+#[test]
+fn test_param_magnitude() {
+    assert!(((-2.0f32).magnitude() - 2.0).abs() < 1e-6);
+    assert!((Vec2::new(3.0, 4.0).magnitude() - 5.0).abs() < 1e-6);
+    assert!((Vec3::new(1.0, 2.0, 2.0).magnitude() - 3.0).abs() < 1e-6);
+    assert!((Rot2::from_angle(1.0).magnitude() - 1.0).abs() < 1e-6);
+    assert!((Rot3::default().magnitude() - 1.0).abs() < 1e-6);
+}
+
+/// Test access to scalar components of derivatives.
+#[test]
+fn test_deriv_components() {
+    let mut x = 2.0f32;
+    assert_eq!(x.component(0), 2.0);
+    *x.component_mut(0) += 1.0;
+    assert_eq!(x, 3.0);
+
+    let mut v = Vec3::new(1.0, 2.0, 3.0);
+    let components: Vec<f32> = (0..v.dim()).map(|i| v.component(i)).collect();
+    assert_eq!(components, [1.0, 2.0, 3.0]);
+    *v.component_mut(1) = 5.0;
+    assert_eq!(v, Vec3::new(1.0, 5.0, 3.0));
+}
+
+/// Test subtraction of derivatives, including derivatives of different length.
+#[test]
+fn test_deriv_sub_assign() {
+    let mut x = 3.0f32;
+    x.sub_assign(&1.5);
+    assert_eq!(x, 1.5);
+
+    let mut v = Vec3::new(1.0, 2.0, 3.0);
+    Deriv::sub_assign(&mut v, &Vec3::new(0.5, 2.0, 4.0));
+    assert_eq!(v, Vec3::new(0.5, 0.0, -1.0));
+
+    let mut pair = (1.0f32, Vec2::new(1.0, 1.0));
+    pair.sub_assign(&(2.0, Vec2::new(0.0, 1.0)));
+    assert_eq!(pair, (-1.0, Vec2::new(1.0, 0.0)));
+
+    #[cfg(feature = "alloc")]
+    {
+        let mut d = alloc::vec![1.0f32];
+        d.sub_assign(&alloc::vec![1.0, 2.0]);
+        assert_eq!(d, alloc::vec![0.0, -2.0]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Tests for double precision integration.
+
+use crate::{
+    AdaptiveSolver, DRot2, DRot3, Deriv, Dopri5, Event, EventLocator, EventSystem, Param, Rk4,
+    Solver, System, Tolerance, Var, Verlet, Visitor,
+};
+use core::f64::consts::PI;
+use glam::{DVec2, DVec3};
+
+/// Body on a circular orbit around the origin: a = -r / |r|^3
+#[derive(Clone)]
+struct Orbit<S: Solver<f64>> {
+    pos: Var<DVec2, S>,
+    vel: Var<DVec2, S>,
+}
+
+impl<S: Solver<f64>> Orbit<S> {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(DVec2::X),
+            vel: Var::new(DVec2::Y),
+        }
+    }
+}
+
+impl<S: Solver<f64>> System<S, f64> for Orbit<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+    }
+
+    fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+impl<S: Solver<f64>> EventSystem<S, f64> for Orbit<S> {
+    fn event_count(&self) -> usize {
+        1
+    }
+
+    fn event(&self, _: usize, _: f64) -> Event<f64> {
+        // Crossing of the positive x half-axis from below
+        Event::new(self.pos.y).rising().terminal()
+    }
+}
+
+/// The same orbit in single precision.
+struct Orbit32<S: Solver> {
+    pos: Var<glam::Vec2, S>,
+    vel: Var<glam::Vec2, S>,
+}
+
+impl<S: Solver> System<S> for Orbit32<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.pos);
+        visitor.apply(&mut self.vel);
+    }
+}
+
+/// Test double precision parameter implementations.
+#[test]
+fn test_f64_params() {
+    let mut x = 1.0f64;
+    x.step(&2.0, 0.25);
+    assert_eq!(x, 1.5);
+    assert_eq!(x.magnitude(), 1.5);
+
+    let mut v = DVec3::new(1.0, 2.0, 3.0);
+    Param::step(&mut v, &DVec3::ONE, 0.5);
+    assert_eq!(v, DVec3::new(1.5, 2.5, 3.5));
+    assert_eq!(DVec3::new(3.0, 0.0, 4.0).norm(), 5.0);
+    assert_eq!(v.dim(), 3);
+    assert_eq!(v.component(1), 2.5);
+
+    let mut rot2 = DRot2::from_angle(0.5);
+    rot2.step(&2.0, 3.0 * PI);
+    assert!((rot2.angle() - 0.5).abs() < 1e-12);
+
+    let mut rot3 = DRot3::default();
+    rot3.step(&(DVec3::Z * PI), 0.5);
+    let rotated = rot3.transform(DVec3::X);
+    assert!(rotated.abs_diff_eq(DVec3::Y, 1e-15));
+    assert!(
+        rot3.chain(rot3.inverse())
+            .transform(DVec3::X)
+            .abs_diff_eq(DVec3::X, 1e-15)
+    );
+}
+
+/// Test that double precision keeps accuracy where single precision does not.
+#[test]
+fn test_rk4_f64_orbit() {
+    let dt = 1e-3;
+    let steps = 20000;
+
+    let mut orbit = Orbit::<Rk4>::new();
+    let mut t = 0.0f64;
+    for _ in 0..steps {
+        Rk4.solve_step(&mut orbit, &mut t, dt);
+    }
+    let expected = DVec2::new(t.cos(), t.sin());
+    let error = (*orbit.pos - expected).length();
+    assert!(error < 1e-10, "Error too large: {}", error);
+
+    let mut orbit32 = Orbit32::<Rk4> {
+        pos: Var::new(glam::Vec2::X),
+        vel: Var::new(glam::Vec2::Y),
+    };
+    let mut t32 = 0.0f32;
+    for _ in 0..steps {
+        Rk4.solve_step(&mut orbit32, &mut t32, dt as f32);
+    }
+    let error32 = (orbit32.pos.as_dvec2() - expected).length();
+    assert!(error32 > 1e3 * error, "f32: {}, f64: {}", error32, error);
+}
+
+/// Test adaptive Dormand–Prince with tolerances beyond single precision.
+#[test]
+fn test_dopri5_f64_tight_tolerance() {
+    let solver = Dopri5::new(Tolerance::new(1e-12, 1e-12));
+    let mut orbit = Orbit::<Dopri5>::new();
+
+    let (mut t, mut dt) = (0.0f64, 0.1f64);
+    while t < 2.0 * PI {
+        dt = dt.min(2.0 * PI - t);
+        dt = solver.adaptive_step(&mut orbit, &mut t, dt).next;
+    }
+    assert!((t - 2.0 * PI).abs() < 1e-12);
+    let error = (*orbit.pos - DVec2::X).length();
+    assert!(error < 1e-9, "Error too large: {}", error);
+}
+
+/// Test symplectic Verlet in double precision over many orbits.
+#[test]
+fn test_verlet_f64_orbit() {
+    let mut orbit = Orbit::<Verlet>::new();
+    let mut t = 0.0f64;
+    for _ in 0..100000 {
+        Verlet.solve_step(&mut orbit, &mut t, 1e-3);
+    }
+    assert!((orbit.pos.length() - 1.0).abs() < 1e-6);
+}
+
+/// Test event location with double precision time.
+#[test]
+fn test_event_f64_period() {
+    let mut orbit = Orbit::<Rk4>::new();
+    // Move a bit past the start, so that the crossing at t = 0 is not detected
+    let mut t = 0.0f64;
+    Rk4.solve_step(&mut orbit, &mut t, 0.01);
+
+    let mut locator = EventLocator::new(1e-6);
+    let hit = locator
+        .integrate(&Rk4, &mut orbit, &mut t, 10.0, 0.01)
+        .unwrap();
+    assert!((hit.time - 2.0 * PI).abs() < 1e-6);
+    assert!(orbit.pos.y >= 0.0 && orbit.pos.y < 1e-6);
+}
+
+/// Body rotating with constant angular velocity in double precision.
+struct Spinner<S: Solver<f64>> {
+    rot2: Var<DRot2, S>,
+    rot3: Var<DRot3, S>,
+}
+
+impl<S: Solver<f64>> System<S, f64> for Spinner<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.rot2.deriv = 1.0;
+        self.rot3.deriv = DVec3::new(0.0, 0.0, 1.0);
+    }
+
+    fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.rot2);
+        visitor.apply(&mut self.rot3);
+    }
+}
+
+/// Test that double precision rotations keep precision over a long run.
+#[test]
+fn test_f64_rotations_long_run() {
+    let mut system = Spinner::<Rk4> {
+        rot2: Var::default(),
+        rot3: Var::default(),
+    };
+    let mut t = 0.0f64;
+    for _ in 0..100000 {
+        Rk4.solve_step(&mut system, &mut t, 0.01);
+    }
+    let expected = t.rem_euclid(2.0 * PI);
+    assert!((system.rot2.angle() - expected).abs() < 1e-9);
+    let rotated = system.rot3.transform(DVec3::X);
+    assert!(rotated.abs_diff_eq(DVec3::new(t.cos(), t.sin(), 0.0), 1e-9));
+}
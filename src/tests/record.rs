@@ -0,0 +1,216 @@
+//! Tests for trajectory recording.
+
+use crate::{Euler, Recorder, Rk4, Rk4Dense, Rot2, Rot3, Solver, System, Var, Verlet, Visitor};
+use core::f32::consts::PI;
+use glam::{DVec2, Quat, Vec3};
+
+/// Oscillator moving along a circle, with a spinning orientation.
+#[derive(Clone)]
+struct Spinner<S: Solver> {
+    pos: Var<Vec3, S>,
+    vel: Var<Vec3, S>,
+    rot: Var<Rot3, S>,
+}
+
+impl<S: Solver> Spinner<S> {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(Vec3::X),
+            vel: Var::new(Vec3::Y),
+            rot: Var::default(),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Spinner<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos;
+        self.rot.deriv = Vec3::Z;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+        visitor.apply(&mut self.rot);
+    }
+}
+
+fn recorder<S: Solver + 'static>() -> Recorder<Spinner<S>> {
+    Recorder::new()
+        .column("pos", |s: &Spinner<S>| *s.pos)
+        .column("rot", |s: &Spinner<S>| *s.rot)
+        .column("energy", |s: &Spinner<S>| {
+            0.5 * (s.pos.length_squared() + s.vel.length_squared())
+        })
+}
+
+/// Flattened values of the recorded columns.
+fn row<S: Solver>(system: &Spinner<S>) -> Vec<f64> {
+    let mut out: Vec<f64> = system.pos.to_array().map(f64::from).into();
+    out.extend(Quat::from(*system.rot).to_array().map(f64::from));
+    out.push(0.5 * (system.pos.length_squared() + system.vel.length_squared()) as f64);
+    out
+}
+
+/// Test that values are flattened into component columns.
+#[test]
+fn test_columns() {
+    let mut recorder = recorder();
+    assert!(recorder.trajectory().columns().is_empty());
+    let mut system = Spinner::<Rk4>::new();
+    *system.rot = Rot3::from_scaled_axis(Vec3::Z * PI);
+    recorder.sample(0.0, &system);
+    assert_eq!(
+        recorder.trajectory().columns(),
+        [
+            "pos.0", "pos.1", "pos.2", "rot.0", "rot.1", "rot.2", "rot.3", "energy"
+        ]
+    );
+    let row = recorder.trajectory().row(0);
+    assert!((row[5] - 1.0).abs() < 1e-6 && row[6].abs() < 1e-6);
+
+    // Rotations in 2D are angles, and dynamically sized values are sized by the first sample
+    let mut recorder = Recorder::<(Rot2, Vec<f32>)>::new()
+        .column("angle", |s: &(Rot2, Vec<f32>)| s.0)
+        .column("v", |s: &(Rot2, Vec<f32>)| s.1.clone());
+    recorder.sample(0.0, &(Rot2::from_angle(0.5), vec![1.0, 2.0]));
+    assert_eq!(recorder.trajectory().columns(), ["angle", "v.0", "v.1"]);
+    assert_eq!(recorder.trajectory().row(0), [0.5, 1.0, 2.0]);
+}
+
+/// Test that a column changing its number of components is refused.
+#[test]
+#[should_panic(expected = "Number of components of a column has changed")]
+fn test_columns_resized() {
+    let mut recorder = Recorder::<Vec<f32>>::new().column("v", |v: &Vec<f32>| v.clone());
+    recorder.sample(0.0, &vec![1.0, 2.0]);
+    recorder.sample(0.1, &vec![1.0]);
+}
+
+/// Test dense sampling at output times which are not aligned with steps.
+#[test]
+fn test_record_output_times() {
+    let mut system = Spinner::<Rk4Dense>::new();
+    let mut recorder = recorder();
+    let steps = recorder.record_dense(&mut system, &Rk4Dense, 0.0, 1.0, 0.03, 0.1);
+    assert_eq!(steps, 34);
+
+    let trajectory = recorder.into_trajectory();
+    assert_eq!(trajectory.len(), 11);
+    for (k, &t) in trajectory.times().iter().enumerate() {
+        assert!((t - 0.1 * k as f64).abs() < 1e-6);
+        let row = trajectory.row(k);
+        assert!((row[0] - t.cos()).abs() < 1e-4, "t={}", t);
+        assert!((row[1] - t.sin()).abs() < 1e-4, "t={}", t);
+        // Rotation about z by angle t
+        assert!((row[5] - (0.5 * t).sin()).abs() < 1e-4, "t={}", t);
+        assert!((row[7] - 1.0).abs() < 1e-4);
+    }
+    assert_eq!(*trajectory.times().last().unwrap(), 1.0);
+    assert_eq!(trajectory.row(10), row(&system));
+}
+
+/// Test sampling at step points with solvers without dense output.
+#[test]
+fn test_record_steps() {
+    let mut system = Spinner::<Verlet>::new();
+    let mut verlet = recorder();
+    let steps = verlet.record(&mut system, &Verlet, 0.0, 1.05, 0.03, 0.1);
+    // Four steps per output interval and one to the end
+    assert_eq!(steps, 42);
+
+    let trajectory = verlet.into_trajectory();
+    assert_eq!(trajectory.len(), 11);
+    for (k, &t) in trajectory.times().iter().enumerate() {
+        assert!((t - 0.1 * k as f64).abs() < 1e-6);
+        let row = trajectory.row(k);
+        assert!((row[0] - t.cos()).abs() < 1e-3, "t={}", t);
+        assert!((row[1] - t.sin()).abs() < 1e-3, "t={}", t);
+    }
+    assert!((system.pos.x - 1.05f32.cos()).abs() < 1e-3);
+
+    // Samples are the states reached at the ends of steps
+    let mut system = Spinner::<Euler>::new();
+    let mut euler = recorder();
+    euler.record(&mut system, &Euler, 0.0, 0.5, 0.1, 0.5);
+    assert_eq!(euler.trajectory().row(1), row(&system));
+}
+
+/// Test time-indexed access to samples.
+#[test]
+fn test_trajectory_access() {
+    let mut recorder = Recorder::<DVec2, f64>::new().column("p", |p: &DVec2| *p);
+    recorder.sample(0.0, &DVec2::new(0.0, 1.0));
+    recorder.sample(0.5, &DVec2::new(1.0, 3.0));
+    recorder.sample(1.5, &DVec2::new(3.0, 2.0));
+    let trajectory = recorder.trajectory();
+
+    assert_eq!(trajectory.index_at(-0.1), None);
+    assert_eq!(trajectory.index_at(0.0), Some(0));
+    assert_eq!(trajectory.index_at(0.7), Some(1));
+    assert_eq!(trajectory.index_at(2.0), Some(2));
+
+    assert_eq!(trajectory.at(0.25), Some(vec![0.5, 2.0]));
+    assert_eq!(trajectory.at(1.0), Some(vec![2.0, 2.5]));
+    assert_eq!(trajectory.at(1.5), Some(vec![3.0, 2.0]));
+    assert_eq!(trajectory.at(1.6), None);
+    assert_eq!(trajectory.at(-1.0), None);
+
+    let y: Vec<f64> = trajectory.column("p.1").unwrap().collect();
+    assert_eq!(y, [1.0, 3.0, 2.0]);
+    assert!(trajectory.column("q").is_none());
+}
+
+/// Test CSV and JSON Lines output.
+#[test]
+fn test_write() {
+    let mut recorder = Recorder::<(f32, Vec3)>::new()
+        .column("a", |s: &(f32, Vec3)| s.0)
+        .column("b", |s: &(f32, Vec3)| s.1);
+    recorder.sample(0.0, &(0.1, Vec3::new(1.0, -2.5, 3.0)));
+    recorder.sample(0.1, &(f32::NAN, Vec3::ZERO));
+
+    let mut csv = Vec::new();
+    recorder.trajectory().write_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "t,a,b.0,b.1,b.2\n0,0.1,1,-2.5,3\n0.1,NaN,0,0,0\n"
+    );
+
+    let mut jsonl = Vec::new();
+    recorder.trajectory().write_jsonl(&mut jsonl).unwrap();
+    assert_eq!(
+        String::from_utf8(jsonl).unwrap(),
+        "{\"t\":0,\"a\":0.1,\"b.0\":1,\"b.1\":-2.5,\"b.2\":3}\n\
+         {\"t\":0.1,\"a\":null,\"b.0\":0,\"b.1\":0,\"b.2\":0}\n"
+    );
+
+    // Double precision values and escaped column names
+    let mut recorder = Recorder::<f64, f64>::new().column("x\"", |x: &f64| *x);
+    recorder.sample(0.1, &0.100000000001);
+    let mut jsonl = Vec::new();
+    recorder.trajectory().write_jsonl(&mut jsonl).unwrap();
+    assert_eq!(
+        String::from_utf8(jsonl).unwrap(),
+        "{\"t\":0.1,\"x\\\"\":0.100000000001}\n"
+    );
+
+    let mut csv = Vec::new();
+    recorder.trajectory().write_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "t,\"x\"\"\"\n0.1,0.100000000001\n"
+    );
+
+    let mut recorder = Recorder::<f32>::new()
+        .column("a,b", |x: &f32| *x)
+        .column("c\nd", |x: &f32| *x)
+        .column("e f", |x: &f32| *x);
+    recorder.sample(0.0, &1.0);
+    let mut csv = Vec::new();
+    recorder.trajectory().write_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "t,\"a,b\",\"c\nd\",e f\n0,1,1,1\n"
+    );
+}
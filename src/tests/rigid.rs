@@ -0,0 +1,215 @@
+//! Tests for rigid body dynamics.
+
+use crate::{
+    DRot3, Euler, Rk4, Rot2, Rot3, Solver, System, Verlet,
+    rigid::{DRigidBody3, RigidBody2, RigidBody3},
+};
+use glam::{DMat3, DVec3, Mat3, Vec2, Vec3};
+
+fn integrate<S: Solver + Default, Y: System<S>>(system: &mut Y, dt: f32, steps: usize) -> f32 {
+    let solver = S::default();
+    let mut t = 0.0;
+    for _ in 0..steps {
+        solver.solve_step(system, &mut t, dt);
+    }
+    t
+}
+
+/// Test torque-free precession of a symmetric top against the analytic solution.
+#[test]
+fn test_symmetric_top_precession() {
+    let (i1, i3) = (1.0, 2.0);
+    let omega = Vec3::new(0.3, 0.0, 1.0);
+    let mut body = RigidBody3::<Rk4>::new(1.0, Mat3::from_diagonal(Vec3::new(i1, i1, i3)))
+        .with_angular_velocity(omega);
+    let momentum = *body.angular_momentum;
+    let energy = body.kinetic_energy();
+
+    let t = integrate(&mut body, 0.01, 500);
+
+    // Symmetry axis precesses around angular momentum with rate |L|/I1
+    let precession = Rot3::from_scaled_axis(momentum.normalize() * momentum.length() / i1 * t);
+    let axis = body.rot.transform(Vec3::Z);
+    assert!(axis.abs_diff_eq(precession.transform(Vec3::Z), 1e-4));
+
+    // In the body frame angular velocity rotates around the symmetry axis
+    // with rate (I3 - I1)/I1 * ω3
+    let rate = (i3 - i1) / i1 * omega.z;
+    let body_omega = body.rot.world_to_body(body.angular_velocity());
+    let expected = Vec3::new(
+        omega.x * (rate * t).cos(),
+        omega.x * (rate * t).sin(),
+        omega.z,
+    );
+    assert!(body_omega.abs_diff_eq(expected, 1e-4));
+
+    assert_eq!(*body.angular_momentum, momentum);
+    assert!((body.kinetic_energy() - energy).abs() < 1e-5);
+}
+
+/// Integrate free rotation and find the smallest projection of a body axis onto the
+/// direction of angular momentum.
+fn min_alignment(inertia: Vec3, omega: Vec3, axis: Vec3) -> (f32, RigidBody3<Rk4>) {
+    let mut body =
+        RigidBody3::<Rk4>::new(1.0, Mat3::from_diagonal(inertia)).with_angular_velocity(omega);
+    let direction = body.angular_momentum.normalize();
+    let mut t = 0.0;
+    let mut min = 1.0f32;
+    for _ in 0..2000 {
+        Rk4.solve_step(&mut body, &mut t, 0.005);
+        min = min.min(body.rot.transform(axis).dot(direction));
+    }
+    (min, body)
+}
+
+/// Test the Dzhanibekov effect: rotation about the intermediate principal axis is
+/// unstable and the body periodically flips, while rotation about the other axes is stable.
+#[test]
+fn test_intermediate_axis_flip() {
+    let inertia = Vec3::new(1.0, 2.0, 3.0);
+    let perturbation = Vec3::splat(0.01);
+
+    let omega = 2.0 * Vec3::Y + perturbation;
+    let (min, body) = min_alignment(inertia, omega, Vec3::Y);
+    assert!(min < -0.99, "no flip, min alignment {}", min);
+    // Momentum and energy are conserved through the flip
+    let start =
+        RigidBody3::<Rk4>::new(1.0, Mat3::from_diagonal(inertia)).with_angular_velocity(omega);
+    assert_eq!(*body.angular_momentum, *start.angular_momentum);
+    assert!((body.kinetic_energy() - start.kinetic_energy()).abs() < 1e-3);
+
+    for axis in [Vec3::X, Vec3::Z] {
+        let (min, _) = min_alignment(inertia, 2.0 * axis + perturbation, axis);
+        assert!(min > 0.99, "axis {} is unstable", axis);
+    }
+}
+
+/// Test that forces applied at points produce torque, and that velocities of body
+/// points agree with their motion.
+#[test]
+fn test_rigid3_forces() {
+    let mut body = RigidBody3::<Rk4>::new(2.0, Mat3::from_diagonal(Vec3::new(1.0, 2.0, 3.0)))
+        .with_pos(Vec3::new(1.0, 0.0, 0.0))
+        .with_rot(Rot3::from_scaled_axis(Vec3::new(0.3, 0.2, -0.1)));
+    body.apply_force_at(Vec3::Z, Vec3::new(1.0, 1.0, 0.0));
+    body.apply_force_at(-Vec3::Z, Vec3::new(1.0, -1.0, 0.0));
+    body.apply_torque(Vec3::Z);
+    assert_eq!(body.force, Vec3::ZERO);
+    assert_eq!(body.torque, Vec3::new(2.0, 0.0, 1.0));
+
+    body.apply_force(Vec3::X);
+    let mut t = 0.0;
+    Rk4.solve_step(&mut body, &mut t, 0.5);
+    assert_eq!(*body.momentum, Vec3::new(0.5, 0.0, 0.0));
+    assert_eq!(*body.angular_momentum, Vec3::new(1.0, 0.0, 0.5));
+    assert!(body.velocity().abs_diff_eq(Vec3::new(0.25, 0.0, 0.0), 1e-6));
+
+    body.clear_forces();
+    assert_eq!((body.force, body.torque), (Vec3::ZERO, Vec3::ZERO));
+
+    // Velocity of a body point by central difference of its position
+    let local = Vec3::new(0.5, -1.0, 2.0);
+    let point = |body: &RigidBody3<Rk4>| *body.pos + body.rot.transform(local);
+    let velocity = body.point_velocity(point(&body));
+    let h = 1e-3;
+    // Free motion is reversible, so reversed momenta move the body backwards in time
+    let copy = |sign: f32| {
+        let mut copy = RigidBody3::<Rk4>::new(body.mass, body.inertia())
+            .with_pos(*body.pos)
+            .with_rot((*body.rot).into());
+        *copy.momentum = sign * *body.momentum;
+        *copy.angular_momentum = sign * *body.angular_momentum;
+        copy
+    };
+    let (mut ahead, mut behind) = (copy(1.0), copy(-1.0));
+    integrate(&mut ahead, h, 1);
+    integrate(&mut behind, h, 1);
+    let difference = (point(&ahead) - point(&behind)) / (2.0 * h);
+    assert!(velocity.abs_diff_eq(difference, 1e-3));
+
+    // Inertia in the world frame maps angular velocity to angular momentum
+    assert!(
+        (body.world_inertia() * body.angular_velocity()).abs_diff_eq(*body.angular_momentum, 1e-5)
+    );
+}
+
+/// Test a planar body spun up by a constant force applied off its center.
+#[test]
+fn test_rigid2_constant_force() {
+    let (mass, inertia) = (2.0, 0.5);
+    let mut body = RigidBody2::<Verlet>::new(mass, inertia)
+        .with_pos(Vec2::new(1.0, 1.0))
+        .with_rot(Rot2::from_angle(0.1));
+    body.apply_force_at(Vec2::Y, Vec2::new(2.0, 1.0));
+    assert_eq!(body.torque, 1.0);
+
+    let t = integrate(&mut body, 0.01, 100);
+    assert!(body.velocity().abs_diff_eq(Vec2::new(0.0, t / mass), 1e-5));
+    assert!(
+        body.pos
+            .abs_diff_eq(Vec2::new(1.0, 1.0 + 0.5 * t * t / mass), 1e-5)
+    );
+    assert!((body.angular_velocity() - t / inertia).abs() < 1e-5);
+    let angle = 0.1 + 0.5 * t * t / inertia;
+    assert!((body.rot.angle() - Rot2::from_angle(angle).angle()).abs() < 1e-4);
+
+    // All work of the force is converted to kinetic energy
+    let work = 0.5 * t * t / mass + 0.5 * t * t / inertia;
+    assert!((body.kinetic_energy() - work).abs() < 1e-4);
+
+    let velocity = body.point_velocity(*body.pos + Vec2::X);
+    assert!(velocity.abs_diff_eq(body.velocity() + body.angular_velocity() * Vec2::Y, 1e-6));
+
+    let spinning = RigidBody2::<Euler>::new(mass, inertia)
+        .with_velocity(Vec2::X)
+        .with_angular_velocity(3.0);
+    assert_eq!(*spinning.momentum, Vec2::new(2.0, 0.0));
+    assert_eq!(*spinning.angular_momentum, 1.5);
+}
+
+/// Test that setting orientation and angular velocity does not depend on the order of calls.
+#[test]
+fn test_rigid3_builder_order() {
+    let inertia = Mat3::from_diagonal(Vec3::new(1.0, 2.0, 3.0));
+    let rot = Rot3::from_scaled_axis(Vec3::new(0.3, 0.2, -0.1));
+    let omega = Vec3::new(0.5, -1.0, 2.0);
+
+    let first = RigidBody3::<Rk4>::new(1.0, inertia)
+        .with_rot(rot)
+        .with_angular_velocity(omega);
+    let second = RigidBody3::<Rk4>::new(1.0, inertia)
+        .with_angular_velocity(omega)
+        .with_rot(rot);
+
+    assert!(first.angular_velocity().abs_diff_eq(omega, 1e-5));
+    assert!(second.angular_velocity().abs_diff_eq(omega, 1e-5));
+    assert!(
+        first
+            .angular_momentum
+            .abs_diff_eq(*second.angular_momentum, 1e-5)
+    );
+}
+
+/// Test torque-free precession of a symmetric top in double precision.
+#[test]
+fn test_rigid3_double_precision() {
+    let (i1, i3) = (1.0, 2.0);
+    let mut body = DRigidBody3::<Rk4>::new(1.0, DMat3::from_diagonal(DVec3::new(i1, i1, i3)))
+        .with_pos(DVec3::X)
+        .with_velocity(DVec3::Y)
+        .with_angular_velocity(DVec3::new(0.3, 0.0, 1.0));
+    let momentum = *body.angular_momentum;
+    let energy = body.kinetic_energy();
+
+    let mut t = 0.0;
+    for _ in 0..500 {
+        Rk4.solve_step(&mut body, &mut t, 0.01);
+    }
+
+    let precession = DRot3::from_scaled_axis(momentum.normalize() * momentum.length() / i1 * t);
+    let axis = body.rot.transform(DVec3::Z);
+    assert!(axis.abs_diff_eq(precession.transform(DVec3::Z), 1e-4));
+    assert!(body.pos.abs_diff_eq(DVec3::new(1.0, t, 0.0), 1e-12));
+    assert_eq!(*body.angular_momentum, momentum);
+    assert!((body.kinetic_energy() - energy).abs() < 1e-6);
+}
@@ -1,6 +1,6 @@
 //! Tests for the RK4 solver, including accuracy verification.
 
-use crate::{Rk4, Solver, System, Var, Visitor, rk4::Rk4Step};
+use crate::{Rk4, Rk4Dense, Solver, System, Var, Visitor, rk4::Rk4Step};
 use glam::Vec2;
 
 /// A simple test system with constant derivative: dx/dt = c
@@ -37,9 +37,10 @@ fn test_rk4_constant_derivative() {
     let dt = 0.5;
     let steps = 4;
 
+    let mut t = 0.0;
     // Integrate for 4 steps of dt=0.5 (total time = 2.0)
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // Analytical solution: x(t) = x0 + c*t = 0 + 2.0 * 2.0 = 4.0
@@ -97,10 +98,11 @@ fn test_rk4_exponential_convergence() {
         let mut system = ExponentialSystemRK4::new(initial_value, growth_rate);
         let solver = Rk4;
         let steps = (total_time / dt) as usize;
+        let mut t = 0.0;
 
         // Integrate for total_time
         for _ in 0..steps {
-            solver.solve_step(&mut system, dt);
+            solver.solve_step(&mut system, &mut t, dt);
         }
 
         // Analytical solution: x(t) = x0 * exp(k*t)
@@ -214,8 +216,9 @@ fn test_rk4_harmonic_oscillator() {
     let dt = period / 100.0; // 100 steps per period
     let steps = 100;
 
+    let mut t = 0.0;
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // After one period, should return close to initial state
@@ -242,8 +245,9 @@ fn test_rk4_vs_euler_accuracy() {
     // RK4 integration
     let mut system_rk4 = ExponentialSystemRK4::new(initial_value, growth_rate);
     let solver_rk4 = Rk4;
+    let mut t = 0.0;
     for _ in 0..steps {
-        solver_rk4.solve_step(&mut system_rk4, dt);
+        solver_rk4.solve_step(&mut system_rk4, &mut t, dt);
     }
 
     // Euler integration
@@ -267,8 +271,9 @@ fn test_rk4_vs_euler_accuracy() {
         growth_rate,
     };
     let solver_euler = Euler;
+    let mut t = 0.0;
     for _ in 0..steps {
-        solver_euler.solve_step(&mut system_euler, dt);
+        solver_euler.solve_step(&mut system_euler, &mut t, dt);
     }
 
     // Analytical solution
@@ -323,8 +328,9 @@ fn test_rk4_vector_variables() {
     let dt = 0.1;
     let steps = 10;
 
+    let mut t = 0.0;
     for _ in 0..steps {
-        solver.solve_step(&mut system, dt);
+        solver.solve_step(&mut system, &mut t, dt);
     }
 
     // Analytical solution for dv/dt = -v: v(t) = v0 * exp(-t)
@@ -347,13 +353,52 @@ fn test_rk4_storage_management() {
     let mut system = ConstantDerivativeSystem::new(0.0, 1.0);
     let solver = Rk4;
 
+    let mut t = 0.0;
     // Take a few steps
-    solver.solve_step(&mut system, 0.1);
-    solver.solve_step(&mut system, 0.1);
-    solver.solve_step(&mut system, 0.1);
+    solver.solve_step(&mut system, &mut t, 0.1);
+    solver.solve_step(&mut system, &mut t, 0.1);
+    solver.solve_step(&mut system, &mut t, 0.1);
 
     // Should still work correctly
     let expected = 0.3; // 3 steps * 0.1 * 1.0
     let error = (*system.x - expected).abs();
     assert!(error < 1e-6, "Error after multiple steps: {}", error);
 }
+
+/// Pendulum generic over the solver.
+struct Pendulum<S: Solver> {
+    angle: Var<f32, S>,
+    speed: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for Pendulum<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.angle.deriv = *self.speed;
+        self.speed.deriv = -self.angle.sin();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.angle, &mut self.speed);
+    }
+}
+
+/// Test that RK4 with dense output takes the same steps as plain RK4.
+#[test]
+fn test_rk4_dense_same_steps() {
+    let mut plain = Pendulum::<Rk4> {
+        angle: Var::new(1.0),
+        speed: Var::new(0.0),
+    };
+    let mut dense = Pendulum::<Rk4Dense> {
+        angle: Var::new(1.0),
+        speed: Var::new(0.0),
+    };
+    let (mut t, mut t_dense) = (0.0, 0.0);
+    for _ in 0..100 {
+        Rk4.solve_step(&mut plain, &mut t, 0.05);
+        Rk4Dense.solve_step(&mut dense, &mut t_dense, 0.05);
+    }
+    assert_eq!(t, t_dense);
+    assert!((*plain.angle - *dense.angle).abs() < 1e-6);
+    assert!((*plain.speed - *dense.speed).abs() < 1e-6);
+}
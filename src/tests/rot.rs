@@ -1,7 +1,10 @@
 //! Tests for rotation types and utility functions.
 
-use crate::{Rot2, Rot3, angular_to_linear2, angular_to_linear3, torque2, torque3};
-use glam::{Vec2, Vec3};
+use crate::{
+    BodyRot3, Context, DBodyRot3, DRot2, DRot3, Param, Rk4, Rot2, Rot3, Solver, System, Var,
+    Visitor, angular_to_linear2, angular_to_linear3, torque2, torque3,
+};
+use glam::{DVec3, Quat, Vec2, Vec3};
 use std::f32::consts::PI;
 
 /// Test Rot2 basic operations.
@@ -23,6 +26,22 @@ fn test_rot2_basics() {
     assert!((rot.angle_degrees() - 60.0).abs() < 1e-6);
 }
 
+/// Test that angles of both precisions are wrapped into the range [0, 2π).
+#[test]
+fn test_rot2_wrap_range() {
+    for angle in [-3.0, -0.5, 0.0, 0.5, 3.5, 7.0] {
+        let rot = Rot2::from_angle(angle);
+        assert!((0.0..2.0 * PI).contains(&rot.angle()), "{:?}", rot);
+        let rot = DRot2::from_angle(angle as f64);
+        assert!(
+            (0.0..2.0 * std::f64::consts::PI).contains(&rot.angle()),
+            "{:?}",
+            rot
+        );
+        assert!((rot.angle() as f32 - Rot2::from_angle(angle).angle()).abs() < 1e-6);
+    }
+}
+
 /// Test Rot2 transformation.
 #[test]
 fn test_rot2_transform() {
@@ -273,3 +292,91 @@ fn test_angular_to_linear3() {
     let expected_magnitude = angular_mag * pos_mag;
     assert!((linear.length() - expected_magnitude).abs() < 1e-6);
 }
+
+/// Test that body-frame angular velocity is applied in the rotating frame.
+#[test]
+fn test_body_rot3_step() {
+    let start = Rot3::from_scaled_axis(Vec3::new(0.4, -0.2, 1.1));
+    let omega_body = Vec3::new(0.5, 1.0, -0.3);
+    let omega_world = start.body_to_world(omega_body);
+    assert!(
+        start
+            .world_to_body(omega_world)
+            .abs_diff_eq(omega_body, 1e-6)
+    );
+
+    let mut world = start;
+    world.step(&omega_world, 0.7);
+    let mut body = BodyRot3::from(start);
+    body.step(&omega_body, 0.7);
+    let v = Vec3::new(1.0, 2.0, 3.0);
+    assert!(body.transform(v).abs_diff_eq(world.transform(v), 1e-5));
+
+    // Rotation around the body x axis keeps the body x axis in place
+    let mut body = BodyRot3::from(start);
+    body.step(&(Vec3::X * 2.0), 0.5);
+    assert!(
+        body.transform(Vec3::X)
+            .abs_diff_eq(start.transform(Vec3::X), 1e-6)
+    );
+    assert!(
+        !body
+            .transform(Vec3::Y)
+            .abs_diff_eq(start.transform(Vec3::Y), 1e-2)
+    );
+    let q: Quat = body.into();
+    assert!((q.length() - 1.0).abs() < 1e-6);
+
+    let mut body = DBodyRot3::from(DRot3::from_scaled_axis(DVec3::Z));
+    body.step(&DVec3::X, 1.0);
+    let expected = DRot3::from_scaled_axis(DVec3::X).chain(DRot3::from_scaled_axis(DVec3::Z));
+    assert!(
+        body.transform(DVec3::Y)
+            .abs_diff_eq(expected.transform(DVec3::Y), 1e-12)
+    );
+}
+
+/// Body with time-varying angular velocity given in the body frame.
+struct Tumbler<S: Solver> {
+    world: Var<Rot3, S>,
+    body: Var<BodyRot3, S>,
+}
+
+fn omega_body(t: f32) -> Vec3 {
+    Vec3::new(t.cos(), t.sin(), 0.5)
+}
+
+impl<S: Solver> System<S> for Tumbler<S> {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        let omega = omega_body(ctx.time());
+        self.world.deriv = self.world.body_to_world(omega);
+        self.body.deriv = omega;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.world);
+        visitor.apply(&mut self.body);
+    }
+}
+
+/// Test that body-frame integration agrees with world-frame integration of converted velocity.
+#[test]
+fn test_body_rot3_integration() {
+    let start = Rot3::from_scaled_axis(Vec3::new(0.3, 0.2, -0.1));
+    let mut system = Tumbler::<Rk4> {
+        world: Var::new(start),
+        body: Var::new(start.into()),
+    };
+    let mut t = 0.0;
+    for _ in 0..500 {
+        Rk4.solve_step(&mut system, &mut t, 0.01);
+    }
+    for v in [Vec3::X, Vec3::Y, Vec3::Z] {
+        assert!(
+            system
+                .body
+                .transform(v)
+                .abs_diff_eq(system.world.transform(v), 1e-4)
+        );
+    }
+}
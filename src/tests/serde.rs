@@ -0,0 +1,134 @@
+//! Tests for serialization of parameters, variables and solver storage.
+
+use crate::{
+    DRot2, Dopri5, Euler, ImplicitEuler, Iso2, Iso3, Rk4, Rot2, Rot3, Solver, System, Tolerance,
+    Twist3, Var, Verlet, Visitor, flatten_state,
+};
+use core::f32::consts::PI;
+use glam::{Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Serialize a value to JSON and back.
+fn round_trip<V: Serialize + DeserializeOwned>(value: &V) -> V {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+/// Spinning oscillator with a checkpoint of concrete variables.
+#[derive(Serialize, Deserialize)]
+struct Body {
+    pos: Var<Vec3, Rk4>,
+    vel: Var<Vec3, Rk4>,
+    rot: Var<Rot3, Rk4>,
+}
+
+impl Body {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(Vec3::X),
+            vel: Var::new(Vec3::new(0.0, 1.0, 0.5)),
+            rot: Var::default(),
+        }
+    }
+}
+
+impl System<Rk4> for Body {
+    fn compute_derivs(&mut self, _: &<Rk4 as Solver>::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos;
+        self.rot.deriv = self.pos.cross(*self.vel);
+    }
+
+    fn visit_vars<V: Visitor<Rk4>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+        visitor.apply(&mut self.rot);
+    }
+}
+
+/// Test that rotations are serialized as angles and normalized quaternions.
+#[test]
+fn test_serde_rotations() {
+    assert_eq!(
+        serde_json::to_string(&Rot2::from_angle(0.5)).unwrap(),
+        "0.5"
+    );
+    let rot: Rot2 = serde_json::from_str("7.0").unwrap();
+    assert!((rot.angle() - (7.0 - 2.0 * PI)).abs() < 1e-6);
+    let rot: DRot2 = serde_json::from_str("-1.0").unwrap();
+    assert!((rot.angle() - (2.0 * core::f64::consts::PI - 1.0)).abs() < 1e-12);
+
+    let rot = Rot3::from(Quat::from_xyzw(0.0, 0.0, 0.0, 1.0 + 1e-6));
+    assert_eq!(serde_json::to_string(&rot).unwrap(), "[0.0,0.0,0.0,1.0]");
+
+    let rot = Rot3::from_scaled_axis(Vec3::new(0.3, -0.2, 1.0));
+    let restored = round_trip(&rot);
+    assert!(Quat::from(restored).abs_diff_eq(Quat::from(rot), 1e-7));
+    assert!((Quat::from(restored).length() - 1.0).abs() < 1e-6);
+
+    // Small rounding errors are corrected
+    let rot: Rot3 = serde_json::from_str("[0.0,0.6,0.0,0.8001]").unwrap();
+    assert!((Quat::from(rot).length() - 1.0).abs() < 1e-6);
+}
+
+/// Test that invalid quaternions and angles are refused.
+#[test]
+fn test_serde_invalid_rotations() {
+    let error = serde_json::from_str::<Rot3>("[0.0,0.0,0.0,2.0]").unwrap_err();
+    assert!(error.to_string().contains("quaternion is not normalized"));
+    let error = serde_json::from_str::<Rot3>("[0.0,0.0,0.0,0.0]").unwrap_err();
+    assert!(error.to_string().contains("quaternion is not normalized"));
+    let error = serde_json::from_str::<Rot3>("[0.0,0.0,1e39,1.0]").unwrap_err();
+    assert!(error.to_string().contains("quaternion is not finite"));
+    assert!(serde_json::from_str::<Rot3>("[0.0,0.0,1.0]").is_err());
+    let error = serde_json::from_str::<Rot2>("1e39").unwrap_err();
+    assert!(error.to_string().contains("rotation angle is not finite"));
+}
+
+/// Test round trips of poses, twists and variables of different solvers.
+#[test]
+fn test_serde_round_trip() {
+    let pose = Iso2::new(Rot2::from_angle(0.25), Vec2::new(-1.0, 2.0));
+    let restored = round_trip(&pose);
+    assert_eq!((restored.rot.angle(), restored.pos), (0.25, pose.pos));
+    let pose = Iso3::new(Rot3::from_scaled_axis(Vec3::Y), Vec3::Z);
+    assert_eq!(round_trip(&pose).pos, Vec3::Z);
+    let twist = Twist3::new(Vec3::X, Vec3::Y);
+    assert_eq!(round_trip(&twist), twist);
+
+    let var = Var::<Vec2, Euler>::new(Vec2::ONE).with_tolerance(Tolerance::new(1e-6, 1e-3));
+    let restored = round_trip(&var);
+    assert_eq!(*restored, Vec2::ONE);
+    assert_eq!(restored.tolerance(), var.tolerance());
+
+    let mut var = Var::<f32, Dopri5>::new(1.0);
+    var.deriv = -1.0;
+    assert_eq!(round_trip(&var).deriv, -1.0);
+    round_trip(&Var::<Iso3, Verlet>::default());
+    round_trip(&Var::<Vec<Vec3>, ImplicitEuler>::new(vec![Vec3::X; 2]));
+}
+
+/// Test that a checkpoint taken between steps resumes the same trajectory.
+#[test]
+fn test_serde_checkpoint_resume() {
+    let mut body = Body::new();
+    let mut t = 0.0;
+    for _ in 0..5 {
+        Rk4.solve_step(&mut body, &mut t, 0.1);
+    }
+    let checkpoint = serde_json::to_string(&body).unwrap();
+
+    let mut expected = Vec::new();
+    for _ in 0..10 {
+        Rk4.solve_step(&mut body, &mut t, 0.1);
+        expected.push(flatten_state(&mut body).0);
+    }
+
+    let mut resumed: Body = serde_json::from_str(&checkpoint).unwrap();
+    let mut t = 0.5;
+    for state in &expected {
+        Rk4.solve_step(&mut resumed, &mut t, 0.1);
+        let replayed = flatten_state(&mut resumed).0;
+        for (a, b) in replayed.iter().zip(state) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+}
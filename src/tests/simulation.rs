@@ -0,0 +1,125 @@
+//! Tests for the fixed time step simulation driver.
+
+use crate::{Euler, Rk4, Rk4Dense, Simulation, Solver, System, Var, Visitor};
+
+/// Harmonic oscillator with unit frequency.
+#[derive(Clone)]
+struct Oscillator<S: Solver> {
+    x: Var<f32, S>,
+    v: Var<f32, S>,
+}
+
+impl<S: Solver> Oscillator<S> {
+    fn new() -> Self {
+        Self {
+            x: Var::new(1.0),
+            v: Var::new(0.0),
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Oscillator<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = *self.v;
+        self.v.deriv = -*self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.x, &mut self.v);
+    }
+}
+
+/// Test that the result does not depend on the frame rate.
+#[test]
+fn test_frame_rate_independence() {
+    let dt = 1.0 / 64.0;
+    let mut reference = Oscillator::<Rk4>::new();
+    let mut t = 0.0;
+    for _ in 0..64 {
+        Rk4.solve_step(&mut reference, &mut t, dt);
+    }
+
+    for frames in [
+        &[1.0 / 16.0; 16][..],
+        &[1.0 / 128.0; 128][..],
+        &[0.125, 0.3125, 0.0625, 0.25, 0.25][..],
+    ] {
+        let mut sim = Simulation::new(Oscillator::<Rk4>::new(), Rk4, dt).with_max_steps(usize::MAX);
+        let steps: usize = frames.iter().map(|&frame| sim.advance(frame).steps).sum();
+        assert_eq!(steps, 64);
+        assert_eq!(sim.time(), t);
+        assert_eq!(sim.alpha(), 0.0);
+        assert_eq!(*sim.system().x, *reference.x);
+        assert_eq!(*sim.system().v, *reference.v);
+    }
+}
+
+/// Test that time left after the last step is carried over to the next frame.
+#[test]
+fn test_accumulator() {
+    let mut sim = Simulation::new(Oscillator::<Euler>::new(), Euler, 0.25);
+    let frame = sim.advance(0.125);
+    assert_eq!((frame.steps, frame.alpha), (0, 0.5));
+    assert_eq!(sim.time(), 0.0);
+    assert_eq!(sim.render_time(), 0.0);
+
+    let frame = sim.advance(0.5);
+    assert_eq!((frame.steps, frame.alpha, frame.dropped), (2, 0.5, 0.0));
+    assert_eq!(sim.time(), 0.5);
+    assert_eq!(sim.render_time(), 0.375);
+
+    sim.step();
+    assert_eq!(sim.time(), 0.75);
+    assert_eq!(sim.alpha(), 0.5);
+}
+
+/// Test that the number of steps per frame is limited and the rest of the time is dropped.
+#[test]
+fn test_max_steps() {
+    let mut sim = Simulation::new(Oscillator::<Euler>::new(), Euler, 0.25).with_max_steps(3);
+    let frame = sim.advance(1.375);
+    assert_eq!(frame.steps, 3);
+    assert_eq!(frame.dropped, 0.5);
+    assert_eq!(frame.alpha, 0.5);
+    assert_eq!(sim.time(), 0.75);
+
+    // The simulation continues normally afterwards
+    let frame = sim.advance(0.125);
+    assert_eq!((frame.steps, frame.alpha, frame.dropped), (1, 0.0, 0.0));
+    assert_eq!(sim.time(), 1.0);
+
+    // Long stalls are dropped at once, keeping the fraction of a step
+    let frame = sim.advance(1e6 + 0.125);
+    assert_eq!(frame.steps, 3);
+    assert_eq!(frame.alpha, 0.5);
+    assert_eq!(frame.dropped, 1e6 - 0.75);
+    assert_eq!(sim.time(), 1.75);
+
+    let sim = Simulation::new(Oscillator::<Euler>::new(), Euler, 0.25).with_time(2.0);
+    assert_eq!(sim.time(), 2.0);
+    assert_eq!(sim.render_time(), 2.0);
+}
+
+/// Test interpolated states between steps.
+#[test]
+fn test_interpolated_state() {
+    let mut sim = Simulation::new(Oscillator::<Rk4Dense>::new(), Rk4Dense, 0.1);
+    let state = sim.interpolated();
+    assert_eq!((*state.x, *state.v), (1.0, 0.0));
+
+    let mut last = sim.render_time();
+    for _ in 0..100 {
+        sim.advance(0.023);
+        let t = sim.render_time();
+        assert!(t >= last && t <= sim.time());
+        last = t;
+        let state = sim.interpolated();
+        assert!((*state.x - t.cos()).abs() < 1e-4, "t={}", t);
+        assert!((*state.v + t.sin()).abs() < 1e-4, "t={}", t);
+    }
+
+    // Interpolation does not change the simulated state
+    let time = sim.time();
+    let system = sim.into_system();
+    assert!((*system.x - time.cos()).abs() < 1e-4);
+}
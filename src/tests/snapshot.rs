@@ -0,0 +1,126 @@
+//! Tests for snapshots and rollback of complete state.
+
+use crate::{
+    DenseSolver, Rk4, Rk4Dense, Rot3, Snapshot, Solver, System, Var, Verlet, Visitor, flatten_state,
+};
+use glam::{Vec2, Vec3};
+
+/// Spinning body with a cloud of particles, driven by an external input.
+#[derive(Clone)]
+struct Body<S: Solver> {
+    pos: Var<Vec3, S>,
+    vel: Var<Vec3, S>,
+    rot: Var<Rot3, S>,
+    particles: Var<Vec<Vec2>, S>,
+    input: Vec3,
+}
+
+impl<S: Solver> Body<S> {
+    fn new() -> Self {
+        Self {
+            pos: Var::new(Vec3::new(1.0, 0.0, 0.5)),
+            vel: Var::new(Vec3::new(0.0, 1.0, 0.0)),
+            rot: Var::default(),
+            particles: Var::new(vec![Vec2::X, Vec2::Y, Vec2::ONE]),
+            input: Vec3::ZERO,
+        }
+    }
+}
+
+impl<S: Solver> System<S> for Body<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = self.input - *self.pos * self.pos.length();
+        self.rot.deriv = self.pos.cross(*self.vel);
+        self.particles.deriv = self.particles.iter().map(|p| p.perp() - *p).collect();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+        visitor.apply(&mut self.rot);
+        visitor.apply(&mut self.particles);
+    }
+}
+
+/// Step the system and collect flattened states after each step.
+fn run<S: Solver>(solver: &S, system: &mut Body<S>, t: &mut f32, steps: usize) -> Vec<Vec<f32>> {
+    (0..steps)
+        .map(|_| {
+            solver.solve_step(system, t, 0.05);
+            flatten_state(system).0
+        })
+        .collect()
+}
+
+/// Test that rollback and replay reproduces identical trajectories.
+#[test]
+fn test_rollback_replay_rk4() {
+    let mut system = Body::<Rk4>::new();
+    let mut t = 0.0;
+    run(&Rk4, &mut system, &mut t, 5);
+    let snapshot = Snapshot::new(&system, t);
+    assert_eq!(snapshot.time(), t);
+    let expected = run(&Rk4, &mut system, &mut t, 20);
+    let end = t;
+
+    // Mispredicted input diverges from the expected trajectory
+    snapshot.restore(&mut system, &mut t);
+    system.input = Vec3::Z;
+    let diverged = run(&Rk4, &mut system, &mut t, 20);
+    assert_ne!(diverged, expected);
+
+    // Rolling back restores the input too, so replay is identical
+    snapshot.restore(&mut system, &mut t);
+    assert_eq!(system.input, Vec3::ZERO);
+    assert_eq!(run(&Rk4, &mut system, &mut t, 20), expected);
+    assert_eq!(t, end);
+
+    // Restoring into another instance of the system
+    let mut other = Body::<Rk4>::new();
+    let mut t = 0.0;
+    snapshot.restore(&mut other, &mut t);
+    assert_eq!(run(&Rk4, &mut other, &mut t, 20), expected);
+}
+
+/// Test that solver storage is restored along with the values.
+#[test]
+fn test_restore_storage() {
+    let mut system = Body::<Rk4Dense>::new();
+    let mut t = 0.0;
+    run(&Rk4Dense, &mut system, &mut t, 3);
+    let snapshot = Snapshot::new(&system, t);
+    let mut expected = snapshot.system().clone();
+    Rk4Dense.interpolate(&mut expected, 0.05, 0.5);
+
+    run(&Rk4Dense, &mut system, &mut t, 3);
+    snapshot.restore(&mut system, &mut t);
+    Rk4Dense.interpolate(&mut system, 0.05, 0.5);
+    assert_eq!(flatten_state(&mut system).0, flatten_state(&mut expected).0);
+
+    // Velocity Verlet keeps accelerations of the previous step
+    let mut system = Body::<Verlet>::new();
+    let mut t = 0.0;
+    run(&Verlet, &mut system, &mut t, 3);
+    let snapshot = Snapshot::new(&system, t);
+    let expected = run(&Verlet, &mut system, &mut t, 10);
+    snapshot.restore(&mut system, &mut t);
+    assert_eq!(run(&Verlet, &mut system, &mut t, 10), expected);
+}
+
+/// Test that dynamically sized variables are restored to their saved size.
+#[test]
+fn test_restore_resized() {
+    let mut system = Body::<Rk4>::new();
+    let mut t = 0.0;
+    let mut snapshot = Snapshot::new(&system, t);
+    system.particles.push(Vec2::NEG_X);
+    snapshot.restore(&mut system, &mut t);
+    assert_eq!(system.particles.len(), 3);
+
+    system.particles.clear();
+    snapshot.save(&system, 1.0);
+    system.particles.push(Vec2::NEG_X);
+    snapshot.restore(&mut system, &mut t);
+    assert!(system.particles.is_empty());
+    assert_eq!(t, 1.0);
+}
@@ -0,0 +1,313 @@
+//! Tests for symplectic splitting solvers.
+
+use crate::{
+    Context, Euler, Solver, Splitting, Symplectic, SymplecticEuler, System, TrySystem, Var,
+    Visitor,
+    splitting::{DriftKick, ForestRuth, KickDrift, Yoshida6, Yoshida8},
+};
+use glam::DVec2;
+
+/// Pendulum in double precision declared as a position-velocity pair.
+struct Pendulum<S: Solver<f64>> {
+    theta: Var<f64, S>,
+    omega: Var<f64, S>,
+}
+
+impl<S: Solver<f64>> Pendulum<S> {
+    fn new(theta: f64, omega: f64) -> Self {
+        Self {
+            theta: Var::new(theta),
+            omega: Var::new(omega),
+        }
+    }
+}
+
+impl<S: Solver<f64>> System<S, f64> for Pendulum<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.theta.deriv = *self.omega;
+        self.omega.deriv = -self.theta.sin();
+    }
+
+    fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.theta, &mut self.omega);
+    }
+}
+
+/// Kepler orbit around a unit mass at the origin.
+struct Planet<S: Solver<f64>> {
+    pos: Var<DVec2, S>,
+    vel: Var<DVec2, S>,
+}
+
+impl<S: Solver<f64>> Planet<S> {
+    /// Orbit with eccentricity 0.5 starting at its pericenter.
+    fn eccentric() -> Self {
+        Self {
+            pos: Var::new(DVec2::new(0.5, 0.0)),
+            vel: Var::new(DVec2::new(0.0, 3.0f64.sqrt())),
+        }
+    }
+
+    fn energy(&self) -> f64 {
+        0.5 * self.vel.length_squared() - 1.0 / self.pos.length()
+    }
+}
+
+impl<S: Solver<f64>> System<S, f64> for Planet<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+    }
+
+    fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+/// State of the pendulum after integrating it up to `t = 4` in `steps` steps.
+fn pendulum_state<T: Splitting>(steps: usize) -> (f64, f64) {
+    let solver = Symplectic::<T>::new();
+    let mut system = Pendulum::new(1.0, 0.0);
+    let dt = 4.0 / steps as f64;
+    let mut t = 0.0;
+    for _ in 0..steps {
+        solver.solve_step(&mut system, &mut t, dt);
+    }
+    (*system.theta, *system.omega)
+}
+
+/// Check that doubling the number of steps reduces the error at least as dt^ORDER.
+///
+/// Number of steps should be chosen so that errors are well above rounding errors.
+fn check_convergence<T: Splitting>(name: &str, steps: usize) {
+    let reference = pendulum_state::<Yoshida8>(1024);
+    let errors = [steps, 2 * steps].map(|steps| {
+        let (theta, omega) = pendulum_state::<T>(steps);
+        (theta - reference.0).hypot(omega - reference.1)
+    });
+    let order = (errors[0] / errors[1]).log2();
+    assert!(
+        order > T::ORDER as f64 - 0.5,
+        "Convergence rate of {} is not of order {}: measured {}, errors={:?}",
+        name,
+        T::ORDER,
+        order,
+        errors
+    );
+}
+
+/// Test convergence order of all ready-made splitting methods.
+#[test]
+fn test_splitting_convergence_order() {
+    check_convergence::<KickDrift>("KickDrift", 64);
+    check_convergence::<DriftKick>("DriftKick", 64);
+    check_convergence::<ForestRuth>("ForestRuth", 32);
+    check_convergence::<Yoshida6>("Yoshida6", 16);
+    check_convergence::<Yoshida8>("Yoshida8", 16);
+}
+
+/// Test that both orderings of symplectic Euler match their formulas.
+#[test]
+fn test_symplectic_euler() {
+    let (dt, mut t) = (0.1, 0.0);
+    let mut system = Pendulum::<SymplecticEuler>::new(1.0, 0.5);
+    let (mut theta, mut omega) = (1.0f64, 0.5f64);
+    for _ in 0..10 {
+        SymplecticEuler::default().solve_step(&mut system, &mut t, dt);
+        omega -= theta.sin() * dt;
+        theta += omega * dt;
+        assert_eq!((*system.theta, *system.omega), (theta, omega));
+    }
+
+    let mut system = Pendulum::<Symplectic<DriftKick>>::new(1.0, 0.5);
+    let (mut theta, mut omega) = (1.0f64, 0.5f64);
+    for _ in 0..10 {
+        Symplectic::<DriftKick>::new().solve_step(&mut system, &mut t, dt);
+        theta += omega * dt;
+        omega -= theta.sin() * dt;
+        assert_eq!((*system.theta, *system.omega), (theta, omega));
+    }
+    assert!((t - 2.0).abs() < 1e-12);
+
+    // Derivatives are reset after the step
+    assert_eq!((system.theta.deriv, system.omega.deriv), (0.0, 0.0));
+}
+
+/// Test that energy error stays bounded over many orbits.
+#[test]
+fn test_splitting_energy_bounded() {
+    fn max_energy_error<T: Splitting>(dt: f64, orbits: usize) -> f64 {
+        let solver = Symplectic::<T>::new();
+        let mut planet = Planet::eccentric();
+        let energy = planet.energy();
+        let mut t = 0.0;
+        // Period of the orbit with semi-major axis 1 is 2π
+        let steps = (orbits as f64 * 2.0 * core::f64::consts::PI / dt) as usize;
+        (0..steps)
+            .map(|_| {
+                solver.solve_step(&mut planet, &mut t, dt);
+                (planet.energy() - energy).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    // The error does not grow with the number of orbits
+    for (name, error_10, error_100) in [
+        (
+            "KickDrift",
+            max_energy_error::<KickDrift>(1e-3, 10),
+            max_energy_error::<KickDrift>(1e-3, 100),
+        ),
+        (
+            "ForestRuth",
+            max_energy_error::<ForestRuth>(1e-2, 10),
+            max_energy_error::<ForestRuth>(1e-2, 100),
+        ),
+        (
+            "Yoshida6",
+            max_energy_error::<Yoshida6>(2e-2, 10),
+            max_energy_error::<Yoshida6>(2e-2, 100),
+        ),
+    ] {
+        assert!(error_10 < 1e-2, "{}: {}", name, error_10);
+        assert!(
+            error_100 < 1.5 * error_10,
+            "{}: {} {}",
+            name,
+            error_10,
+            error_100
+        );
+    }
+}
+
+/// Test that symmetric compositions retrace their steps with a negative time step.
+#[test]
+fn test_splitting_time_reversible() {
+    fn check<T: Splitting>(name: &str) {
+        let solver = Symplectic::<T>::new();
+        let mut planet = Planet::eccentric();
+        let mut t = 0.0;
+        for _ in 0..100 {
+            solver.solve_step(&mut planet, &mut t, 0.05);
+        }
+        for _ in 0..100 {
+            solver.solve_step(&mut planet, &mut t, -0.05);
+        }
+        let error = (*planet.pos - DVec2::new(0.5, 0.0)).length();
+        assert!(error < 1e-12, "{}: {}", name, error);
+        assert!(t.abs() < 1e-12);
+    }
+
+    check::<ForestRuth>("ForestRuth");
+    check::<Yoshida6>("Yoshida6");
+    check::<Yoshida8>("Yoshida8");
+}
+
+/// Test that accelerations are evaluated at the times of positions.
+#[test]
+fn test_splitting_stage_times() {
+    struct Clock<S: Solver<f64>> {
+        x: Var<f64, S>,
+        v: Var<f64, S>,
+        times: Vec<f64>,
+    }
+
+    impl<S: Solver<f64>> System<S, f64> for Clock<S> {
+        fn compute_derivs(&mut self, ctx: &S::Context) {
+            self.times.push(ctx.time());
+        }
+
+        fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+            visitor.apply_pair(&mut self.x, &mut self.v);
+        }
+    }
+
+    let mut clock = Clock::<Symplectic<ForestRuth>> {
+        x: Var::new(0.0),
+        v: Var::new(1.0),
+        times: Vec::new(),
+    };
+    let mut t = 1.0;
+    Symplectic::<ForestRuth>::new().solve_step(&mut clock, &mut t, 0.5);
+    assert_eq!(clock.times.len(), 3);
+    // Uniform motion: position is the time since the beginning
+    assert!((*clock.x - 0.5).abs() < 1e-12);
+    let theta = 1.0 / (2.0 - 2.0f64.cbrt());
+    let expected = [1.0 + 0.25 * theta, 1.25, 1.5 - 0.25 * theta];
+    for (time, expected) in clock.times.iter().zip(expected) {
+        assert!((time - expected).abs() < 1e-12, "{:?}", clock.times);
+    }
+}
+
+/// Test that variables outside of pairs are stepped by their derivatives in kicks.
+#[test]
+fn test_splitting_unpaired_variables() {
+    struct Decay<S: Solver<f64>> {
+        x: Var<f64, S>,
+    }
+
+    impl<S: Solver<f64>> System<S, f64> for Decay<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.x.deriv = -*self.x;
+        }
+
+        fn visit_vars<V: Visitor<S, f64>>(&mut self, visitor: &mut V) {
+            visitor.apply(&mut self.x);
+        }
+    }
+
+    let mut system = Decay::<Symplectic<DriftKick>> { x: Var::new(1.0) };
+    let mut reference = Decay::<Euler> { x: Var::new(1.0) };
+    let (mut t, mut t_reference) = (0.0, 0.0);
+    for _ in 0..10 {
+        Symplectic::<DriftKick>::new().solve_step(&mut system, &mut t, 0.1);
+        Euler.solve_step(&mut reference, &mut t_reference, 0.1);
+    }
+    assert_eq!(*system.x, *reference.x);
+
+    // Higher-order methods are still first order for such variables
+    let mut system = Decay::<Symplectic<Yoshida6>> { x: Var::new(1.0) };
+    let mut t = 0.0;
+    for _ in 0..1000 {
+        Symplectic::<Yoshida6>::new().solve_step(&mut system, &mut t, 0.001);
+    }
+    assert!((*system.x - (-1.0f64).exp()).abs() < 2e-3);
+}
+
+/// Test that a failed evaluation after the first drift restores positions.
+#[test]
+fn test_splitting_try_restore() {
+    /// Pendulum which is not defined beyond the horizontal position.
+    struct Bounded(Pendulum<Symplectic<DriftKick>>);
+
+    impl TrySystem<Symplectic<DriftKick>, f64> for Bounded {
+        type Error = ();
+
+        fn try_compute_derivs(
+            &mut self,
+            ctx: &<Symplectic<DriftKick> as Solver<f64>>::Context,
+        ) -> Result<(), ()> {
+            if self.0.theta.abs() > core::f64::consts::FRAC_PI_2 {
+                return Err(());
+            }
+            self.0.compute_derivs(ctx);
+            Ok(())
+        }
+
+        fn visit_vars<V: Visitor<Symplectic<DriftKick>, f64>>(&mut self, visitor: &mut V) {
+            System::visit_vars(&mut self.0, visitor);
+        }
+    }
+
+    let solver = Symplectic::<DriftKick>::new();
+    let mut system = Bounded(Pendulum::new(1.0, 1.0));
+    let mut t = 0.0;
+    solver.try_solve_step(&mut system, &mut t, 0.1).unwrap();
+    let before = (*system.0.theta, *system.0.omega);
+
+    // The drift moves beyond the domain before the only evaluation
+    assert_eq!(solver.try_solve_step(&mut system, &mut t, 1.0), Err(()));
+    assert_eq!(t, 0.1);
+    assert_eq!((*system.0.theta, *system.0.omega), before);
+    assert_eq!((system.0.theta.deriv, system.0.omega.deriv), (0.0, 0.0));
+}
@@ -1,6 +1,9 @@
 //! Example systems for testing the System trait implementations.
 
-use crate::{Euler, Rk4, Solver, System, Var, Visitor};
+use crate::{
+    AdaptiveSolver, ButcherRk, Context, Dopri5, Euler, ImplicitEuler, Rk4, Solver, System, Var,
+    Verlet, Visitor, tableau::Kutta3,
+};
 use glam::Vec2;
 
 /// A simple particle system with position and velocity.
@@ -69,8 +72,9 @@ fn test_simple_particle_system() {
     let mut particle = SimpleParticle::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0));
     let solver = Euler;
 
+    let mut t = 0.0;
     // Take one step
-    solver.solve_step(&mut particle, 0.1);
+    solver.solve_step(&mut particle, &mut t, 0.1);
 
     // After one step with dt=0.1 and velocity (1, 2)
     let expected_pos = Vec2::new(0.1, 0.2);
@@ -92,9 +96,10 @@ fn test_particle_with_drag() {
     let dt = 0.01;
     let steps = 100;
 
+    let mut t = 0.0;
     // Integrate for 1 second total
     for _ in 0..steps {
-        solver.solve_step(&mut particle, dt);
+        solver.solve_step(&mut particle, &mut t, dt);
     }
 
     // Analytical solution for dv/dt = -k*v:
@@ -136,13 +141,178 @@ fn test_system_solver_genericity() {
     // Test with Euler
     let mut system_euler = GenericSystem::<Euler> { x: Var::new(0.0) };
     let solver_euler = Euler;
-    solver_euler.solve_step(&mut system_euler, 0.1);
+    let mut t = 0.0;
+    solver_euler.solve_step(&mut system_euler, &mut t, 0.1);
     assert!((*system_euler.x - 0.1).abs() < 1e-6);
 
     // Test with RK4
     let mut system_rk4 = GenericSystem::<Rk4> { x: Var::new(0.0) };
     let solver_rk4 = Rk4;
-    solver_rk4.solve_step(&mut system_rk4, 0.1);
+    let mut t = 0.0;
+    solver_rk4.solve_step(&mut system_rk4, &mut t, 0.1);
     // RK4 should be exact for constant derivative
     assert!((*system_rk4.x - 0.1).abs() < 1e-6);
 }
+
+/// System that records times at which its derivatives are computed.
+struct TimeRecorder<S: Solver> {
+    x: Var<f32, S>,
+    times: Vec<f32>,
+}
+
+impl<S: Solver> System<S> for TimeRecorder<S> {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        self.times.push(ctx.time());
+        self.x.deriv = 1.0;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Take a single step from `t = 1.0` with `dt = 0.5` and return the recorded stage times.
+fn stage_times<S: Solver>(solver: &S) -> Vec<f32> {
+    let mut system = TimeRecorder::<S> {
+        x: Var::new(0.0),
+        times: Vec::new(),
+    };
+    let mut t = 1.0;
+    solver.solve_step(&mut system, &mut t, 0.5);
+    assert_eq!(t, 1.5, "Time was not advanced by the step");
+    system.times
+}
+
+/// Test that contexts report the time of the current stage.
+#[test]
+fn test_context_stage_times() {
+    assert_eq!(stage_times(&Euler), [1.0]);
+    assert_eq!(stage_times(&Rk4), [1.0, 1.25, 1.25, 1.5]);
+    assert_eq!(stage_times(&Verlet), [1.0, 1.5]);
+    assert_eq!(stage_times(&ImplicitEuler::default())[0], 1.5);
+    assert_eq!(
+        stage_times(&ButcherRk::<Kutta3>::default()),
+        [1.0, 1.25, 1.5]
+    );
+}
+
+/// Test that adaptive steps advance time by the step actually taken.
+#[test]
+fn test_adaptive_step_time() {
+    let mut system = TimeRecorder::<Dopri5> {
+        x: Var::new(0.0),
+        times: Vec::new(),
+    };
+    let mut t = 2.0;
+    let step = Dopri5::default().adaptive_step(&mut system, &mut t, 0.1);
+    assert_eq!(t, 2.0 + step.taken);
+    assert_eq!(system.times.first(), Some(&2.0));
+    assert_eq!(system.times.last(), Some(&t));
+}
+
+/// Non-autonomous system dx/dt = cos(t) with solution x(t) = sin(t).
+struct Driven<S: Solver> {
+    x: Var<f32, S>,
+}
+
+impl<S: Solver> System<S> for Driven<S> {
+    fn compute_derivs(&mut self, ctx: &S::Context) {
+        self.x.deriv = ctx.time().cos();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply(&mut self.x);
+    }
+}
+
+/// Test that RK4 integrates explicitly time-dependent derivatives with fourth-order accuracy.
+#[test]
+fn test_non_autonomous_system() {
+    let mut system = Driven::<Rk4> { x: Var::new(0.0) };
+    let mut t = 0.0;
+    for _ in 0..20 {
+        Rk4.solve_step(&mut system, &mut t, 0.1);
+    }
+
+    assert!((t - 2.0).abs() < 1e-5);
+    let error = (*system.x - 2.0f32.sin()).abs();
+    assert!(error < 1e-6, "Error too large: {}", error);
+}
+
+/// Chain of independent oscillators with different frequencies stored in single variables.
+struct OscillatorArray<S: Solver> {
+    pos: Var<[Vec2; 32], S>,
+    vel: Var<[Vec2; 32], S>,
+}
+
+/// Single oscillator from the array.
+struct Oscillator<S: Solver> {
+    pos: Var<Vec2, S>,
+    vel: Var<Vec2, S>,
+    omega: f32,
+}
+
+fn omega(index: usize) -> f32 {
+    1.0 + 0.1 * index as f32
+}
+
+impl<S: Solver> System<S> for OscillatorArray<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        for i in 0..32 {
+            self.pos.deriv[i] = self.vel[i];
+            self.vel.deriv[i] = -omega(i).powi(2) * self.pos[i];
+        }
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+impl<S: Solver> System<S> for Oscillator<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.pos.deriv = *self.vel;
+        self.vel.deriv = -self.omega.powi(2) * *self.pos;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.pos, &mut self.vel);
+    }
+}
+
+/// Test that array variables are stepped element-wise by every solver.
+#[test]
+fn test_array_var_all_solvers() {
+    fn check<S: Solver>(solver: S) {
+        let init = |i: usize| (Vec2::new(1.0, i as f32 / 32.0), Vec2::new(0.0, 0.5));
+        let mut array = OscillatorArray::<S> {
+            pos: Var::new(core::array::from_fn(|i| init(i).0)),
+            vel: Var::new(core::array::from_fn(|i| init(i).1)),
+        };
+        let mut t = 0.0;
+        for _ in 0..50 {
+            solver.solve_step(&mut array, &mut t, 0.02);
+        }
+
+        for i in 0..32 {
+            let mut single = Oscillator::<S> {
+                pos: Var::new(init(i).0),
+                vel: Var::new(init(i).1),
+                omega: omega(i),
+            };
+            let mut t = 0.0;
+            for _ in 0..50 {
+                solver.solve_step(&mut single, &mut t, 0.02);
+            }
+            assert!(array.pos[i].abs_diff_eq(*single.pos, 1e-6));
+            assert!(array.vel[i].abs_diff_eq(*single.vel, 1e-6));
+        }
+    }
+
+    check(Euler);
+    check(Rk4);
+    check(Verlet);
+    check(Dopri5::default());
+    check(ButcherRk::<Kutta3>::default());
+    check(ImplicitEuler::default());
+}
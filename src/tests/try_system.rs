@@ -0,0 +1,139 @@
+//! Tests for systems with fallible evaluation of derivatives.
+
+#[cfg(feature = "alloc")]
+use crate::ImplicitEuler;
+use crate::{Dopri5, Euler, Rk4, Rot2, Solver, System, TrySystem, Var, Verlet, Visitor};
+
+/// Error of the gas model.
+#[derive(Debug, PartialEq)]
+struct Collapsed;
+
+/// Gas compressed by a falling piston, with a rotating valve.
+///
+/// Pressure is not defined at non-positive volumes.
+struct Piston<S: Solver> {
+    volume: Var<f32, S>,
+    speed: Var<f32, S>,
+    valve: Var<Rot2, S>,
+    evaluations: usize,
+}
+
+impl<S: Solver> Piston<S> {
+    fn new(volume: f32, speed: f32) -> Self {
+        Self {
+            volume: Var::new(volume),
+            speed: Var::new(speed),
+            valve: Var::new(Rot2::from_angle(0.5)),
+            evaluations: 0,
+        }
+    }
+
+    fn state(&self) -> (f32, f32, f32) {
+        (*self.volume, *self.speed, self.valve.angle())
+    }
+
+    fn derivs(&self) -> (f32, f32, f32) {
+        (self.volume.deriv, self.speed.deriv, self.valve.deriv)
+    }
+
+    /// Set derivatives, returning whether the state is within the domain.
+    fn set_derivs(&mut self) -> Result<(), Collapsed> {
+        self.evaluations += 1;
+        // Derivatives set before the error are discarded
+        self.valve.deriv = 1.0;
+        if *self.volume <= 0.0 {
+            return Err(Collapsed);
+        }
+        self.volume.deriv = *self.speed;
+        self.speed.deriv = 0.1 / *self.volume - 1.0;
+        Ok(())
+    }
+}
+
+impl<S: Solver> TrySystem<S> for Piston<S> {
+    type Error = Collapsed;
+
+    fn try_compute_derivs(&mut self, _: &S::Context) -> Result<(), Collapsed> {
+        self.set_derivs()
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.volume, &mut self.speed);
+        visitor.apply(&mut self.valve);
+    }
+}
+
+/// The same model which panics outside of its domain.
+struct InfalliblePiston<S: Solver>(Piston<S>);
+
+impl<S: Solver> System<S> for InfalliblePiston<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.0.set_derivs().unwrap();
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        TrySystem::visit_vars(&mut self.0, visitor);
+    }
+}
+
+/// Step the piston until a step fails, then check that the state is restored.
+fn check_restore<S: Solver>(solver: &S, dt: f32) {
+    let mut piston = Piston::<S>::new(1.0, -1.0);
+    let mut reference = InfalliblePiston(Piston::<S>::new(1.0, -1.0));
+    let (mut t, mut t_reference) = (0.0, 0.0);
+
+    // Successful steps are the same as the infallible ones
+    for _ in 0..5 {
+        solver.try_solve_step(&mut piston, &mut t, 0.05).unwrap();
+        solver.solve_step(&mut reference, &mut t_reference, 0.05);
+        assert_eq!(t, t_reference);
+        assert_eq!(piston.state(), reference.0.state());
+    }
+
+    let before = piston.state();
+    piston.evaluations = 0;
+    let result = solver.try_solve_step(&mut piston, &mut t, dt);
+    assert_eq!(result, Err(Collapsed));
+    // The step is interrupted at a later stage
+    assert!(piston.evaluations > 1, "{}", piston.evaluations);
+    assert_eq!(t, t_reference);
+    assert_eq!(piston.state(), before);
+    assert_eq!(piston.derivs(), (0.0, 0.0, 0.0));
+
+    // Retrying with a smaller step continues the same trajectory
+    solver.try_solve_step(&mut piston, &mut t, 0.05).unwrap();
+    solver.solve_step(&mut reference, &mut t_reference, 0.05);
+    assert_eq!(piston.state(), reference.0.state());
+}
+
+/// Test that every solver propagates errors and restores the state.
+#[test]
+fn test_try_solve_step_restore() {
+    check_restore(&Rk4, 2.0);
+    check_restore(&Dopri5::default(), 2.0);
+    check_restore(&Verlet, 2.0);
+    #[cfg(feature = "alloc")]
+    check_restore(&ImplicitEuler::default(), 2.0);
+}
+
+/// Test an error at the first evaluation, before any variable is changed.
+#[test]
+fn test_try_solve_step_first_evaluation() {
+    let mut piston = Piston::<Euler>::new(-0.5, 1.0);
+    let mut t = 1.0;
+    assert_eq!(
+        Euler.try_solve_step(&mut piston, &mut t, 0.1),
+        Err(Collapsed)
+    );
+    assert_eq!(piston.evaluations, 1);
+    assert_eq!(t, 1.0);
+    assert_eq!(piston.state(), (-0.5, 1.0, 0.5));
+    assert_eq!(piston.derivs(), (0.0, 0.0, 0.0));
+
+    // Storage of the previous step is not used
+    let mut piston = Piston::<Rk4>::new(1.0, 0.0);
+    Rk4.try_solve_step(&mut piston, &mut t, 0.1).unwrap();
+    *piston.volume = -1.0;
+    assert_eq!(Rk4.try_solve_step(&mut piston, &mut t, 0.1), Err(Collapsed));
+    assert_eq!(*piston.volume, -1.0);
+}
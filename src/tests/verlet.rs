@@ -0,0 +1,243 @@
+//! Tests for the velocity Verlet solver.
+
+use crate::{Euler, Rk4, Rot2, Rot3, Solver, System, Var, Verlet, Visitor};
+use glam::{Vec2, Vec3};
+
+/// Harmonic oscillator declared as a position-velocity pair.
+struct PairedOscillator<S: Solver> {
+    x: Var<f32, S>,
+    v: Var<f32, S>,
+}
+
+impl<S: Solver> PairedOscillator<S> {
+    fn new(x: f32, v: f32) -> Self {
+        Self {
+            x: Var::new(x),
+            v: Var::new(v),
+        }
+    }
+
+    fn energy(&self) -> f32 {
+        0.5 * (*self.x * *self.x + *self.v * *self.v)
+    }
+}
+
+impl<S: Solver> System<S> for PairedOscillator<S> {
+    fn compute_derivs(&mut self, _: &S::Context) {
+        self.x.deriv = *self.v;
+        self.v.deriv = -*self.x;
+    }
+
+    fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+        visitor.apply_pair(&mut self.x, &mut self.v);
+    }
+}
+
+/// Test that Verlet is exact for constant acceleration.
+#[test]
+fn test_verlet_constant_acceleration() {
+    struct Falling<S: Solver> {
+        pos: Var<Vec3, S>,
+        vel: Var<Vec3, S>,
+    }
+
+    impl<S: Solver> System<S> for Falling<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.pos.deriv = *self.vel;
+            self.vel.deriv = Vec3::new(0.0, 0.0, -10.0);
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply_pair(&mut self.pos, &mut self.vel);
+        }
+    }
+
+    let mut system = Falling::<Verlet> {
+        pos: Var::new(Vec3::ZERO),
+        vel: Var::new(Vec3::new(1.0, 0.0, 5.0)),
+    };
+    let mut t = 0.0;
+    for _ in 0..10 {
+        Verlet.solve_step(&mut system, &mut t, 0.1);
+    }
+
+    // x = v0 * t + a * t^2 / 2
+    let expected_pos = Vec3::new(1.0, 0.0, 5.0 - 5.0);
+    let expected_vel = Vec3::new(1.0, 0.0, 5.0 - 10.0);
+    assert!((*system.pos - expected_pos).length() < 1e-5);
+    assert!((*system.vel - expected_vel).length() < 1e-5);
+
+    // Derivatives are reset after the step
+    assert_eq!(system.pos.deriv, Vec3::ZERO);
+    assert_eq!(system.vel.deriv, Vec3::ZERO);
+}
+
+/// Test second-order convergence of Verlet.
+#[test]
+fn test_verlet_convergence() {
+    let total_time = 2.0;
+    let mut errors = Vec::new();
+    for dt in [0.1, 0.05, 0.025] {
+        let mut system = PairedOscillator::<Verlet>::new(1.0, 0.0);
+        let mut t = 0.0;
+        for _ in 0..(total_time / dt) as usize {
+            Verlet.solve_step(&mut system, &mut t, dt);
+        }
+        errors.push((*system.x - total_time.cos()).abs());
+    }
+
+    for pair in errors.windows(2) {
+        let ratio = pair[0] / pair[1];
+        assert!(
+            (ratio - 4.0).abs() < 1.0,
+            "Not second-order: errors={:?}",
+            errors
+        );
+    }
+}
+
+/// Test that Verlet keeps energy bounded over a long run while RK4 drifts.
+#[test]
+fn test_verlet_energy_conservation() {
+    let dt = 0.5;
+    let steps = 2000;
+
+    let mut verlet = PairedOscillator::<Verlet>::new(1.0, 0.0);
+    let mut rk4 = PairedOscillator::<Rk4>::new(1.0, 0.0);
+    let initial_energy = verlet.energy();
+
+    let mut max_verlet_error = 0.0f32;
+    let (mut t_verlet, mut t_rk4) = (0.0, 0.0);
+    for _ in 0..steps {
+        Verlet.solve_step(&mut verlet, &mut t_verlet, dt);
+        Rk4.solve_step(&mut rk4, &mut t_rk4, dt);
+        max_verlet_error =
+            max_verlet_error.max((verlet.energy() - initial_energy).abs() / initial_energy);
+    }
+
+    let rk4_error = (rk4.energy() - initial_energy).abs() / initial_energy;
+    assert!(
+        max_verlet_error < 0.1,
+        "Verlet energy error: {}",
+        max_verlet_error
+    );
+    assert!(
+        rk4_error > max_verlet_error,
+        "RK4 energy error {} is less than Verlet {}",
+        rk4_error,
+        max_verlet_error
+    );
+}
+
+/// Test Kepler orbit: radius and angular momentum are preserved.
+#[test]
+fn test_verlet_orbit() {
+    struct Orbit<S: Solver> {
+        pos: Var<Vec2, S>,
+        vel: Var<Vec2, S>,
+    }
+
+    impl<S: Solver> System<S> for Orbit<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.pos.deriv = *self.vel;
+            self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply_pair(&mut self.pos, &mut self.vel);
+        }
+    }
+
+    let mut system = Orbit::<Verlet> {
+        pos: Var::new(Vec2::new(1.0, 0.0)),
+        vel: Var::new(Vec2::new(0.0, 1.2)),
+    };
+    let angular_momentum = |s: &Orbit<Verlet>| s.pos.perp_dot(*s.vel);
+    let initial = angular_momentum(&system);
+
+    let mut t = 0.0;
+    for _ in 0..10000 {
+        Verlet.solve_step(&mut system, &mut t, 0.01);
+        assert!(system.pos.length() < 3.0);
+    }
+    assert!((angular_momentum(&system) - initial).abs() < 1e-3);
+}
+
+/// Test rotations paired with angular velocities.
+#[test]
+fn test_verlet_rotations() {
+    struct Spinner<S: Solver> {
+        angle: Var<Rot2, S>,
+        omega: Var<f32, S>,
+        orientation: Var<Rot3, S>,
+        angular_vel: Var<Vec3, S>,
+    }
+
+    impl<S: Solver> System<S> for Spinner<S> {
+        fn compute_derivs(&mut self, _: &S::Context) {
+            self.angle.deriv = *self.omega;
+            self.omega.deriv = 1.0;
+            self.orientation.deriv = *self.angular_vel;
+            self.angular_vel.deriv = Vec3::ZERO;
+        }
+
+        fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+            visitor.apply_pair(&mut self.angle, &mut self.omega);
+            visitor.apply_pair(&mut self.orientation, &mut self.angular_vel);
+        }
+    }
+
+    let mut system = Spinner::<Verlet> {
+        angle: Var::new(Rot2::from_angle(0.0)),
+        omega: Var::new(0.0),
+        orientation: Var::new(Rot3::default()),
+        angular_vel: Var::new(Vec3::Z),
+    };
+    let dt = core::f32::consts::FRAC_PI_2 / 10.0;
+    let mut t = 0.0;
+    for _ in 0..10 {
+        Verlet.solve_step(&mut system, &mut t, dt);
+    }
+
+    // Constant angular acceleration: angle = t^2 / 2
+    let t = core::f32::consts::FRAC_PI_2;
+    assert!((system.angle.angle() - 0.5 * t * t).abs() < 1e-5);
+    assert!((*system.omega - t).abs() < 1e-5);
+
+    // Constant angular velocity: quarter turn around Z
+    assert!((system.orientation.transform(Vec3::X) - Vec3::Y).length() < 1e-5);
+}
+
+/// Test that variables outside of pairs are integrated with second order.
+#[test]
+fn test_verlet_unpaired_variables() {
+    let mut system = crate::tests::system::ParticleWithDrag::<Verlet>::new(
+        Vec2::ZERO,
+        Vec2::new(10.0, 0.0),
+        0.5,
+    );
+    let mut system_euler =
+        crate::tests::system::ParticleWithDrag::<Euler>::new(Vec2::ZERO, Vec2::new(10.0, 0.0), 0.5);
+    let (mut t_verlet, mut t_euler) = (0.0, 0.0);
+    for _ in 0..10 {
+        Verlet.solve_step(&mut system, &mut t_verlet, 0.1);
+        Euler.solve_step(&mut system_euler, &mut t_euler, 0.1);
+    }
+
+    let expected_vel = 10.0 * (-0.5f32).exp();
+    let error = (system.velocity.x - expected_vel).abs();
+    let error_euler = (system_euler.velocity.x - expected_vel).abs();
+    assert!(error < error_euler / 10.0, "Heun error: {}", error);
+}
+
+/// Test that paired systems still work with other solvers.
+#[test]
+fn test_pairs_with_other_solvers() {
+    let mut system = PairedOscillator::<Rk4>::new(1.0, 0.0);
+    let mut t = 0.0;
+    for _ in 0..100 {
+        Rk4.solve_step(&mut system, &mut t, 0.01);
+    }
+    assert!((*system.x - 1.0f32.cos()).abs() < 1e-5);
+    assert!((*system.v + 1.0f32.sin()).abs() < 1e-5);
+}
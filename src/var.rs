@@ -1,4 +1,6 @@
-use crate::{Param, Solver};
+use crate::{Param, Scalar, Solver, Tolerance, Vars, Visitor};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug, Formatter},
     ops::{Deref, DerefMut},
@@ -19,6 +21,12 @@ use core::{
 /// - `deriv`: Current derivative (rate of change) of the variable.
 /// - `storage`: Solver-specific storage for intermediate computations.
 ///
+/// Variables also keep an optional error tolerance used by adaptive solvers, which is
+/// accessed with [`tolerance`](Self::tolerance) and [`set_tolerance`](Self::set_tolerance).
+///
+/// With the `serde` feature, all fields including the solver storage are serialized,
+/// so a variable saved between steps continues its trajectory when loaded.
+///
 /// # Example
 /// ```
 /// use phy::{Var, Euler};
@@ -36,7 +44,15 @@ use core::{
 /// // Set the derivative (velocity)
 /// position.deriv = Vec2::new(1.0, 0.0);
 /// ```
-pub struct Var<P: Param, S: Solver + ?Sized> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, P::Scalar: serde::Serialize, P::Deriv: serde::Serialize, S::Storage<P>: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, P::Scalar: serde::Deserialize<'de>, P::Deriv: serde::Deserialize<'de>, S::Storage<P>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Var<P: Param, S: Solver<P::Scalar> + ?Sized> {
     /// The current value of the variable.
     pub value: P,
     /// The derivative (rate of change) of the variable.
@@ -46,19 +62,29 @@ pub struct Var<P: Param, S: Solver + ?Sized> {
     /// This storage is used by solvers like RK4 to hold temporary values
     /// between integration stages. For Euler method, this is `()`.
     pub storage: S::Storage<P>,
+    /// Error tolerance for this variable.
+    tolerance: Option<Tolerance<P::Scalar>>,
 }
 
-impl<P: Param, S: Solver> Clone for Var<P, S> {
+impl<P: Param, S: Solver<P::Scalar>> Clone for Var<P, S> {
     fn clone(&self) -> Self {
         Self {
             value: self.value.clone(),
             deriv: self.deriv.clone(),
             storage: self.storage.clone(),
+            tolerance: self.tolerance,
         }
     }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.value.clone_from(&source.value);
+        self.deriv.clone_from(&source.deriv);
+        self.storage.clone_from(&source.storage);
+        self.tolerance = source.tolerance;
+    }
 }
 
-impl<P: Param, S: Solver> Copy for Var<P, S>
+impl<P: Param, S: Solver<P::Scalar>> Copy for Var<P, S>
 where
     P: Copy,
     P::Deriv: Copy,
@@ -66,7 +92,7 @@ where
 {
 }
 
-impl<P: Param, S: Solver> Default for Var<P, S> {
+impl<P: Param, S: Solver<P::Scalar>> Default for Var<P, S> {
     /// Create a variable with default values.
     ///
     /// The value and derivative are set to their type's default,
@@ -76,28 +102,51 @@ impl<P: Param, S: Solver> Default for Var<P, S> {
             value: P::default(),
             deriv: P::Deriv::default(),
             storage: S::Storage::<P>::default(),
+            tolerance: None,
         }
     }
 }
 
-impl<P: Param, S: Solver> Var<P, S> {
+impl<P: Param, S: Solver<P::Scalar>> Var<P, S> {
     /// Create a new variable with the given initial value.
     ///
-    /// The derivative is set to its default value, and storage
+    /// The derivative is set to zero (see [`Param::zero_deriv`]), and storage
     /// is initialized using `Default::default()`.
     ///
     /// # Arguments
     /// * `value` - Initial value for the variable.
     pub fn new(value: P) -> Self {
         Var {
+            deriv: value.zero_deriv(),
             value,
-            deriv: Default::default(),
             storage: Default::default(),
+            tolerance: None,
         }
     }
+
+    /// Set error tolerance for this variable.
+    ///
+    /// The tolerance overrides the solver's default one for adaptive solvers
+    /// and is ignored by fixed-step solvers.
+    pub fn with_tolerance(mut self, tolerance: Tolerance<P::Scalar>) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Error tolerance of this variable.
+    ///
+    /// Adaptive solvers use their own default tolerance if this is `None`.
+    pub fn tolerance(&self) -> Option<Tolerance<P::Scalar>> {
+        self.tolerance
+    }
+
+    /// Set or reset error tolerance of this variable, see [`with_tolerance`](Self::with_tolerance).
+    pub fn set_tolerance(&mut self, tolerance: Option<Tolerance<P::Scalar>>) {
+        self.tolerance = tolerance;
+    }
 }
 
-impl<P: Param, S: Solver> Deref for Var<P, S> {
+impl<P: Param, S: Solver<P::Scalar>> Deref for Var<P, S> {
     type Target = P;
 
     /// Provides immutable access to the variable's value.
@@ -118,7 +167,7 @@ impl<P: Param, S: Solver> Deref for Var<P, S> {
     }
 }
 
-impl<P: Param, S: Solver> DerefMut for Var<P, S> {
+impl<P: Param, S: Solver<P::Scalar>> DerefMut for Var<P, S> {
     /// Provides mutable access to the variable's value.
     ///
     /// This allows treating `Var<P, S>` as if it were `P` for modification.
@@ -137,7 +186,7 @@ impl<P: Param, S: Solver> DerefMut for Var<P, S> {
     }
 }
 
-impl<P: Param, S: Solver> Debug for Var<P, S>
+impl<P: Param, S: Solver<P::Scalar>> Debug for Var<P, S>
 where
     P: Debug,
     P::Deriv: Debug,
@@ -145,12 +194,43 @@ where
 {
     /// Formats the variable for debugging.
     ///
-    /// Shows the value, derivative, and storage fields.
+    /// Shows the value, derivative, storage and tolerance fields.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Var {{ value: {:?}, deriv: {:?}, storage: {:?} }}",
-            &self.value, &self.deriv, &self.storage
+            "Var {{ value: {:?}, deriv: {:?}, storage: {:?}, tolerance: {:?} }}",
+            &self.value, &self.deriv, &self.storage, &self.tolerance
         )
     }
 }
+
+impl<P: Param, S: Solver<P::Scalar>> Vars<S, P::Scalar> for Var<P, S> {
+    /// Apply the visitor to this variable.
+    fn visit_vars<V: Visitor<S, P::Scalar>>(&mut self, visitor: &mut V) {
+        visitor.apply(self);
+    }
+}
+
+/// Visit variables in a slice, e.g. particles of a system.
+///
+/// The slice may be of different length at each step, but not within a step.
+impl<S: Solver<T>, T: Scalar, V: Vars<S, T>> Vars<S, T> for [V] {
+    fn visit_vars<U: Visitor<S, T>>(&mut self, visitor: &mut U) {
+        for vars in self {
+            vars.visit_vars(visitor);
+        }
+    }
+}
+
+impl<S: Solver<T>, T: Scalar, V: Vars<S, T>, const N: usize> Vars<S, T> for [V; N] {
+    fn visit_vars<U: Visitor<S, T>>(&mut self, visitor: &mut U) {
+        self.as_mut_slice().visit_vars(visitor);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Solver<T>, T: Scalar, V: Vars<S, T>> Vars<S, T> for Vec<V> {
+    fn visit_vars<U: Visitor<S, T>>(&mut self, visitor: &mut U) {
+        self.as_mut_slice().visit_vars(visitor);
+    }
+}
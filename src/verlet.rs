@@ -0,0 +1,206 @@
+use crate::{Context, Deriv, Param, Scalar, Solver, System, Var, Visitor};
+
+/// The velocity Verlet method for second-order systems.
+///
+/// Velocity Verlet (also known as leapfrog in its kick-drift-kick form) is a
+/// second-order symplectic integrator. For conservative systems it does not
+/// accumulate energy error over time, which makes it suitable for long runs of
+/// orbital and molecular dynamics.
+///
+/// Splitting methods of other orders are provided by [`Symplectic`](crate::Symplectic).
+///
+/// # Position-Velocity Pairs
+///
+/// The method needs to know which variables are positions and which are
+/// velocities. Systems declare this by visiting them together with
+/// [`Visitor::apply_pair`]. The derivative of the velocity variable is the
+/// acceleration, while the derivative of the position variable is ignored:
+/// positions are advanced using the velocity value itself.
+///
+/// Variables visited with plain [`Visitor::apply`] are integrated with
+/// Heun's method, which is also second-order but not symplectic.
+///
+/// # Algorithm
+///
+/// For a position `x` with velocity `v` and acceleration `a(x)`:
+/// ```text
+/// v_{n+1/2} = v_n + a(x_n) * h/2
+/// x_{n+1} = x_n + v_{n+1/2} * h
+/// v_{n+1} = v_{n+1/2} + a(x_{n+1}) * h/2
+/// ```
+///
+/// The acceleration is evaluated with velocities at the half step, so the
+/// method stays symplectic only if acceleration does not depend on velocity.
+///
+/// # Example
+/// ```
+/// use phy::{Solver, System, Var, Verlet, Visitor};
+/// use glam::Vec2;
+///
+/// struct Planet<S: Solver> {
+///     pos: Var<Vec2, S>,
+///     vel: Var<Vec2, S>,
+/// }
+///
+/// impl<S: Solver> System<S> for Planet<S> {
+///     fn compute_derivs(&mut self, _: &S::Context) {
+///         self.pos.deriv = *self.vel;
+///         self.vel.deriv = -*self.pos / self.pos.length().powi(3);
+///     }
+///
+///     fn visit_vars<V: Visitor<S>>(&mut self, visitor: &mut V) {
+///         visitor.apply_pair(&mut self.pos, &mut self.vel);
+///     }
+/// }
+///
+/// let mut planet = Planet::<Verlet> {
+///     pos: Var::new(Vec2::X),
+///     vel: Var::new(Vec2::Y),
+/// };
+/// let mut t = 0.0;
+/// for _ in 0..1000 {
+///     Verlet.solve_step(&mut planet, &mut t, 0.01);
+/// }
+/// // Circular orbit keeps its radius
+/// assert!((planet.pos.length() - 1.0).abs() < 1e-3);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Verlet;
+
+/// Storage required by the Verlet solver for each variable.
+///
+/// Holds the value and derivative at the beginning of the step. Variables of
+/// position-velocity pairs only keep their values, to restore an interrupted step.
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "P: serde::Serialize, P::Deriv: serde::Serialize",
+        deserialize = "P: serde::Deserialize<'de>, P::Deriv: serde::Deserialize<'de>"
+    ))
+)]
+pub struct VerletStorage<P: Param> {
+    /// The initial value at the beginning of the step (y_n)
+    init_value: P,
+    /// Derivative at the beginning of the step (k1)
+    init_deriv: P::Deriv,
+}
+
+/// The two stages of the Verlet algorithm.
+#[derive(Clone, Copy)]
+enum VerletStage {
+    /// Half kick and drift using accelerations at the beginning of the step.
+    Stage1,
+    /// Half kick using accelerations at the end of the step.
+    Stage2,
+}
+
+/// Visitor that applies a single Verlet stage to variables.
+pub struct VerletStep<T: Scalar = f32> {
+    stage: VerletStage,
+    /// Time at the beginning of the step (t_n)
+    t: T,
+    dt: T,
+}
+
+impl<T: Scalar> Context<Verlet, T> for VerletStep<T> {
+    /// Both stages evaluate derivatives at the ends of the step.
+    fn time(&self) -> T {
+        match self.stage {
+            VerletStage::Stage1 => self.t,
+            VerletStage::Stage2 => self.t + self.dt,
+        }
+    }
+
+    fn time_step(&self) -> T {
+        self.dt
+    }
+}
+
+impl<T: Scalar> Visitor<Verlet, T> for VerletStep<T> {
+    /// Integrate a standalone variable using Heun's method.
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Verlet>) {
+        let storage = &mut var.storage;
+        match self.stage {
+            VerletStage::Stage1 => {
+                // Save y_n and k1, predict y_{n+1} = y_n + k1 * dt
+                storage.init_value.clone_from(&var.value);
+                storage.init_deriv.clone_from(&var.deriv);
+                var.value.step(&var.deriv, self.dt);
+            }
+            VerletStage::Stage2 => {
+                // Correct: y_{n+1} = y_n + (k1 + k2) * dt / 2
+                var.deriv.add_scaled(&storage.init_deriv, T::ONE);
+                var.deriv.scale(T::from_f32(0.5));
+                var.value.clone_from(&storage.init_value);
+                var.value.step(&var.deriv, self.dt);
+            }
+        }
+
+        // Reset derivative for next stage
+        var.deriv = var.value.zero_deriv();
+    }
+
+    /// Integrate a position-velocity pair using velocity Verlet.
+    fn apply_pair<P: Param<Scalar = T>>(
+        &mut self,
+        pos: &mut Var<P, Verlet>,
+        vel: &mut Var<P::Deriv, Verlet>,
+    ) where
+        P::Deriv: Param<Scalar = T>,
+    {
+        if let VerletStage::Stage1 = self.stage {
+            // Save x_n and v_n to be able to restore an interrupted step
+            pos.storage.init_value.clone_from(&pos.value);
+            vel.storage.init_value.clone_from(&vel.value);
+        }
+        // Half kick: v = v + a * dt / 2
+        vel.value.step(&vel.deriv, T::from_f32(0.5) * self.dt);
+        if let VerletStage::Stage1 = self.stage {
+            // Drift: x = x + v * dt
+            pos.value.step(&vel.value, self.dt);
+        }
+
+        // Reset derivatives for next stage
+        pos.deriv = pos.value.zero_deriv();
+        vel.deriv = vel.value.zero_deriv();
+    }
+}
+
+/// Visitor that restores variables to their values at the beginning of the step.
+struct VerletRestore;
+
+impl<T: Scalar> Visitor<Verlet, T> for VerletRestore {
+    fn apply<P: Param<Scalar = T>>(&mut self, var: &mut Var<P, Verlet>) {
+        var.value.clone_from(&var.storage.init_value);
+        var.deriv = var.value.zero_deriv();
+    }
+}
+
+impl<T: Scalar> Solver<T> for Verlet {
+    type Context = VerletStep<T>;
+    type Storage<P: Param<Scalar = T>> = VerletStorage<P>;
+
+    /// Perform one velocity Verlet step for the given system.
+    ///
+    /// Derivatives are computed twice per step: at the beginning
+    /// and after positions are moved to the end of the step.
+    fn solve_step<S: System<Self, T>>(&self, system: &mut S, t: &mut T, dt: T) {
+        for stage in [VerletStage::Stage1, VerletStage::Stage2] {
+            let mut step = VerletStep { stage, t: *t, dt };
+
+            // Compute accelerations at current positions
+            system.compute_derivs(&step);
+
+            // Apply the Verlet stage to all variables
+            system.visit_vars(&mut step);
+        }
+
+        *t += dt;
+    }
+
+    fn restore_step<S: System<Self, T>>(&self, system: &mut S) {
+        system.visit_vars(&mut VerletRestore);
+    }
+}